@@ -37,6 +37,7 @@ pub fn main() -> Result<(), JsValue> {
                 red: 0,
                 green: 0,
                 blue: 0,
+                alpha: 255,
             },
         }
     );
@@ -10,7 +10,7 @@ use rapier2d::geometry::ColliderBuilder;
 use rapier2d::math::Isometry;
 use rapier2d::prelude::{vector, Ball, RigidBodyBuilder};
 use winit::event::VirtualKeyCode;
-use alcubierre::game_object::graphics::SpriteData;
+use alcubierre::game_object::graphics::{BlendMode, SpriteData};
 
 use alcubierre::game_object::behaviours::EngineView;
 use alcubierre::game_object::behaviours::UserBehaviour;
@@ -81,7 +81,19 @@ pub fn register_main_scene(mut flame: &mut Engine) {
             height: 1.0,
             sprite_id: "tile003".to_string(),
             flip_h: false,
-            flip_v: false
+            flip_v: false,
+            atlas: None,
+            pivot: None,
+            tint: RGBColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+                alpha: 255,
+            },
+            opacity: 1.0,
+            flash: 0.0,
+            blend_mode: BlendMode::Alpha,
+            material: None,
         }));
         // .graphics(GraphicsType::Circle(CircleData {
         //     radius: 1.0,
@@ -0,0 +1,562 @@
+use crate::game_object::graphics::RenderLayer;
+use crate::renderer::camera::OPENGL_TO_WGPU_MATRIX;
+use cgmath::{ortho, Matrix4, Rad, Vector3};
+use hashbrown::HashSet;
+use rand::Rng;
+
+/// World units visible from the bottom to the top of the view at zoom 1.
+pub const VIEW_HEIGHT: f32 = 14.0;
+
+/// Part of the window a camera draws to, as fractions of the window size from the top-left.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    /// Column `index` of `count` side by side columns, for split-screen.
+    pub fn column(index: u32, count: u32) -> Viewport {
+        let width = 1.0 / count.max(1) as f32;
+        Viewport::new(index as f32 * width, 0.0, width, 1.0)
+    }
+    /// Row `index` of `count` rows stacked from the top, for split-screen.
+    pub fn row(index: u32, count: u32) -> Viewport {
+        let height = 1.0 / count.max(1) as f32;
+        Viewport::new(0.0, index as f32 * height, 1.0, height)
+    }
+    /// Whether a window position in pixels is inside the viewport.
+    pub fn contains(&self, screen: [f32; 2], window: [f32; 2]) -> bool {
+        let [x, y, width, height] = self.to_pixels(window);
+        screen[0] >= x && screen[0] < x + width && screen[1] >= y && screen[1] < y + height
+    }
+    /// The viewport in pixels for a window of `window` size.
+    pub fn to_pixels(&self, window: [f32; 2]) -> [f32; 4] {
+        [
+            self.x * window[0],
+            self.y * window[1],
+            self.width * window[0],
+            self.height * window[1],
+        ]
+    }
+}
+
+/// How the design resolution is scaled to fill a window of a different size.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// As large as fits while keeping the aspect ratio, with bars filling the rest
+    #[default]
+    Fit,
+    /// Covers the whole window while keeping the aspect ratio, cutting off what pokes out
+    Fill,
+    /// Covers the whole window, squashing or stretching to match it
+    Stretch,
+    /// Like `Fit` but only by whole multiples, so every design pixel is the same size
+    Integer,
+}
+
+/// The window size a game is designed for. The world and the UI are laid out for it and
+/// then scaled to the real window with a `ScaleMode`, so the game looks the same at any
+/// window size. At zoom 1 cameras see `VIEW_HEIGHT` world units across the design height.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DesignResolution {
+    pub width: u32,
+    pub height: u32,
+    pub scale_mode: ScaleMode,
+}
+
+impl DesignResolution {
+    pub fn new(width: u32, height: u32, scale_mode: ScaleMode) -> DesignResolution {
+        DesignResolution {
+            width,
+            height,
+            scale_mode,
+        }
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        [self.width.max(1) as f32, self.height.max(1) as f32]
+    }
+
+    /// Window pixels per design pixel, horizontally and vertically. `Integer` only uses
+    /// whole numbers unless the window is smaller than the design, where it shrinks to fit.
+    pub fn scale(&self, window: [f32; 2]) -> [f32; 2] {
+        let [width, height] = self.size();
+        let (horizontal, vertical) = (window[0] / width, window[1] / height);
+        let uniform = match self.scale_mode {
+            ScaleMode::Fit => horizontal.min(vertical),
+            ScaleMode::Fill => horizontal.max(vertical),
+            ScaleMode::Stretch => return [horizontal, vertical],
+            ScaleMode::Integer => {
+                let fit = horizontal.min(vertical);
+                if fit >= 1.0 {
+                    fit.floor()
+                } else {
+                    fit
+                }
+            }
+        };
+        [uniform, uniform]
+    }
+
+    /// Design pixels showing in the window. The design size except with `Fill`, which cuts
+    /// off one side.
+    pub fn visible(&self, window: [f32; 2]) -> [f32; 2] {
+        let scale = self.scale(window);
+        let [width, height] = self.size();
+        match self.scale_mode {
+            ScaleMode::Fill => [window[0] / scale[0], window[1] / scale[1]],
+            _ => [width, height],
+        }
+    }
+
+    /// Where the game is drawn in window pixels, centred with bars around it for `Fit`
+    /// and `Integer`.
+    pub fn letterbox(&self, window: [f32; 2]) -> [f32; 4] {
+        let scale = self.scale(window);
+        let visible = self.visible(window);
+        let [width, height] = [visible[0] * scale[0], visible[1] * scale[1]].map(|length| length.round());
+        [
+            ((window[0] - width) * 0.5).floor(),
+            ((window[1] - height) * 0.5).floor(),
+            width,
+            height,
+        ]
+    }
+
+    /// Converts a window position in pixels to design pixels from the top-left of the
+    /// visible area.
+    pub fn window_to_design(&self, screen: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        let [x, y, width, height] = self.letterbox(window);
+        let visible = self.visible(window);
+        [
+            (screen[0] - x) * visible[0] / width.max(1.0),
+            (screen[1] - y) * visible[1] / height.max(1.0),
+        ]
+    }
+
+    /// Converts design pixels from the top-left of the visible area to a window position.
+    pub fn design_to_window(&self, position: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        let [x, y, width, height] = self.letterbox(window);
+        let visible = self.visible(window);
+        [
+            x + position[0] * width / visible[0],
+            y + position[1] * height / visible[1],
+        ]
+    }
+
+    /// World units from the bottom to the top of the visible area at zoom 1.
+    pub fn view_height(&self, window: [f32; 2]) -> f32 {
+        VIEW_HEIGHT * self.visible(window)[1] / self.size()[1]
+    }
+}
+
+/// Draws the world into a small virtual resolution and scales it up by whole pixels, with
+/// black bars filling the rest of the window, so pixel art stays crisp. Cameras see
+/// `height / pixels_per_unit` world units at zoom 1 and sprites snap to texels. The UI is
+/// drawn over the scaled-up image at the window's resolution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PixelPerfect {
+    pub width: u32,
+    pub height: u32,
+    /// Texels per world unit, e.g. 12 for 12x12 tiles one unit across
+    pub pixels_per_unit: f32,
+}
+
+impl PixelPerfect {
+    pub fn new(width: u32, height: u32, pixels_per_unit: f32) -> PixelPerfect {
+        PixelPerfect {
+            width,
+            height,
+            pixels_per_unit,
+        }
+    }
+
+    pub fn size(&self) -> [f32; 2] {
+        [self.width.max(1) as f32, self.height.max(1) as f32]
+    }
+
+    /// The virtual resolution scaled with `ScaleMode::Integer`.
+    pub fn design(&self) -> DesignResolution {
+        DesignResolution::new(self.width, self.height, ScaleMode::Integer)
+    }
+
+    /// World units visible from the bottom to the top of the view at zoom 1.
+    pub fn view_height(&self) -> f32 {
+        self.size()[1] / self.pixels_per_unit.max(f32::EPSILON)
+    }
+
+    /// Window pixels per virtual pixel. Whole numbers unless the window is smaller than the
+    /// virtual resolution, where it shrinks to fit.
+    pub fn scale(&self, window: [f32; 2]) -> f32 {
+        self.design().scale(window)[0]
+    }
+
+    /// The scaled-up image in window pixels, centred with bars around it.
+    pub fn letterbox(&self, window: [f32; 2]) -> [f32; 4] {
+        self.design().letterbox(window)
+    }
+
+    /// Converts a window position in pixels to a position in the virtual resolution, both
+    /// from the top-left.
+    pub fn window_to_virtual(&self, screen: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        self.design().window_to_design(screen, window)
+    }
+
+    /// Converts a position in the virtual resolution to a window position in pixels.
+    pub fn virtual_to_window(&self, position: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        self.design().design_to_window(position, window)
+    }
+
+    /// Size in world units of one texel seen through a camera at `zoom`.
+    pub fn texel(&self, zoom: f32) -> f32 {
+        1.0 / (self.pixels_per_unit * zoom).max(f32::EPSILON)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraFollow {
+    /// Id of the object to follow
+    pub target: u128,
+    /// How quickly the camera catches up. 0 snaps straight to the target.
+    pub smoothing: f32,
+    /// Size of the box around the centre of the view the target can move in without the
+    /// camera moving
+    pub deadzone: [f32; 2],
+    /// Added to the target's position, e.g. to look ahead of a player
+    pub offset: [f32; 2],
+}
+
+impl CameraFollow {
+    pub fn new(target: u128) -> CameraFollow {
+        CameraFollow {
+            target,
+            smoothing: 0.0,
+            deadzone: [0.0, 0.0],
+            offset: [0.0, 0.0],
+        }
+    }
+    pub fn smoothing(mut self, smoothing: f32) -> CameraFollow {
+        self.smoothing = smoothing;
+        self
+    }
+    pub fn deadzone(mut self, width: f32, height: f32) -> CameraFollow {
+        self.deadzone = [width, height];
+        self
+    }
+    pub fn offset(mut self, x: f32, y: f32) -> CameraFollow {
+        self.offset = [x, y];
+        self
+    }
+}
+
+/// Area of the world the view is kept inside.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraBounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct CameraShake {
+    intensity: f32,
+    duration: f32,
+    remaining: f32,
+}
+
+/// Where a scene's UI is drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UiMode {
+    /// Once over the whole window
+    #[default]
+    Global,
+    /// Once inside the viewport of every camera with `show_ui` set, laid out as if the
+    /// viewport were the window
+    PerViewport,
+}
+
+/// Changes to a scene's camera, sent from behaviours and scripts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraCommand {
+    SetPosition([f32; 2]),
+    SetZoom(f32),
+    SetRotation(f32),
+    SetViewport(Viewport),
+    Follow(CameraFollow),
+    /// Changes the dead zone of the current follow, if there is one
+    SetDeadzone([f32; 2]),
+    StopFollowing,
+    SetBounds(Option<CameraBounds>),
+    Shake { intensity: f32, duration: f32 },
+    SetLayerVisible(RenderLayer, bool),
+    SetShowUi(bool),
+}
+
+/// A scene's view of the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    /// World position at the centre of the view
+    pub position: [f32; 2],
+    /// 2 shows everything twice as large
+    pub zoom: f32,
+    /// Radians, counter-clockwise
+    pub rotation: f32,
+    pub viewport: Viewport,
+    pub follow: Option<CameraFollow>,
+    pub bounds: Option<CameraBounds>,
+    /// Layers this camera doesn't draw, e.g. everything but the world on a minimap
+    pub hidden_layers: HashSet<RenderLayer>,
+    /// Whether the UI is drawn in this camera's viewport with `UiMode::PerViewport`
+    pub show_ui: bool,
+    /// Name of the render target drawn into instead of the window. The viewport is then a
+    /// fraction of the target.
+    pub target: Option<String>,
+    /// World units across the height of the view at zoom 1, set by the engine from the
+    /// design resolution or pixel-perfect mode
+    pub(crate) view_height: f32,
+    /// Texels per world unit the view snaps to in pixel-perfect mode
+    pub(crate) pixels_per_unit: Option<f32>,
+    pub(crate) shake: Option<CameraShake>,
+    pub(crate) shake_offset: [f32; 2],
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Camera2D {
+            position: [0.0, 0.0],
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport: Viewport::default(),
+            follow: None,
+            bounds: None,
+            hidden_layers: HashSet::new(),
+            show_ui: true,
+            target: None,
+            view_height: VIEW_HEIGHT,
+            pixels_per_unit: None,
+            shake: None,
+            shake_offset: [0.0, 0.0],
+        }
+    }
+}
+
+impl Camera2D {
+    pub fn new() -> Camera2D {
+        Camera2D::default()
+    }
+
+    pub fn with_viewport(mut self, viewport: Viewport) -> Camera2D {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_zoom(mut self, zoom: f32) -> Camera2D {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Draws into the render target `name` instead of the window.
+    pub fn with_target(mut self, name: &str) -> Camera2D {
+        self.target = Some(name.to_string());
+        self
+    }
+
+    /// Only draws `layers`.
+    pub fn with_layers(mut self, layers: &[RenderLayer]) -> Camera2D {
+        self.hidden_layers = RenderLayer::ALL
+            .iter()
+            .filter(|layer| !layers.contains(layer))
+            .copied()
+            .collect();
+        self
+    }
+
+    pub fn set_layer_visible(&mut self, layer: RenderLayer, visible: bool) {
+        if visible {
+            self.hidden_layers.remove(&layer);
+        } else {
+            self.hidden_layers.insert(layer);
+        }
+    }
+
+    pub fn sees(&self, layer: RenderLayer) -> bool {
+        !self.hidden_layers.contains(&layer)
+    }
+
+    /// Shakes the view up to `intensity` world units, fading out over `duration` seconds.
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        self.shake = Some(CameraShake {
+            intensity,
+            duration,
+            remaining: duration,
+        });
+    }
+
+    pub fn apply(&mut self, command: CameraCommand) {
+        match command {
+            CameraCommand::SetPosition(position) => self.position = position,
+            CameraCommand::SetZoom(zoom) => self.zoom = zoom,
+            CameraCommand::SetRotation(rotation) => self.rotation = rotation,
+            CameraCommand::SetViewport(viewport) => self.viewport = viewport,
+            CameraCommand::Follow(follow) => self.follow = Some(follow),
+            CameraCommand::SetDeadzone(deadzone) => {
+                if let Some(follow) = &mut self.follow {
+                    follow.deadzone = deadzone;
+                }
+            }
+            CameraCommand::StopFollowing => self.follow = None,
+            CameraCommand::SetBounds(bounds) => self.bounds = bounds,
+            CameraCommand::Shake { intensity, duration } => self.shake(intensity, duration),
+            CameraCommand::SetLayerVisible(layer, visible) => self.set_layer_visible(layer, visible),
+            CameraCommand::SetShowUi(show_ui) => self.show_ui = show_ui,
+        }
+    }
+
+    /// Half the width and height of the view in world units.
+    pub fn half_extents(&self, aspect: f32) -> [f32; 2] {
+        let half_height = self.view_height * 0.5 / self.zoom.max(f32::EPSILON);
+        [half_height * aspect, half_height]
+    }
+
+    /// World area the view covers, as min x, min y, max x, max y. Rotated views give the
+    /// box around them.
+    pub fn view_bounds(&self, aspect: f32) -> [f32; 4] {
+        let [half_width, half_height] = self.half_extents(aspect);
+        let (sin, cos) = self.rotation.sin_cos();
+        let reach = [
+            half_width * cos.abs() + half_height * sin.abs(),
+            half_width * sin.abs() + half_height * cos.abs(),
+        ];
+        let eye = self.eye();
+        [eye[0] - reach[0], eye[1] - reach[1], eye[0] + reach[0], eye[1] + reach[1]]
+    }
+
+    /// Moves towards the followed object at `target`, keeps the view inside the bounds and
+    /// advances any shake by `delta` seconds.
+    pub fn update(&mut self, delta: f32, target: Option<[f32; 2]>, aspect: f32) {
+        if let (Some(follow), Some(target)) = (self.follow, target) {
+            let desired = [0, 1].map(|axis| {
+                let offset = target[axis] + follow.offset[axis] - self.position[axis];
+                let slack = follow.deadzone[axis] * 0.5;
+                if offset.abs() > slack {
+                    self.position[axis] + offset - slack * offset.signum()
+                } else {
+                    self.position[axis]
+                }
+            });
+            let t = if follow.smoothing > 0.0 {
+                1.0 - (-follow.smoothing * delta).exp()
+            } else {
+                1.0
+            };
+            self.position = [0, 1].map(|axis| self.position[axis] + (desired[axis] - self.position[axis]) * t);
+        }
+
+        if let Some(bounds) = self.bounds {
+            let half = self.half_extents(aspect);
+            self.position = [0, 1].map(|axis| {
+                let (min, max) = (bounds.min[axis] + half[axis], bounds.max[axis] - half[axis]);
+                if min > max {
+                    // The view is bigger than the bounds
+                    (bounds.min[axis] + bounds.max[axis]) * 0.5
+                } else {
+                    self.position[axis].clamp(min, max)
+                }
+            });
+        }
+
+        self.shake_offset = [0.0, 0.0];
+        if let Some(shake) = &mut self.shake {
+            shake.remaining -= delta;
+            if shake.remaining <= 0.0 {
+                self.shake = None;
+            } else {
+                let strength = shake.intensity * shake.remaining / shake.duration;
+                let mut rng = rand::thread_rng();
+                self.shake_offset = [
+                    rng.gen_range(-1.0..=1.0) * strength,
+                    rng.gen_range(-1.0..=1.0) * strength,
+                ];
+            }
+        }
+    }
+
+    /// Centre of the view including shake, on a texel in pixel-perfect mode.
+    fn eye(&self) -> [f32; 2] {
+        let eye = [
+            self.position[0] + self.shake_offset[0],
+            self.position[1] + self.shake_offset[1],
+        ];
+        match self.pixels_per_unit {
+            Some(pixels_per_unit) => {
+                let texel = 1.0 / (pixels_per_unit * self.zoom).max(f32::EPSILON);
+                eye.map(|axis| (axis / texel).round() * texel)
+            }
+            None => eye,
+        }
+    }
+
+    pub(crate) fn view_proj(&self, aspect: f32) -> Matrix4<f32> {
+        let [half_width, half_height] = self.half_extents(aspect);
+        let eye = self.eye();
+        OPENGL_TO_WGPU_MATRIX
+            * ortho(-half_width, half_width, -half_height, half_height, -1000.0, 1000.0)
+            * Matrix4::from_angle_z(Rad(-self.rotation))
+            * Matrix4::from_translation(Vector3::new(-eye[0], -eye[1], 0.0))
+    }
+
+    pub(crate) fn aspect(&self, window: [f32; 2]) -> f32 {
+        let [_, _, width, height] = self.viewport.to_pixels(window);
+        width / height.max(1.0)
+    }
+
+    /// Converts a window position in pixels, from the top-left, to a world position.
+    pub fn screen_to_world(&self, screen: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        let [x, y, width, height] = self.viewport.to_pixels(window);
+        let [half_width, half_height] = self.half_extents(self.aspect(window));
+        let view_x = ((screen[0] - x) / width * 2.0 - 1.0) * half_width;
+        let view_y = (1.0 - (screen[1] - y) / height * 2.0) * half_height;
+        let (sin, cos) = self.rotation.sin_cos();
+        let eye = self.eye();
+        [
+            eye[0] + view_x * cos - view_y * sin,
+            eye[1] + view_x * sin + view_y * cos,
+        ]
+    }
+
+    /// Converts a world position to a window position in pixels, from the top-left.
+    pub fn world_to_screen(&self, world: [f32; 2], window: [f32; 2]) -> [f32; 2] {
+        let [x, y, width, height] = self.viewport.to_pixels(window);
+        let [half_width, half_height] = self.half_extents(self.aspect(window));
+        let eye = self.eye();
+        let (dx, dy) = (world[0] - eye[0], world[1] - eye[1]);
+        let (sin, cos) = self.rotation.sin_cos();
+        let view_x = dx * cos + dy * sin;
+        let view_y = -dx * sin + dy * cos;
+        [
+            x + (view_x / half_width + 1.0) * 0.5 * width,
+            y + (1.0 - view_y / half_height) * 0.5 * height,
+        ]
+    }
+}
@@ -1,6 +1,11 @@
 use crate::audio::basic::AudioSource;
+use crate::camera::CameraCommand;
 use crate::game_object::behaviours::EngineView;
-use crate::game_object::{GameObject, GameObjectIPC, GameObjectView};
+use crate::game_object::events::UserEvent;
+use crate::game_object::graphics::RenderLayer;
+use crate::game_object::GameObject;
+use crate::timers::{Timer, TimerHandle};
+use crate::tween::{Tween, TweenHandle};
 use crate::Engine;
 use rapier2d::geometry::ColliderHandle;
 use std::time::Duration;
@@ -17,16 +22,30 @@ pub enum EngineEvent {
     RemoveDatamapValue(String),
     PlaySound(AudioSource),
     PullGameObject(PullGameObjectRequest),
-    UserEvent(Vec<u8>),
+    UserEvent(UserEvent),
+    Subscribe((u128, String)),
+    Unsubscribe((u128, String)),
+    StartTimer(Timer),
+    CancelTimer(TimerHandle),
+    SetTimeScale(f32),
+    SetPaused(bool),
+    StartTween(Tween),
+    StopTween(TweenHandle),
+    SetLayerVisible(RenderLayer, bool),
+    /// Index of the camera in the active scene, and what to do with it
+    Camera(usize, CameraCommand),
+    /// Pass name, parameter name and value
+    SetPostParam(String, String, f32),
+    SetPostPassEnabled(String, bool),
 }
 
 impl Engine {
     pub(crate) fn handle_events(&mut self) {
-        let packet = self.event_rx.try_recv();
-        match packet {
-            Ok(event) => {
-                if event.is_some() {
-                    match event.unwrap() {
+        loop {
+            let packet = self.event_rx.try_recv();
+            match packet {
+                Ok(Some(event)) => {
+                    match event {
                         EngineEvent::SwitchToScene(scene) => {
                             self.set_current_scene(scene);
                         }
@@ -78,14 +97,77 @@ impl Engine {
                         }
                         EngineEvent::UserEvent(event) => {
                             for object in &self.active_scene.as_ref().unwrap().game_objects {
-                                object.notify_internal(event.clone());
+                                let receives = match event.target {
+                                    Some(target) => object.id == target,
+                                    None => object.is_subscribed(event.topic()),
+                                };
+                                if receives {
+                                    object.notify_internal(event.clone());
+                                }
                             }
                         }
+                        EngineEvent::Subscribe((object_id, topic)) => {
+                            let scene = self.active_scene.as_mut().unwrap();
+                            if let Some(object) =
+                                scene.game_objects.iter_mut().find(|o| o.id == object_id)
+                            {
+                                object.subscribe(&topic);
+                            }
+                        }
+                        EngineEvent::Unsubscribe((object_id, topic)) => {
+                            let scene = self.active_scene.as_mut().unwrap();
+                            if let Some(object) =
+                                scene.game_objects.iter_mut().find(|o| o.id == object_id)
+                            {
+                                object.unsubscribe(&topic);
+                            }
+                        }
+                        EngineEvent::StartTimer(timer) => {
+                            self.timers.push(timer);
+                        }
+                        EngineEvent::CancelTimer(handle) => {
+                            self.timers.retain(|timer| timer.handle != handle);
+                        }
+                        EngineEvent::SetTimeScale(time_scale) => {
+                            self.set_time_scale(time_scale);
+                        }
+                        EngineEvent::SetPaused(paused) => {
+                            self.set_paused(paused);
+                        }
+                        EngineEvent::SetLayerVisible(layer, visible) => {
+                            self.set_layer_visible(layer, visible);
+                        }
+                        EngineEvent::Camera(index, command) => {
+                            let scene = self.active_scene.as_mut().unwrap();
+                            if let Some(camera) = scene.camera_at_mut(index) {
+                                camera.apply(command);
+                            }
+                        }
+                        EngineEvent::SetPostParam(pass, param, value) => {
+                            if let Some(pass) = self.post_pass_mut(&pass) {
+                                // Scripts can't add parameters past the limit
+                                if !pass.try_set_param(&param, value) {
+                                    log::warn!("Ignoring parameter {} of post pass {}, which has no room for more", param, pass.name);
+                                }
+                            }
+                        }
+                        EngineEvent::SetPostPassEnabled(pass, enabled) => {
+                            if let Some(pass) = self.post_pass_mut(&pass) {
+                                pass.enabled = enabled;
+                            }
+                        }
+                        EngineEvent::StartTween(tween) => {
+                            self.start_tween(tween);
+                        }
+                        EngineEvent::StopTween(handle) => {
+                            self.stop_tween(handle);
+                        }
                     }
                 }
-            }
-            Err(e) => {
-                panic!("{}", e);
+                Ok(None) => break,
+                Err(e) => {
+                    panic!("{}", e);
+                }
             }
         }
     }
@@ -1,52 +1,98 @@
+use crate::camera::{CameraBounds, CameraCommand, CameraFollow, Viewport};
 use crate::game_object::behaviours::{EngineView, UserBehaviour};
-use crate::game_object::graphics::{Graphics, GraphicsType};
+use crate::game_object::coroutines::{Coroutine, CoroutineState};
+use crate::game_object::events::{dynamic_to_json, GameEvent, UserEvent};
+use crate::game_object::graphics::{BlendMode, GraphicsType, RenderLayer, SpriteID};
 use crate::game_object::physics::{PhysicsData, PhysicsObject};
+use crate::game_object::tilemap::Tile;
+use crate::light::Light2D;
 use crate::physics::AlcubierreCollider;
-use crate::renderer::buffer::QuadBufferBuilder;
 use crate::{EngineEvent, Scene};
 use hashbrown::HashSet;
 use kanal::{Receiver, Sender};
 use rapier2d::dynamics::{RigidBody, RigidBodyHandle};
 use rapier2d::geometry::NarrowPhase;
 use rapier2d::prelude::{ColliderHandle, ColliderSet, QueryPipeline, RigidBodySet, vector};
-use rhai::{Engine, Scope, AST, ImmutableString, CallFnOptions};
+use rhai::{Engine, Scope, AST, ImmutableString, CallFnOptions, Dynamic, EvalAltResult, FnPtr, Map};
+use std::cell::Cell;
 use std::{fmt, fs};
 use std::sync::Arc;
 use std::time::Duration;
 use rapier2d::math::Isometry;
 use winit::event::VirtualKeyCode;
-use crate::renderer::atlas::SpriteAtlas;
-use crate::renderer::sprite::SpriteVertex;
+use crate::timers::{Clock, Timer, TimerAction, TimerHandle};
+use crate::tween::{tween_from_rhai, TweenHandle};
+use crate::ui::frontend::RGBColor;
 
+pub mod animation;
 pub mod behaviours;
+mod coroutines;
+pub mod events;
 pub mod graphics;
+pub mod particles;
 pub mod physics;
+pub mod tilemap;
 
 #[derive(Clone)]
 pub enum GameObjectIPC {
-    UserEvent(Vec<u8>), // User can use #[repr(u16)] on an enum to use this nicely
+    UserEvent(UserEvent),
+    ScriptCall(String),
+    StartCoroutine(String),
+    StopCoroutine(String),
+    PlayAnimation(String),
+    SwitchAnimation(String),
+    StopAnimation,
+    SetTint(RGBColor),
+    SetOpacity(f32),
+    SetFlash(f32),
+    SetBlendMode(BlendMode),
+    SetZIndex(i32),
+    SetLayer(RenderLayer),
+    SetMaterialParam(String, f32),
+    /// Changes a tile of a tilemap, `None` clearing it
+    SetTile(u32, u32, Option<Tile>),
+    StartParticles,
+    StopParticles,
+    BurstParticles(u32),
+    SetLightEnabled(bool),
+    SetLightIntensity(f32),
 }
 
 #[derive(Clone)]
 pub struct Behaviour {
     pub ast: Arc<AST>,
     pub scope: Scope<'static>,
+    pub(crate) coroutines: Vec<Coroutine>,
 }
 
 #[derive(Clone)]
 pub struct GameObject {
     pub graphics: Option<GraphicsType>,
     pub behaviours: Vec<Behaviour>,
+    pub user_behaviours: Vec<Box<dyn UserBehaviour>>,
     pub pos_x: f32,
     pub pos_y: f32,
+    /// Rotation in radians
+    pub rotation: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub layer: RenderLayer,
+    /// Higher values are drawn in front of lower ones in the same layer
+    pub z_index: i32,
     pub physics: PhysicsData,
+    /// Only shows while the scene has an ambient light
+    pub light: Option<Light2D>,
     pub(crate) id: u128,
+    pub(crate) subscriptions: HashSet<String>,
     pub(crate) event_tx: Sender<GameObjectIPC>,
     pub(crate) event_rx: Receiver<GameObjectIPC>,
+    /// Set once a missing atlas or sprite has been reported, so it isn't every frame
+    pub(crate) missing_graphics_logged: Cell<bool>,
 }
 
 #[derive(Clone)]
 pub struct GameObjectRhaiView {
+    pub id: u128,
     pub pos_x: f64,
     pub pos_y: f64,
     pub rigid_body: RigidBody
@@ -54,24 +100,293 @@ pub struct GameObjectRhaiView {
 
 #[derive(Clone)]
 pub struct EngineController {
-    pub(crate) event_tx: Arc<Sender<EngineEvent>>
+    pub(crate) event_tx: Arc<Sender<EngineEvent>>,
+    pub(crate) object_tx: Sender<GameObjectIPC>,
+    pub(crate) object_id: u128,
+    /// Sprites the object's tilemap can show, once it has been drawn
+    pub(crate) tile_ids: Option<Arc<HashSet<SpriteID>>>,
 }
 
 impl EngineController {
+    fn send(&self, event: EngineEvent) {
+        // Coroutines re-run code that already ran, so skip anything sent while catching up
+        if !coroutines::is_replaying() {
+            self.event_tx.send(event).unwrap();
+        }
+    }
+    fn send_to_self(&self, event: GameObjectIPC) {
+        if !coroutines::is_replaying() {
+            self.object_tx.send(event).unwrap();
+        }
+    }
+    fn start_timer(&mut self, seconds: f64, repeating: bool, action: TimerAction) -> i64 {
+        if let Some(handle) = coroutines::replayed_handle() {
+            return handle;
+        }
+        let timer = Timer::new(self.object_id, seconds as f32, repeating, action);
+        let handle = timer.handle.0 as i64;
+        self.send(EngineEvent::StartTimer(timer));
+        coroutines::record_handle(handle);
+        handle
+    }
     pub fn insert_into_datamap(&mut self, var: &str, val: &str) {
-        self.event_tx
-            .send(EngineEvent::InsertDatamapValue((var.to_string(), val.to_string())))
-            .unwrap();
+        self.send(EngineEvent::InsertDatamapValue((var.to_string(), val.to_string())));
     }
     pub fn set_datamap_value(&mut self, var: &str, val: &str) {
-        self.event_tx
-            .send(EngineEvent::SetDatamapValue((var.to_string(), val.to_string())))
-            .unwrap();
+        self.send(EngineEvent::SetDatamapValue((var.to_string(), val.to_string())));
     }
     pub fn remove_datamap_value(&mut self, var: &str) {
-        self.event_tx
-            .send(EngineEvent::RemoveDatamapValue(var.to_string()))
-            .unwrap();
+        self.send(EngineEvent::RemoveDatamapValue(var.to_string()));
+    }
+    pub fn publish(&mut self, topic: &str, data: Dynamic) {
+        let event = UserEvent::from_value(topic, dynamic_to_json(&data)).with_sender(self.object_id);
+        self.send(EngineEvent::UserEvent(event));
+    }
+    pub fn publish_to(&mut self, target: i64, topic: &str, data: Dynamic) {
+        let event = UserEvent::from_value(topic, dynamic_to_json(&data))
+            .with_sender(self.object_id)
+            .with_target(target as u128);
+        self.send(EngineEvent::UserEvent(event));
+    }
+    pub fn subscribe(&mut self, topic: &str) {
+        self.send(EngineEvent::Subscribe((self.object_id, topic.to_string())));
+    }
+    pub fn unsubscribe(&mut self, topic: &str) {
+        self.send(EngineEvent::Unsubscribe((self.object_id, topic.to_string())));
+    }
+    /// Sends `topic` to this object's `on_event` after `seconds`.
+    pub fn after(&mut self, seconds: f64, topic: &str, data: Dynamic) -> i64 {
+        let event = UserEvent::from_value(topic, dynamic_to_json(&data));
+        self.start_timer(seconds, false, TimerAction::Event(event))
+    }
+    pub fn every(&mut self, seconds: f64, topic: &str, data: Dynamic) -> i64 {
+        let event = UserEvent::from_value(topic, dynamic_to_json(&data));
+        self.start_timer(seconds, true, TimerAction::Event(event))
+    }
+    /// Calls `callback` on this object's behaviours after `seconds`.
+    pub fn after_call(&mut self, seconds: f64, callback: FnPtr) -> i64 {
+        let action = TimerAction::ScriptCallback(callback.fn_name().to_string());
+        self.start_timer(seconds, false, action)
+    }
+    pub fn every_call(&mut self, seconds: f64, callback: FnPtr) -> i64 {
+        let action = TimerAction::ScriptCallback(callback.fn_name().to_string());
+        self.start_timer(seconds, true, action)
+    }
+    pub fn cancel_timer(&mut self, handle: i64) {
+        self.send(EngineEvent::CancelTimer(TimerHandle(handle as u64)));
+    }
+    pub fn start_coroutine(&mut self, function: &str) {
+        self.send_to_self(GameObjectIPC::StartCoroutine(function.to_string()));
+    }
+    pub fn stop_coroutine(&mut self, function: &str) {
+        self.send_to_self(GameObjectIPC::StopCoroutine(function.to_string()));
+    }
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.send(EngineEvent::SetTimeScale(time_scale as f32));
+    }
+    pub fn pause(&mut self) {
+        self.send(EngineEvent::SetPaused(true));
+    }
+    pub fn resume(&mut self) {
+        self.send(EngineEvent::SetPaused(false));
+    }
+    pub fn tween(&mut self, options: Map) -> Result<i64, Box<EvalAltResult>> {
+        if let Some(handle) = coroutines::replayed_handle() {
+            return Ok(handle);
+        }
+        let tween = tween_from_rhai(self.object_id, &options)?;
+        let handle = tween.handle.0 as i64;
+        self.send(EngineEvent::StartTween(tween));
+        coroutines::record_handle(handle);
+        Ok(handle)
+    }
+    pub fn stop_tween(&mut self, handle: i64) {
+        self.send(EngineEvent::StopTween(TweenHandle(handle as u64)));
+    }
+    pub fn play_animation(&mut self, clip: &str) {
+        self.send_to_self(GameObjectIPC::PlayAnimation(clip.to_string()));
+    }
+    pub fn switch_animation(&mut self, clip: &str) {
+        self.send_to_self(GameObjectIPC::SwitchAnimation(clip.to_string()));
+    }
+    pub fn stop_animation(&mut self) {
+        self.send_to_self(GameObjectIPC::StopAnimation);
+    }
+    pub fn set_tint(&mut self, red: i64, green: i64, blue: i64) {
+        let channel = |value: i64| value.clamp(0, 255) as u8;
+        self.send_to_self(GameObjectIPC::SetTint(RGBColor {
+            red: channel(red),
+            green: channel(green),
+            blue: channel(blue),
+            alpha: 255,
+        }));
+    }
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.send_to_self(GameObjectIPC::SetOpacity(opacity as f32));
+    }
+    pub fn set_flash(&mut self, flash: f64) {
+        self.send_to_self(GameObjectIPC::SetFlash(flash as f32));
+    }
+    /// "alpha", "additive" or "premultiplied"
+    pub fn set_blend_mode(&mut self, name: &str) -> Result<(), Box<EvalAltResult>> {
+        let blend_mode = BlendMode::from_name(name).ok_or(format!("unknown blend mode '{}'", name))?;
+        self.send_to_self(GameObjectIPC::SetBlendMode(blend_mode));
+        Ok(())
+    }
+    pub fn set_z_index(&mut self, z_index: i64) {
+        self.send_to_self(GameObjectIPC::SetZIndex(z_index as i32));
+    }
+    pub fn set_material_param(&mut self, name: &str, value: f64) {
+        self.send_to_self(GameObjectIPC::SetMaterialParam(name.to_string(), value as f32));
+    }
+    /// Shows `sprite_id` in a cell of this object's tilemap.
+    pub fn set_tile(&mut self, x: i64, y: i64, sprite_id: &str) -> Result<(), Box<EvalAltResult>> {
+        if self.tile_ids.as_ref().is_some_and(|ids| !ids.contains(sprite_id)) {
+            return Err(format!("unknown tile '{}'", sprite_id).into());
+        }
+        if x >= 0 && y >= 0 {
+            self.send_to_self(GameObjectIPC::SetTile(x as u32, y as u32, Some(Tile::new(sprite_id))));
+        }
+        Ok(())
+    }
+    pub fn clear_tile(&mut self, x: i64, y: i64) {
+        if x >= 0 && y >= 0 {
+            self.send_to_self(GameObjectIPC::SetTile(x as u32, y as u32, None));
+        }
+    }
+    pub fn start_particles(&mut self) {
+        self.send_to_self(GameObjectIPC::StartParticles);
+    }
+    pub fn stop_particles(&mut self) {
+        self.send_to_self(GameObjectIPC::StopParticles);
+    }
+    pub fn burst_particles(&mut self, count: i64) {
+        self.send_to_self(GameObjectIPC::BurstParticles(count.clamp(0, u32::MAX as i64) as u32));
+    }
+    pub fn set_light_enabled(&mut self, enabled: bool) {
+        self.send_to_self(GameObjectIPC::SetLightEnabled(enabled));
+    }
+    pub fn set_light_intensity(&mut self, intensity: f64) {
+        self.send_to_self(GameObjectIPC::SetLightIntensity(intensity as f32));
+    }
+    /// "background", "world", "foreground" or "overlay"
+    pub fn set_layer(&mut self, name: &str) -> Result<(), Box<EvalAltResult>> {
+        let layer = RenderLayer::from_name(name).ok_or(format!("unknown layer '{}'", name))?;
+        self.send_to_self(GameObjectIPC::SetLayer(layer));
+        Ok(())
+    }
+    /// Shows or hides every object on a layer
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> Result<(), Box<EvalAltResult>> {
+        let layer = RenderLayer::from_name(name).ok_or(format!("unknown layer '{}'", name))?;
+        self.send(EngineEvent::SetLayerVisible(layer, visible));
+        Ok(())
+    }
+    /// Changes a parameter of one of the scene's post passes, e.g. a vignette's "intensity".
+    pub fn set_post_param(&mut self, pass: &str, param: &str, value: f64) {
+        self.send(EngineEvent::SetPostParam(pass.to_string(), param.to_string(), value as f32));
+    }
+    pub fn set_post_pass_enabled(&mut self, pass: &str, enabled: bool) {
+        self.send(EngineEvent::SetPostPassEnabled(pass.to_string(), enabled));
+    }
+    /// One of the scene's cameras, 0 being the main one.
+    pub fn camera(&mut self, index: i64) -> CameraController {
+        CameraController {
+            controller: self.clone(),
+            index: index.max(0) as usize,
+        }
+    }
+    /// Makes the main camera follow this object. Higher smoothing catches up faster, 0 snaps to it.
+    pub fn camera_follow(&mut self, smoothing: f64) {
+        self.camera(0).follow(smoothing);
+    }
+    pub fn camera_follow_object(&mut self, id: i64, smoothing: f64) {
+        self.camera(0).follow_object(id, smoothing);
+    }
+    pub fn camera_stop_follow(&mut self) {
+        self.camera(0).stop_follow();
+    }
+    pub fn set_camera_deadzone(&mut self, width: f64, height: f64) {
+        self.camera(0).set_deadzone(width, height);
+    }
+    pub fn set_camera_position(&mut self, x: f64, y: f64) {
+        self.camera(0).set_position(x, y);
+    }
+    pub fn set_camera_zoom(&mut self, zoom: f64) {
+        self.camera(0).set_zoom(zoom);
+    }
+    pub fn set_camera_rotation(&mut self, rotation: f64) {
+        self.camera(0).set_rotation(rotation);
+    }
+    pub fn set_camera_bounds(&mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) {
+        self.camera(0).set_bounds(min_x, min_y, max_x, max_y);
+    }
+    pub fn clear_camera_bounds(&mut self) {
+        self.camera(0).clear_bounds();
+    }
+    pub fn camera_shake(&mut self, intensity: f64, duration: f64) {
+        self.camera(0).shake(intensity, duration);
+    }
+}
+
+/// Script handle to one camera, from `engine.camera(index)`.
+#[derive(Clone)]
+pub struct CameraController {
+    controller: EngineController,
+    index: usize,
+}
+
+impl CameraController {
+    fn send(&self, command: CameraCommand) {
+        self.controller.send(EngineEvent::Camera(self.index, command));
+    }
+    /// Follows the object running the script
+    pub fn follow(&mut self, smoothing: f64) {
+        self.follow_object(self.controller.object_id as i64, smoothing);
+    }
+    pub fn follow_object(&mut self, id: i64, smoothing: f64) {
+        self.send(CameraCommand::Follow(CameraFollow::new(id as u128).smoothing(smoothing as f32)));
+    }
+    pub fn stop_follow(&mut self) {
+        self.send(CameraCommand::StopFollowing);
+    }
+    pub fn set_deadzone(&mut self, width: f64, height: f64) {
+        self.send(CameraCommand::SetDeadzone([width as f32, height as f32]));
+    }
+    pub fn set_position(&mut self, x: f64, y: f64) {
+        self.send(CameraCommand::SetPosition([x as f32, y as f32]));
+    }
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.send(CameraCommand::SetZoom(zoom as f32));
+    }
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.send(CameraCommand::SetRotation(rotation as f32));
+    }
+    pub fn set_bounds(&mut self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) {
+        self.send(CameraCommand::SetBounds(Some(CameraBounds {
+            min: [min_x as f32, min_y as f32],
+            max: [max_x as f32, max_y as f32],
+        })));
+    }
+    pub fn clear_bounds(&mut self) {
+        self.send(CameraCommand::SetBounds(None));
+    }
+    pub fn shake(&mut self, intensity: f64, duration: f64) {
+        self.send(CameraCommand::Shake {
+            intensity: intensity as f32,
+            duration: duration as f32,
+        });
+    }
+    /// Fractions of the window from the top-left
+    pub fn set_viewport(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.send(CameraCommand::SetViewport(Viewport::new(x as f32, y as f32, width as f32, height as f32)));
+    }
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> Result<(), Box<EvalAltResult>> {
+        let layer = RenderLayer::from_name(name).ok_or(format!("unknown layer '{}'", name))?;
+        self.send(CameraCommand::SetLayerVisible(layer, visible));
+        Ok(())
+    }
+    pub fn set_show_ui(&mut self, show_ui: bool) {
+        self.send(CameraCommand::SetShowUi(show_ui));
     }
 }
 
@@ -91,6 +406,9 @@ impl Input {
 
 
 impl GameObjectRhaiView {
+    fn get_id(&mut self) -> i64 {
+        self.id as i64
+    }
     fn get_pos_x(&mut self) -> f64 {
         self.pos_x.clone()
     }
@@ -130,19 +448,233 @@ impl GameObjectRhaiView {
 
 
 
+pub(crate) fn create_rhai_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_type::<GameObjectRhaiView>()
+        .register_get("id", GameObjectRhaiView::get_id)
+        .register_get_set(
+            "pos_x",
+            GameObjectRhaiView::get_pos_x,
+            GameObjectRhaiView::set_pos_x,
+        )
+        .register_get_set(
+            "pos_y",
+            GameObjectRhaiView::get_pos_y,
+            GameObjectRhaiView::set_pos_y,
+        )
+        .register_get_set(
+            "rigid_body_pos_x",
+            GameObjectRhaiView::get_rigid_body_pos_x,
+            GameObjectRhaiView::set_rigid_body_pos_x,
+        )
+        .register_get_set(
+            "rigid_body_pos_y",
+            GameObjectRhaiView::get_rigid_body_pos_y,
+            GameObjectRhaiView::set_rigid_body_pos_y,
+        );
+
+    engine.register_type::<Input>()
+        .register_fn("is_key_down", Input::is_key_down)
+        .register_fn("is_key_up", Input::is_key_up);
+
+    engine.register_type::<EngineController>()
+        .register_fn("insert_into_datamap", EngineController::insert_into_datamap)
+        .register_fn("set_datamap_value", EngineController::set_datamap_value)
+        .register_fn("remove_datamap_value", EngineController::remove_datamap_value)
+        .register_fn("publish", EngineController::publish)
+        .register_fn("publish", |controller: &mut EngineController, topic: &str| {
+            controller.publish(topic, Dynamic::UNIT)
+        })
+        .register_fn("publish_to", EngineController::publish_to)
+        .register_fn("publish_to", |controller: &mut EngineController, target: i64, topic: &str| {
+            controller.publish_to(target, topic, Dynamic::UNIT)
+        })
+        .register_fn("subscribe", EngineController::subscribe)
+        .register_fn("unsubscribe", EngineController::unsubscribe)
+        .register_fn("after", EngineController::after)
+        .register_fn("after", |controller: &mut EngineController, seconds: f64, topic: &str| {
+            controller.after(seconds, topic, Dynamic::UNIT)
+        })
+        .register_fn("after", EngineController::after_call)
+        .register_fn("every", EngineController::every)
+        .register_fn("every", |controller: &mut EngineController, seconds: f64, topic: &str| {
+            controller.every(seconds, topic, Dynamic::UNIT)
+        })
+        .register_fn("every", EngineController::every_call)
+        .register_fn("cancel_timer", EngineController::cancel_timer)
+        .register_fn("start_coroutine", EngineController::start_coroutine)
+        .register_fn("stop_coroutine", EngineController::stop_coroutine)
+        .register_fn("set_time_scale", EngineController::set_time_scale)
+        .register_fn("pause", EngineController::pause)
+        .register_fn("resume", EngineController::resume)
+        .register_fn("tween", EngineController::tween)
+        .register_fn("stop_tween", EngineController::stop_tween)
+        .register_fn("play_animation", EngineController::play_animation)
+        .register_fn("switch_animation", EngineController::switch_animation)
+        .register_fn("stop_animation", EngineController::stop_animation)
+        .register_fn("set_tint", EngineController::set_tint)
+        .register_fn("set_opacity", EngineController::set_opacity)
+        .register_fn("set_flash", EngineController::set_flash)
+        .register_fn("set_blend_mode", EngineController::set_blend_mode)
+        .register_fn("set_z_index", EngineController::set_z_index)
+        .register_fn("set_layer", EngineController::set_layer)
+        .register_fn("set_material_param", EngineController::set_material_param)
+        .register_fn("set_tile", EngineController::set_tile)
+        .register_fn("clear_tile", EngineController::clear_tile)
+        .register_fn("start_particles", EngineController::start_particles)
+        .register_fn("stop_particles", EngineController::stop_particles)
+        .register_fn("burst_particles", EngineController::burst_particles)
+        .register_fn("set_light_enabled", EngineController::set_light_enabled)
+        .register_fn("set_light_intensity", EngineController::set_light_intensity)
+        .register_fn("set_layer_visible", EngineController::set_layer_visible)
+        .register_fn("set_post_param", EngineController::set_post_param)
+        .register_fn("set_post_pass_enabled", EngineController::set_post_pass_enabled)
+        .register_fn("camera_follow", EngineController::camera_follow)
+        .register_fn("camera_follow", EngineController::camera_follow_object)
+        .register_fn("camera_stop_follow", EngineController::camera_stop_follow)
+        .register_fn("set_camera_deadzone", EngineController::set_camera_deadzone)
+        .register_fn("set_camera_position", EngineController::set_camera_position)
+        .register_fn("set_camera_zoom", EngineController::set_camera_zoom)
+        .register_fn("set_camera_rotation", EngineController::set_camera_rotation)
+        .register_fn("set_camera_bounds", EngineController::set_camera_bounds)
+        .register_fn("clear_camera_bounds", EngineController::clear_camera_bounds)
+        .register_fn("camera_shake", EngineController::camera_shake)
+        .register_fn("camera", EngineController::camera);
+
+    engine
+        .register_type_with_name::<CameraController>("Camera")
+        .register_fn("follow", CameraController::follow)
+        .register_fn("follow", CameraController::follow_object)
+        .register_fn("stop_follow", CameraController::stop_follow)
+        .register_fn("set_deadzone", CameraController::set_deadzone)
+        .register_fn("set_position", CameraController::set_position)
+        .register_fn("set_zoom", CameraController::set_zoom)
+        .register_fn("set_rotation", CameraController::set_rotation)
+        .register_fn("set_bounds", CameraController::set_bounds)
+        .register_fn("clear_bounds", CameraController::clear_bounds)
+        .register_fn("shake", CameraController::shake)
+        .register_fn("set_viewport", CameraController::set_viewport)
+        .register_fn("set_layer_visible", CameraController::set_layer_visible)
+        .register_fn("set_show_ui", CameraController::set_show_ui);
+
+    // Coroutines
+    engine.register_fn("wait", coroutines::wait);
+    engine
+        .register_custom_syntax(["yield"], false, |_, _| {
+            coroutines::wait(0.0).map(|_| Dynamic::UNIT)
+        })
+        .unwrap();
+
+    engine
+}
+
+pub(crate) fn has_script_fn(ast: &AST, name: &str, arity: usize) -> bool {
+    ast.iter_functions()
+        .any(|f| f.name == name && f.params.len() == arity)
+}
+
 pub struct GameObjectView<'a> {
+    pub id: u128,
     pub physics: &'a mut PhysicsData,
     pub pos_x: &'a mut f32,
     pub pos_y: &'a mut f32,
+    pub(crate) object_tx: &'a Sender<GameObjectIPC>,
+}
+
+impl GameObjectView<'_> {
+    /// Starts an animation clip from its first frame.
+    pub fn play_animation(&self, clip: &str) {
+        self.object_tx.send(GameObjectIPC::PlayAnimation(clip.to_string())).unwrap();
+    }
+    /// Changes clip while keeping the current frame.
+    pub fn switch_animation(&self, clip: &str) {
+        self.object_tx.send(GameObjectIPC::SwitchAnimation(clip.to_string())).unwrap();
+    }
+    pub fn stop_animation(&self) {
+        self.object_tx.send(GameObjectIPC::StopAnimation).unwrap();
+    }
+    /// Multiplies the sprite's texture by `tint`.
+    pub fn set_tint(&self, tint: RGBColor) {
+        self.object_tx.send(GameObjectIPC::SetTint(tint)).unwrap();
+    }
+    pub fn set_opacity(&self, opacity: f32) {
+        self.object_tx.send(GameObjectIPC::SetOpacity(opacity)).unwrap();
+    }
+    /// Pushes the sprite towards solid white, e.g. for hit flashes.
+    pub fn set_flash(&self, flash: f32) {
+        self.object_tx.send(GameObjectIPC::SetFlash(flash)).unwrap();
+    }
+    pub fn set_blend_mode(&self, blend_mode: BlendMode) {
+        self.object_tx.send(GameObjectIPC::SetBlendMode(blend_mode)).unwrap();
+    }
+    pub fn set_z_index(&self, z_index: i32) {
+        self.object_tx.send(GameObjectIPC::SetZIndex(z_index)).unwrap();
+    }
+    pub fn set_layer(&self, layer: RenderLayer) {
+        self.object_tx.send(GameObjectIPC::SetLayer(layer)).unwrap();
+    }
+    /// Changes one of this object's material parameters, e.g. a dissolve amount.
+    pub fn set_material_param(&self, name: &str, value: f32) {
+        self.object_tx
+            .send(GameObjectIPC::SetMaterialParam(name.to_string(), value))
+            .unwrap();
+    }
+    /// Changes a cell of this object's tilemap, along with its colliders.
+    pub fn set_tile(&self, x: u32, y: u32, tile: Tile) {
+        self.object_tx.send(GameObjectIPC::SetTile(x, y, Some(tile))).unwrap();
+    }
+    pub fn clear_tile(&self, x: u32, y: u32) {
+        self.object_tx.send(GameObjectIPC::SetTile(x, y, None)).unwrap();
+    }
+    /// Starts this object's particle emitter emitting at its rate.
+    pub fn start_particles(&self) {
+        self.object_tx.send(GameObjectIPC::StartParticles).unwrap();
+    }
+    pub fn stop_particles(&self) {
+        self.object_tx.send(GameObjectIPC::StopParticles).unwrap();
+    }
+    /// Emits `count` particles at once from this object's particle emitter.
+    pub fn burst_particles(&self, count: u32) {
+        self.object_tx.send(GameObjectIPC::BurstParticles(count)).unwrap();
+    }
+    /// Turns this object's light on or off.
+    pub fn set_light_enabled(&self, enabled: bool) {
+        self.object_tx.send(GameObjectIPC::SetLightEnabled(enabled)).unwrap();
+    }
+    pub fn set_light_intensity(&self, intensity: f32) {
+        self.object_tx.send(GameObjectIPC::SetLightIntensity(intensity)).unwrap();
+    }
 }
 
 impl GameObject {
-    pub fn notify(&self, event: &[u8]) {
-        let ev = event.to_vec();
-        self.notify_internal(ev);
+    /// Sprites a script may place on this object's tilemap, `None` if anything goes.
+    fn tile_ids(&self) -> Option<Arc<HashSet<SpriteID>>> {
+        match &self.graphics {
+            Some(GraphicsType::Tilemap(tilemap)) => tilemap.atlas_sprites.clone(),
+            _ => None,
+        }
+    }
+    pub fn id(&self) -> u128 {
+        self.id
+    }
+    /// Sends an event straight to this object, regardless of its subscriptions.
+    pub fn notify<E: GameEvent>(&self, event: E) {
+        self.notify_internal(UserEvent::new(event));
+    }
+    pub(crate) fn notify_internal(&self, event: UserEvent) {
+        self.event_tx.send(GameObjectIPC::UserEvent(event)).unwrap();
+    }
+    /// Subscribes to a topic. `*` subscribes to every topic.
+    pub fn subscribe(&mut self, topic: &str) {
+        self.subscriptions.insert(topic.to_string());
     }
-    pub(crate) fn notify_internal(&self, event: Vec<u8>) {
-        self.event_tx.send(GameObjectIPC::UserEvent(event));
+    pub fn unsubscribe(&mut self, topic: &str) {
+        self.subscriptions.remove(topic);
+    }
+    pub fn is_subscribed(&self, topic: &str) -> bool {
+        self.subscriptions.contains(topic) || self.subscriptions.contains("*")
     }
     pub(crate) fn unloading(
         &mut self,
@@ -156,26 +688,28 @@ impl GameObject {
         frame_delta: &mut Duration,
         collision_locks: &mut HashSet<ColliderHandle>,
     ) {
-        // for behaviour in &mut self.behaviours {
-        // behaviour.unloaded(
-        //     EngineView {
-        //         rigid_body_set,
-        //         narrow_phase,
-        //         event_tx: tx,
-        //         keys_pressed,
-        //         key_locks,
-        //         query_pipeline,
-        //         collider_set,
-        //         frame_delta,
-        //         collision_locks,
-        //     },
-        //     GameObjectView {
-        //         physics: &mut self.physics,
-        //         pos_x: &mut self.pos_x,
-        //         pos_y: &mut self.pos_y,
-        //     },
-        // );
-        // }
+        for behaviour in &mut self.user_behaviours {
+            behaviour.unloaded(
+                EngineView {
+                    rigid_body_set,
+                    narrow_phase,
+                    event_tx: tx,
+                    keys_pressed,
+                    key_locks,
+                    query_pipeline,
+                    collider_set,
+                    frame_delta,
+                    collision_locks,
+                },
+                GameObjectView {
+                    id: self.id,
+                    physics: &mut self.physics,
+                    pos_x: &mut self.pos_x,
+                    pos_y: &mut self.pos_y,
+                object_tx: &self.event_tx,
+                },
+            );
+        }
     }
     pub(crate) fn loading(
         &mut self,
@@ -188,32 +722,44 @@ impl GameObject {
         collider_set: &mut ColliderSet,
         frame_delta: &mut Duration,
         collision_locks: &mut HashSet<ColliderHandle>,
+        engine: &mut Engine,
     ) {
-        let mut engine = Engine::new();
+        let tile_ids = self.tile_ids();
         for behaviour in &mut self.behaviours {
+            let engine_controller = EngineController {
+                event_tx: Arc::new(tx.clone()),
+                object_tx: self.event_tx.clone(),
+                object_id: self.id,
+                tile_ids: tile_ids.clone(),
+            };
+            behaviour.scope.set_or_push("engine", engine_controller);
+
             let  options = CallFnOptions::new().rewind_scope(false);
             engine
                 .call_fn_with_options::<i64>(options,&mut behaviour.scope, &behaviour.ast, "awake", ())
                 .unwrap();
-
-            //     behaviour.loaded(
-        //         EngineView {
-        //             rigid_body_set,
-        //             narrow_phase,
-        //             event_tx: tx,
-        //             key_locks,
-        //             keys_pressed,
-        //             query_pipeline,
-        //             collider_set,
-        //             frame_delta,
-        //             collision_locks,
-        //         },
-        //         GameObjectView {
-        //             physics: &mut self.physics,
-        //             pos_x: &mut self.pos_x,
-        //             pos_y: &mut self.pos_y,
-        //         },
-        //     );
+        }
+        for behaviour in &mut self.user_behaviours {
+            behaviour.loaded(
+                EngineView {
+                    rigid_body_set,
+                    narrow_phase,
+                    event_tx: tx,
+                    key_locks,
+                    keys_pressed,
+                    query_pipeline,
+                    collider_set,
+                    frame_delta,
+                    collision_locks,
+                },
+                GameObjectView {
+                    id: self.id,
+                    physics: &mut self.physics,
+                    pos_x: &mut self.pos_x,
+                    pos_y: &mut self.pos_y,
+                object_tx: &self.event_tx,
+                },
+            );
         }
     }
     pub(crate) fn execute(
@@ -221,36 +767,124 @@ impl GameObject {
         rigid_body_set: &mut RigidBodySet,
         narrow_phase: &mut NarrowPhase,
         event_tx: &mut Sender<EngineEvent>,
-        buffer: &mut QuadBufferBuilder,
         keys_pressed: &mut HashSet<VirtualKeyCode>,
         key_locks: &mut HashSet<VirtualKeyCode>,
         query_pipeline: &mut QueryPipeline,
         collider_set: &mut ColliderSet,
         frame_delta: &mut Duration,
         collision_locks: &mut HashSet<ColliderHandle>,
-        sprite_verticies: &mut Vec<SpriteVertex>,
-        sprite_indicies: &mut Vec<u16>,
-        atlas: &Option<SpriteAtlas>,
-        engine: &mut Engine
+        engine: &mut Engine,
+        clock: &Clock,
     ) {
-        let event = self.event_rx.try_recv();
-        let mut object_event: Option<GameObjectIPC> = None;
-        match event {
-            Ok(object) => {
-                object_event = object;
-            }
-            Err(e) => {
-                panic!("{}", e);
+        let mut object_events: Vec<GameObjectIPC> = Vec::new();
+        loop {
+            match self.event_rx.try_recv() {
+                Ok(Some(GameObjectIPC::StartCoroutine(function))) => {
+                    for behaviour in &mut self.behaviours {
+                        if has_script_fn(&behaviour.ast, &function, 0) {
+                            behaviour.coroutines.push(Coroutine::new(&function));
+                        }
+                    }
+                }
+                Ok(Some(GameObjectIPC::StopCoroutine(function))) => {
+                    for behaviour in &mut self.behaviours {
+                        behaviour.coroutines.retain(|c| c.function != function);
+                    }
+                }
+                Ok(Some(GameObjectIPC::PlayAnimation(clip))) => {
+                    if let Some(GraphicsType::AnimatedSprite(animated)) = &mut self.graphics {
+                        animated.play(&clip);
+                    }
+                }
+                Ok(Some(GameObjectIPC::SwitchAnimation(clip))) => {
+                    if let Some(GraphicsType::AnimatedSprite(animated)) = &mut self.graphics {
+                        animated.switch_clip(&clip);
+                    }
+                }
+                Ok(Some(GameObjectIPC::StopAnimation)) => {
+                    if let Some(GraphicsType::AnimatedSprite(animated)) = &mut self.graphics {
+                        animated.stop();
+                    }
+                }
+                Ok(Some(GameObjectIPC::SetTint(tint))) => match &mut self.graphics {
+                    Some(GraphicsType::Sprite(sprite)) => sprite.tint = tint,
+                    Some(GraphicsType::AnimatedSprite(animated)) => animated.tint = tint,
+                    Some(GraphicsType::Tilemap(tilemap)) => tilemap.tint = tint,
+                    _ => {}
+                },
+                Ok(Some(GameObjectIPC::SetOpacity(opacity))) => match &mut self.graphics {
+                    Some(GraphicsType::Sprite(sprite)) => sprite.opacity = opacity,
+                    Some(GraphicsType::AnimatedSprite(animated)) => animated.opacity = opacity,
+                    Some(GraphicsType::Tilemap(tilemap)) => tilemap.opacity = opacity,
+                    _ => {}
+                },
+                Ok(Some(GameObjectIPC::SetFlash(flash))) => match &mut self.graphics {
+                    Some(GraphicsType::Sprite(sprite)) => sprite.flash = flash,
+                    Some(GraphicsType::AnimatedSprite(animated)) => animated.flash = flash,
+                    _ => {}
+                },
+                Ok(Some(GameObjectIPC::SetBlendMode(blend_mode))) => {
+                    if let Some(graphics) = &mut self.graphics {
+                        graphics.set_blend_mode(blend_mode);
+                    }
+                }
+                Ok(Some(GameObjectIPC::SetZIndex(z_index))) => self.z_index = z_index,
+                Ok(Some(GameObjectIPC::SetLayer(layer))) => self.layer = layer,
+                Ok(Some(GameObjectIPC::SetMaterialParam(name, value))) => {
+                    if let Some(graphics) = &mut self.graphics {
+                        graphics.set_material_param(&name, value);
+                    }
+                }
+                Ok(Some(GameObjectIPC::SetTile(x, y, tile))) => {
+                    if let Some(GraphicsType::Tilemap(tilemap)) = &mut self.graphics {
+                        match tile {
+                            Some(tile) => tilemap.set_tile(x, y, tile),
+                            None => tilemap.clear_tile(x, y),
+                        }
+                    }
+                }
+                Ok(Some(GameObjectIPC::StartParticles)) => {
+                    if let Some(GraphicsType::ParticleEmitter(emitter)) = &mut self.graphics {
+                        emitter.start();
+                    }
+                }
+                Ok(Some(GameObjectIPC::StopParticles)) => {
+                    if let Some(GraphicsType::ParticleEmitter(emitter)) = &mut self.graphics {
+                        emitter.stop();
+                    }
+                }
+                Ok(Some(GameObjectIPC::BurstParticles(count))) => {
+                    if let Some(GraphicsType::ParticleEmitter(emitter)) = &mut self.graphics {
+                        emitter.burst(count);
+                    }
+                }
+                Ok(Some(GameObjectIPC::SetLightEnabled(enabled))) => {
+                    if let Some(light) = &mut self.light {
+                        light.enabled = enabled;
+                    }
+                }
+                Ok(Some(GameObjectIPC::SetLightIntensity(intensity))) => {
+                    if let Some(light) = &mut self.light {
+                        light.intensity = intensity;
+                    }
+                }
+                Ok(Some(event)) => object_events.push(event),
+                Ok(None) => break,
+                Err(e) => {
+                    panic!("{}", e);
+                }
             }
         }
+        let game_delta = clock.scaled_delta(frame_delta);
 
-        // let mut engine = Engine::new();
+        let tile_ids = self.tile_ids();
         for behaviour in &mut self.behaviours {
             let rigid_body = rigid_body_set
                 .get_mut(self.physics.rigid_body_handle.unwrap())
                 .unwrap();
 
             let mut pt = GameObjectRhaiView {
+                id: self.id,
                 pos_x: self.pos_x as f64,
                 pos_y: self.pos_y as f64,
                 rigid_body:rigid_body.clone(),
@@ -267,45 +901,55 @@ impl GameObject {
             };
 
             let engine_controller = EngineController {
-                event_tx: Arc::new(event_tx.clone())
+                event_tx: Arc::new(event_tx.clone()),
+                object_tx: self.event_tx.clone(),
+                object_id: self.id,
+                tile_ids: tile_ids.clone(),
             };
 
             behaviour.scope.set_or_push("self", pt.clone());
             behaviour.scope.set_or_push("Input", rh_input);
             behaviour.scope.set_or_push("engine", engine_controller);
 
+            let handles_events = has_script_fn(&behaviour.ast, "on_event", 1);
+            for event in &object_events {
+                match event {
+                    GameObjectIPC::UserEvent(user_event) if handles_events => {
+                        let options = CallFnOptions::new().rewind_scope(false);
+                        let _ = engine
+                            .call_fn_with_options::<Dynamic>(options, &mut behaviour.scope, &behaviour.ast, "on_event", (user_event.to_rhai(),))
+                            .unwrap();
+                    }
+                    GameObjectIPC::ScriptCall(function) if has_script_fn(&behaviour.ast, function, 0) => {
+                        let options = CallFnOptions::new().rewind_scope(false);
+                        let _ = engine
+                            .call_fn_with_options::<Dynamic>(options, &mut behaviour.scope, &behaviour.ast, function, ())
+                            .unwrap();
+                    }
+                    _ => {}
+                }
+            }
 
-            engine
-                .register_type::<GameObjectRhaiView>()
-                .register_get_set(
-                    "pos_x",
-                    GameObjectRhaiView::get_pos_x,
-                    GameObjectRhaiView::set_pos_x,
-                )
-                .register_get_set(
-                    "pos_y",
-                    GameObjectRhaiView::get_pos_y,
-                    GameObjectRhaiView::set_pos_y,
-                )
-                .register_get_set(
-                    "rigid_body_pos_x",
-                    GameObjectRhaiView::get_rigid_body_pos_x,
-                    GameObjectRhaiView::set_rigid_body_pos_x,
-                )
-                .register_get_set(
-                "rigid_body_pos_y",
-                GameObjectRhaiView::get_rigid_body_pos_y,
-                GameObjectRhaiView::set_rigid_body_pos_y,
-                );
-
-            engine.register_type::<Input>()
-                .register_fn("is_key_down", Input::is_key_down)
-                .register_fn("is_key_up", Input::is_key_up);
+            let mut finished = vec![];
+            for (i, coroutine) in behaviour.coroutines.iter_mut().enumerate() {
+                if !coroutine.tick(game_delta) {
+                    continue;
+                }
+                let function = coroutine.function.clone();
+                let state = coroutine
+                    .resume(|| {
+                        let options = CallFnOptions::new().rewind_scope(false);
+                        engine.call_fn_with_options::<Dynamic>(options, &mut behaviour.scope, &behaviour.ast, &function, ())
+                    })
+                    .unwrap();
+                if let CoroutineState::Finished = state {
+                    finished.push(i);
+                }
+            }
+            for i in finished.into_iter().rev() {
+                behaviour.coroutines.remove(i);
+            }
 
-            engine.register_type::<EngineController>()
-                .register_fn("insert_into_datamap", EngineController::insert_into_datamap)
-                .register_fn("set_datamap_value", EngineController::set_datamap_value)
-                .register_fn("remove_datamap_value", EngineController::remove_datamap_value);
             let mut options = CallFnOptions::new().rewind_scope(false);
             let new_view = engine
                 .call_fn_with_options::<GameObjectRhaiView>(options,&mut behaviour.scope, &behaviour.ast, "update", (frame_delta.as_secs_f64(),))
@@ -313,64 +957,96 @@ impl GameObject {
 
 
             rigid_body.set_position(new_view.rigid_body.position().clone(),true)
-            //TODO: Maybe this should be a drain so all events get sent per frame
-            // if object_event.is_some() {
-            //     behaviour.received_event(
-            //         object_event.as_ref().unwrap(),
-            //         EngineView {
-            //             rigid_body_set,
-            //             narrow_phase,
-            //             event_tx,
-            //             keys_pressed,
-            //             key_locks,
-            //             query_pipeline,
-            //             collider_set,
-            //             frame_delta,
-            //             collision_locks,
-            //         },
-            //         GameObjectView {
-            //             physics: &mut self.physics,
-            //             pos_x: &mut self.pos_x,
-            //             pos_y: &mut self.pos_y,
-            //         },
-            //     );
-            // }
-            // behaviour.game_loop(
-            //     GameObjectView {
-            //         physics: &mut self.physics,
-            //         pos_x: &mut self.pos_x,
-            //         pos_y: &mut self.pos_y,
-            //     },
-            //     EngineView {
-            //         rigid_body_set,
-            //         narrow_phase,
-            //         event_tx,
-            //         keys_pressed,
-            //         key_locks,
-            //         query_pipeline,
-            //         collider_set,
-            //         frame_delta,
-            //         collision_locks,
-            //     },
-            // );
+        }
+
+        for behaviour in &mut self.user_behaviours {
+            for event in &object_events {
+                if !matches!(event, GameObjectIPC::UserEvent(_)) {
+                    continue;
+                }
+                behaviour.received_event(
+                    event,
+                    EngineView {
+                        rigid_body_set,
+                        narrow_phase,
+                        event_tx,
+                        keys_pressed,
+                        key_locks,
+                        query_pipeline,
+                        collider_set,
+                        frame_delta,
+                        collision_locks,
+                    },
+                    GameObjectView {
+                        id: self.id,
+                        physics: &mut self.physics,
+                        pos_x: &mut self.pos_x,
+                        pos_y: &mut self.pos_y,
+                object_tx: &self.event_tx,
+                    },
+                );
+            }
+            behaviour.game_loop(
+                GameObjectView {
+                    id: self.id,
+                    physics: &mut self.physics,
+                    pos_x: &mut self.pos_x,
+                    pos_y: &mut self.pos_y,
+                object_tx: &self.event_tx,
+                },
+                EngineView {
+                    rigid_body_set,
+                    narrow_phase,
+                    event_tx,
+                    keys_pressed,
+                    key_locks,
+                    query_pipeline,
+                    collider_set,
+                    frame_delta,
+                    collision_locks,
+                },
+            );
         }
 
         if self.physics.rigid_body_handle.is_some() {
             let new_pos = self.get_updated_physics_position(rigid_body_set);
             self.pos_x = new_pos.0;
             self.pos_y = new_pos.1;
+            self.rotation = rigid_body_set
+                .get(self.physics.rigid_body_handle.unwrap())
+                .unwrap()
+                .rotation()
+                .angle();
+        }
+        if let Some(GraphicsType::AnimatedSprite(animated)) = &mut self.graphics {
+            // Handled by on_event next frame, like any other event sent to this object
+            for event in animated.advance(game_delta) {
+                self.event_tx.send(GameObjectIPC::UserEvent(event)).unwrap();
+            }
+        }
+        if let Some(GraphicsType::ParticleEmitter(emitter)) = &mut self.graphics {
+            emitter.update(game_delta, [self.pos_x, self.pos_y], self.rotation);
         }
-        self.render(buffer,sprite_verticies,sprite_indicies,atlas);
     }
 }
 
 pub struct GameObjectBuilder {
     pub graphics: Option<GraphicsType>,
     pub behaviours: Vec<Behaviour>,
+    pub user_behaviours: Vec<Box<dyn UserBehaviour>>,
+    pub subscriptions: HashSet<String>,
     pub pos_x: f32,
     pub pos_y: f32,
+    pub rotation: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub layer: RenderLayer,
+    pub z_index: i32,
     pub pre_rapier_collider: Option<AlcubierreCollider>,
     pub rigid_body: Option<RigidBody>,
+    pub light: Option<Light2D>,
+    /// Given to every behaviour's scope as `properties`
+    pub properties: Map,
 }
 
 impl GameObjectBuilder {
@@ -378,16 +1054,42 @@ impl GameObjectBuilder {
         GameObjectBuilder {
             graphics: None,
             behaviours: vec![],
+            user_behaviours: vec![],
+            subscriptions: HashSet::new(),
             pos_y: 0.0,
             pos_x: 0.0,
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            layer: RenderLayer::World,
+            z_index: 0,
             pre_rapier_collider: None,
             rigid_body: None,
+            light: None,
+            properties: Map::new(),
         }
     }
     pub fn graphics(mut self, graphics: GraphicsType) -> GameObjectBuilder {
         self.graphics = Some(graphics);
         self
     }
+    pub fn rotation(mut self, rotation: f32) -> GameObjectBuilder {
+        self.rotation = rotation;
+        self
+    }
+    pub fn scale(mut self, scale_x: f32, scale_y: f32) -> GameObjectBuilder {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+        self
+    }
+    pub fn layer(mut self, layer: RenderLayer) -> GameObjectBuilder {
+        self.layer = layer;
+        self
+    }
+    pub fn z_index(mut self, z_index: i32) -> GameObjectBuilder {
+        self.z_index = z_index;
+        self
+    }
     pub fn rigid_body(mut self, rigid_body: RigidBody) -> GameObjectBuilder {
         self.rigid_body = Some(rigid_body);
         self
@@ -395,19 +1097,39 @@ impl GameObjectBuilder {
     pub fn behaviour(mut self, behaviourPath: &str) -> GameObjectBuilder {
         let data = fs::read_to_string(behaviourPath).expect("Unable to rea behaviour file");
         //
-        let engine = Engine::new();
+        let engine = create_rhai_engine();
         //TODO: Remove unwrap
         let ast = engine.compile(data).unwrap();
 
         let behaviour = Behaviour {
             ast: Arc::new(ast),
             scope: Scope::new(),
+            coroutines: vec![],
         };
         self.behaviours.push(behaviour);
         self
     }
+    pub fn user_behaviour<B: UserBehaviour>(mut self, behaviour: B) -> GameObjectBuilder {
+        self.user_behaviours.push(Box::new(behaviour));
+        self
+    }
+    pub fn subscribe(mut self, topic: &str) -> GameObjectBuilder {
+        self.subscriptions.insert(topic.to_string());
+        self
+    }
     pub fn collider(mut self, collider: AlcubierreCollider) -> GameObjectBuilder {
         self.pre_rapier_collider = Some(collider);
         self
     }
+    /// Gives off light while the scene has an ambient light. The object's own collider
+    /// doesn't shadow it.
+    pub fn light(mut self, light: Light2D) -> GameObjectBuilder {
+        self.light = Some(light);
+        self
+    }
+    /// Sets `properties.<name>` for the object's scripts.
+    pub fn property(mut self, name: &str, value: Dynamic) -> GameObjectBuilder {
+        self.properties.insert(name.into(), value);
+        self
+    }
 }
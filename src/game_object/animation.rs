@@ -0,0 +1,212 @@
+use crate::game_object::events::{GameEvent, UserEvent};
+use crate::game_object::graphics::SpriteID;
+use crate::tween::LoopMode;
+use hashbrown::HashMap;
+use serde_derive::{Deserialize, Serialize};
+
+/// Frames shorter than this are stretched so a clip can never spin forever in one update.
+const MIN_FRAME_DURATION: f32 = 0.001;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationFrame {
+    pub sprite_id: SpriteID,
+    /// Seconds
+    pub duration: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+    pub frames: Vec<AnimationFrame>,
+    pub loop_mode: LoopMode,
+    /// Playback speed multiplier
+    pub speed: f32,
+}
+
+impl AnimationClip {
+    pub fn new(loop_mode: LoopMode) -> AnimationClip {
+        AnimationClip {
+            frames: vec![],
+            loop_mode,
+            speed: 1.0,
+        }
+    }
+    /// A clip where every frame lasts `1 / fps` seconds.
+    pub fn from_frames(sprite_ids: &[&str], fps: f32, loop_mode: LoopMode) -> AnimationClip {
+        sprite_ids
+            .iter()
+            .fold(AnimationClip::new(loop_mode), |clip, id| clip.frame(id, 1.0 / fps))
+    }
+    pub fn frame(mut self, sprite_id: &str, duration: f32) -> AnimationClip {
+        self.frames.push(AnimationFrame {
+            sprite_id: sprite_id.to_string(),
+            duration,
+        });
+        self
+    }
+    pub fn speed(mut self, speed: f32) -> AnimationClip {
+        self.speed = speed;
+        self
+    }
+}
+
+/// Sent to the animated object whenever the displayed frame changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AnimationFrameChanged {
+    pub clip: String,
+    pub frame: usize,
+}
+
+impl GameEvent for AnimationFrameChanged {
+    const TOPIC: &'static str = "animation_frame";
+}
+
+/// Sent to the animated object when a clip reaches its end. `finished` is false for
+/// looping clips, which carry on playing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AnimationEnded {
+    pub clip: String,
+    pub finished: bool,
+}
+
+impl GameEvent for AnimationEnded {
+    const TOPIC: &'static str = "animation_ended";
+}
+
+/// Playback position within a set of clips.
+#[derive(Clone, Debug, Default)]
+pub struct AnimationPlayer {
+    pub(crate) clip: String,
+    pub(crate) frame: usize,
+    pub(crate) elapsed: f32,
+    pub(crate) playing: bool,
+    pub(crate) reversed: bool,
+}
+
+impl AnimationPlayer {
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    /// Starts `clip` from its first frame.
+    pub(crate) fn play(&mut self, clip: &str) {
+        self.clip = clip.to_string();
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.playing = true;
+        self.reversed = false;
+    }
+    /// Changes clip but keeps the current frame and timing, e.g. for swapping between
+    /// "run" and "run_shoot".
+    pub(crate) fn switch(&mut self, clip: &str, clips: &HashMap<String, AnimationClip>) {
+        self.clip = clip.to_string();
+        let frames = clips.get(clip).map_or(0, |clip| clip.frames.len());
+        if self.frame >= frames {
+            self.frame = 0;
+            self.elapsed = 0.0;
+        }
+        self.playing = true;
+    }
+    pub(crate) fn stop(&mut self) {
+        self.playing = false;
+    }
+    /// Advances by `delta` seconds and returns the events that playback produced: at most one
+    /// frame change and one end, however many frames a long update skips.
+    pub(crate) fn advance(
+        &mut self,
+        clips: &HashMap<String, AnimationClip>,
+        speed: f32,
+        delta: f32,
+    ) -> Vec<UserEvent> {
+        let mut events = vec![];
+        let clip = match clips.get(&self.clip) {
+            Some(clip) if self.playing && !clip.frames.is_empty() => clip,
+            _ => return events,
+        };
+        let last_frame = clip.frames.len() - 1;
+        self.frame = self.frame.min(last_frame);
+        self.elapsed += delta * clip.speed * speed;
+        let start_frame = self.frame;
+        let mut ended = None;
+
+        // Whole cycles end where they started, so skip them rather than stepping through
+        let cycle = cycle_duration(clip);
+        if clip.loop_mode != LoopMode::Once && self.elapsed >= cycle {
+            self.elapsed %= cycle;
+            ended = Some(false);
+        }
+
+        loop {
+            let duration = frame_duration(&clip.frames[self.frame]);
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+
+            let at_end = if self.reversed {
+                self.frame == 0
+            } else {
+                self.frame == last_frame
+            };
+            if !at_end {
+                self.frame = if self.reversed { self.frame - 1 } else { self.frame + 1 };
+            } else {
+                match clip.loop_mode {
+                    LoopMode::Once => {
+                        self.playing = false;
+                        self.elapsed = 0.0;
+                        ended = Some(true);
+                        break;
+                    }
+                    LoopMode::Loop => {
+                        ended = Some(false);
+                        self.frame = 0;
+                    }
+                    LoopMode::PingPong => {
+                        // One cycle is there and back again
+                        if self.reversed {
+                            ended = Some(false);
+                        }
+                        self.reversed = !self.reversed;
+                        if last_frame > 0 {
+                            self.frame = if self.reversed { self.frame - 1 } else { self.frame + 1 };
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.frame != start_frame {
+            events.push(UserEvent::new(AnimationFrameChanged {
+                clip: self.clip.clone(),
+                frame: self.frame,
+            }));
+        }
+        if let Some(finished) = ended {
+            events.push(UserEvent::new(AnimationEnded {
+                clip: self.clip.clone(),
+                finished,
+            }));
+        }
+        events
+    }
+}
+
+fn frame_duration(frame: &AnimationFrame) -> f32 {
+    frame.duration.max(MIN_FRAME_DURATION)
+}
+
+/// Seconds a looping clip takes to come back to where it started. Ping-pong clips play their
+/// inner frames twice, and a single frame twice for there and back.
+fn cycle_duration(clip: &AnimationClip) -> f32 {
+    let forward: f32 = clip.frames.iter().map(frame_duration).sum();
+    match (clip.loop_mode, clip.frames.len()) {
+        (LoopMode::PingPong, 1) => forward * 2.0,
+        (LoopMode::PingPong, frames) => forward + clip.frames[1..frames - 1].iter().map(frame_duration).sum::<f32>(),
+        _ => forward,
+    }
+}
@@ -11,11 +11,16 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::audio::basic::AudioSource;
+use crate::camera::CameraCommand;
 use winit::event::VirtualKeyCode;
 
 use crate::events::PullGameObjectRequest;
+use crate::game_object::events::{GameEvent, UserEvent};
+use crate::game_object::graphics::RenderLayer;
 use crate::game_object::{GameObject, GameObjectIPC, GameObjectView};
 use crate::physics::screen_units_to_physics_units;
+use crate::timers::{Timer, TimerAction, TimerHandle};
+use crate::tween::{Tween, TweenHandle};
 use crate::EngineEvent;
 
 pub trait UserBehaviour: UserBehaviourClone {
@@ -79,9 +84,84 @@ impl<'a> EngineView<'a> {
         }
         None
     }
-    pub fn notify_global(&self, event: &[u8]) {
-        let ev = event.to_vec();
-        self.event_tx.send(EngineEvent::UserEvent(ev)).unwrap();
+    /// Publishes an event to every GameObject subscribed to its topic.
+    pub fn publish<E: GameEvent>(&self, event: E) {
+        self.event_tx
+            .send(EngineEvent::UserEvent(UserEvent::new(event)))
+            .unwrap();
+    }
+    /// Sends an event to a single GameObject, whether or not it is subscribed.
+    pub fn publish_to<E: GameEvent>(&self, object_id: u128, event: E) {
+        self.event_tx
+            .send(EngineEvent::UserEvent(
+                UserEvent::new(event).with_target(object_id),
+            ))
+            .unwrap();
+    }
+    pub fn subscribe(&self, object_id: u128, topic: &str) {
+        self.event_tx
+            .send(EngineEvent::Subscribe((object_id, topic.to_string())))
+            .unwrap();
+    }
+    pub fn unsubscribe(&self, object_id: u128, topic: &str) {
+        self.event_tx
+            .send(EngineEvent::Unsubscribe((object_id, topic.to_string())))
+            .unwrap();
+    }
+    /// Runs `action` once after `seconds` of game time. Events are sent to `owner`.
+    pub fn after(&self, owner: u128, seconds: f32, action: TimerAction) -> TimerHandle {
+        let timer = Timer::new(owner, seconds, false, action);
+        let handle = timer.handle;
+        self.event_tx.send(EngineEvent::StartTimer(timer)).unwrap();
+        handle
+    }
+    /// Runs `action` every `seconds` of game time until cancelled.
+    pub fn every(&self, owner: u128, seconds: f32, action: TimerAction) -> TimerHandle {
+        let timer = Timer::new(owner, seconds, true, action);
+        let handle = timer.handle;
+        self.event_tx.send(EngineEvent::StartTimer(timer)).unwrap();
+        handle
+    }
+    pub fn cancel_timer(&self, handle: TimerHandle) {
+        self.event_tx.send(EngineEvent::CancelTimer(handle)).unwrap();
+    }
+    pub fn set_time_scale(&self, time_scale: f32) {
+        self.event_tx
+            .send(EngineEvent::SetTimeScale(time_scale))
+            .unwrap();
+    }
+    pub fn set_paused(&self, paused: bool) {
+        self.event_tx.send(EngineEvent::SetPaused(paused)).unwrap();
+    }
+    pub fn set_layer_visible(&mut self, layer: RenderLayer, visible: bool) {
+        self.event_tx.send(EngineEvent::SetLayerVisible(layer, visible)).unwrap();
+    }
+    /// Moves, zooms, shakes or otherwise changes the active scene's main camera.
+    pub fn camera(&self, command: CameraCommand) {
+        self.camera_at(0, command);
+    }
+    /// Changes one of the active scene's cameras, by the index `Scene::add_camera` returned.
+    pub fn camera_at(&self, index: usize, command: CameraCommand) {
+        self.event_tx.send(EngineEvent::Camera(index, command)).unwrap();
+    }
+    /// Changes a parameter of one of the active scene's post passes.
+    pub fn set_post_param(&self, pass: &str, param: &str, value: f32) {
+        self.event_tx
+            .send(EngineEvent::SetPostParam(pass.to_string(), param.to_string(), value))
+            .unwrap();
+    }
+    pub fn set_post_pass_enabled(&self, pass: &str, enabled: bool) {
+        self.event_tx
+            .send(EngineEvent::SetPostPassEnabled(pass.to_string(), enabled))
+            .unwrap();
+    }
+    pub fn tween(&self, tween: Tween) -> TweenHandle {
+        let handle = tween.handle;
+        self.event_tx.send(EngineEvent::StartTween(tween)).unwrap();
+        handle
+    }
+    pub fn stop_tween(&self, handle: TweenHandle) {
+        self.event_tx.send(EngineEvent::StopTween(handle)).unwrap();
     }
     pub fn is_colliding_with_sensor_once(
         &mut self,
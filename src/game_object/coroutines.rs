@@ -0,0 +1,136 @@
+// Coroutines for Rhai behaviours.
+//
+// Rhai can't suspend a running function, so a coroutine is re-run from the top every time it
+// resumes. Waits that already finished return straight away and engine calls made before them are
+// skipped, so the script picks up where it left off. Engine calls that return a value, like timer and
+// tween handles, give back what they returned on the first run. Control flow before a `wait` must
+// therefore be deterministic, and scope variables shouldn't be mutated before a `wait`.
+
+use rhai::{EvalAltResult, Position};
+use std::cell::RefCell;
+
+const YIELD_TOKEN: &str = "__alcubierre_yield";
+
+#[derive(Clone)]
+pub(crate) struct Coroutine {
+    pub(crate) function: String,
+    waits_passed: usize,
+    remaining: f32,
+    /// Handles engine calls returned so far, in call order
+    handles: Vec<i64>,
+}
+
+struct CoroutineCursor {
+    skip: usize,
+    seen: usize,
+    yielded: Option<f32>,
+    handles: Vec<i64>,
+    calls: usize,
+}
+
+thread_local! {
+    static CURSOR: RefCell<Option<CoroutineCursor>> = const { RefCell::new(None) };
+}
+
+pub(crate) enum CoroutineState {
+    Suspended,
+    Finished,
+}
+
+impl Coroutine {
+    pub(crate) fn new(function: &str) -> Self {
+        Coroutine {
+            function: function.to_string(),
+            waits_passed: 0,
+            remaining: 0.0,
+            handles: vec![],
+        }
+    }
+
+    /// Counts down the current wait. Returns true if the coroutine should run this frame.
+    pub(crate) fn tick(&mut self, delta: f32) -> bool {
+        if delta <= 0.0 {
+            return false;
+        }
+        self.remaining -= delta;
+        self.remaining <= 0.0
+    }
+
+    /// Runs one step of the coroutine. `run` should call the coroutine's function.
+    pub(crate) fn resume<T>(
+        &mut self,
+        run: impl FnOnce() -> Result<T, Box<EvalAltResult>>,
+    ) -> Result<CoroutineState, Box<EvalAltResult>> {
+        CURSOR.with(|cursor| {
+            *cursor.borrow_mut() = Some(CoroutineCursor {
+                skip: self.waits_passed,
+                seen: 0,
+                yielded: None,
+                handles: std::mem::take(&mut self.handles),
+                calls: 0,
+            })
+        });
+        let result = run();
+        let cursor = CURSOR.with(|cursor| cursor.borrow_mut().take()).unwrap();
+        self.handles = cursor.handles;
+
+        match result {
+            Ok(_) => Ok(CoroutineState::Finished),
+            Err(e) => match *e {
+                EvalAltResult::ErrorTerminated(ref token, _)
+                    if token.clone().into_string().as_deref() == Ok(YIELD_TOKEN) =>
+                {
+                    self.waits_passed += 1;
+                    self.remaining = cursor.yielded.unwrap_or(0.0);
+                    Ok(CoroutineState::Suspended)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+/// True while a coroutine is fast-forwarding through waits it already finished.
+pub(crate) fn is_replaying() -> bool {
+    CURSOR.with(|cursor| {
+        cursor
+            .borrow()
+            .as_ref()
+            .is_some_and(|cursor| cursor.seen < cursor.skip)
+    })
+}
+
+/// The handle an engine call returned when the coroutine first ran it, if it has run it before.
+/// Calls that get `None` should issue a new handle and pass it to `record_handle`.
+pub(crate) fn replayed_handle() -> Option<i64> {
+    CURSOR.with(|cursor| {
+        let mut cursor = cursor.borrow_mut();
+        let cursor = cursor.as_mut()?;
+        cursor.calls += 1;
+        cursor.handles.get(cursor.calls - 1).copied()
+    })
+}
+
+pub(crate) fn record_handle(handle: i64) {
+    CURSOR.with(|cursor| {
+        if let Some(cursor) = cursor.borrow_mut().as_mut() {
+            cursor.handles.push(handle);
+        }
+    })
+}
+
+pub(crate) fn wait(seconds: f64) -> Result<(), Box<EvalAltResult>> {
+    CURSOR.with(|cursor| {
+        let mut cursor = cursor.borrow_mut();
+        let cursor = match cursor.as_mut() {
+            Some(cursor) => cursor,
+            None => return Err("wait() can only be called from a coroutine".into()),
+        };
+        cursor.seen += 1;
+        if cursor.seen <= cursor.skip {
+            return Ok(());
+        }
+        cursor.yielded = Some(seconds as f32);
+        Err(EvalAltResult::ErrorTerminated(YIELD_TOKEN.into(), Position::NONE).into())
+    })
+}
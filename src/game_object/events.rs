@@ -0,0 +1,143 @@
+use rhai::{Array, Dynamic, Map};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Number, Value};
+use std::any::Any;
+use std::sync::Arc;
+
+/// A typed event that can be sent over the event bus.
+///
+/// Objects subscribe to events by `TOPIC`. The serde form of the event is what
+/// Rhai behaviours receive in their `on_event` handler.
+pub trait GameEvent: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {
+    const TOPIC: &'static str;
+}
+
+/// An event travelling over the event bus. Created from a `GameEvent` in Rust or from a
+/// topic and a value in Rhai.
+#[derive(Clone)]
+pub struct UserEvent {
+    topic: String,
+    sender: Option<u128>,
+    pub(crate) target: Option<u128>,
+    payload: Option<Arc<dyn Any + Send + Sync>>,
+    data: Value,
+}
+
+impl UserEvent {
+    pub fn new<E: GameEvent>(event: E) -> Self {
+        let data = serde_json::to_value(&event).unwrap_or(Value::Null);
+        UserEvent {
+            topic: E::TOPIC.to_string(),
+            sender: None,
+            target: None,
+            payload: Some(Arc::new(event)),
+            data,
+        }
+    }
+    pub fn from_value(topic: &str, data: Value) -> Self {
+        UserEvent {
+            topic: topic.to_string(),
+            sender: None,
+            target: None,
+            payload: None,
+            data,
+        }
+    }
+    pub(crate) fn with_sender(mut self, sender: u128) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+    pub(crate) fn with_target(mut self, target: u128) -> Self {
+        self.target = Some(target);
+        self
+    }
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+    /// ID of the GameObject that sent this event. `None` if it came from the engine or Rust code.
+    pub fn sender(&self) -> Option<u128> {
+        self.sender
+    }
+    pub fn data(&self) -> &Value {
+        &self.data
+    }
+    pub fn is<E: GameEvent>(&self) -> bool {
+        self.topic == E::TOPIC
+    }
+    /// Reads the event back as `E`. Events published from Rhai are deserialized from their data.
+    pub fn read<E: GameEvent>(&self) -> Option<E> {
+        if !self.is::<E>() {
+            return None;
+        }
+        if let Some(event) = self
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.downcast_ref::<E>())
+        {
+            return Some(event.clone());
+        }
+        serde_json::from_value(self.data.clone()).ok()
+    }
+    pub(crate) fn to_rhai(&self) -> Dynamic {
+        let mut map = Map::new();
+        map.insert("topic".into(), self.topic.clone().into());
+        map.insert(
+            "sender".into(),
+            match self.sender {
+                Some(sender) => Dynamic::from_int(sender as i64),
+                None => Dynamic::UNIT,
+            },
+        );
+        map.insert("data".into(), json_to_dynamic(&self.data));
+        Dynamic::from_map(map)
+    }
+}
+
+pub(crate) fn json_to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from_bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Dynamic::from_int(i),
+            None => Dynamic::from_float(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => s.clone().into(),
+        Value::Array(values) => {
+            Dynamic::from_array(values.iter().map(json_to_dynamic).collect::<Array>())
+        }
+        Value::Object(values) => {
+            let mut map = Map::new();
+            for (key, value) in values {
+                map.insert(key.as_str().into(), json_to_dynamic(value));
+            }
+            Dynamic::from_map(map)
+        }
+    }
+}
+
+pub(crate) fn dynamic_to_json(value: &Dynamic) -> Value {
+    if value.is_unit() {
+        Value::Null
+    } else if let Ok(b) = value.as_bool() {
+        Value::Bool(b)
+    } else if let Ok(i) = value.as_int() {
+        Value::Number(i.into())
+    } else if let Ok(f) = value.as_float() {
+        Number::from_f64(f).map_or(Value::Null, Value::Number)
+    } else if value.is_string() {
+        Value::String(value.clone().into_string().unwrap_or_default())
+    } else if value.is_array() {
+        let array = value.clone().into_array().unwrap_or_default();
+        Value::Array(array.iter().map(dynamic_to_json).collect())
+    } else if value.is_map() {
+        let map = value.clone().try_cast::<Map>().unwrap_or_default();
+        Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.to_string(), dynamic_to_json(value)))
+                .collect(),
+        )
+    } else {
+        Value::String(value.to_string())
+    }
+}
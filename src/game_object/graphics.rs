@@ -1,84 +1,827 @@
+use crate::game_object::animation::{AnimationClip, AnimationPlayer};
+use crate::game_object::events::UserEvent;
+use crate::game_object::particles::{ParticleEmitterData, ParticleShape};
+use crate::game_object::tilemap::TilemapData;
 use crate::game_object::GameObject;
-use crate::renderer::atlas::{AtlasVector2, SpriteAtlas};
-use crate::renderer::buffer::QuadBufferBuilder;
+use crate::light::Occluder;
+use crate::renderer::atlas::{AtlasVector2, SpriteAtlas, DEFAULT_ATLAS};
+use crate::renderer::buffer::{linear_rgba, srgb_to_linear, QuadBufferBuilder, Vertex};
+use crate::renderer::light::light_triangles;
+use crate::renderer::material::{MaterialInstance, MaterialVertex};
+use crate::renderer::shapes::{circle_points, polyline, rect_points, rotate_points, triangulate};
 use crate::renderer::sprite::SpriteVertex;
 use crate::ui::frontend::RGBColor;
+use hashbrown::HashMap;
 
 pub type SpriteID = String;
 
+const WHITE: RGBColor = RGBColor {
+    red: 255,
+    green: 255,
+    blue: 255,
+    alpha: 255,
+};
+
+/// How a graphic is combined with whatever has already been drawn beneath it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Regular transparency
+    #[default]
+    Alpha,
+    /// Adds onto what is beneath, for glows, fire and lasers
+    Additive,
+    /// For textures whose colours have already been multiplied by their alpha
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub(crate) const ALL: [BlendMode; 3] = [BlendMode::Alpha, BlendMode::Additive, BlendMode::Premultiplied];
+
+    pub fn from_name(name: &str) -> Option<BlendMode> {
+        match name {
+            "alpha" => Some(BlendMode::Alpha),
+            "additive" => Some(BlendMode::Additive),
+            "premultiplied" => Some(BlendMode::Premultiplied),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn blend_state(self) -> wgpu::BlendState {
+        match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                // Leave the destination's coverage alone
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Zero,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+            },
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        }
+    }
+
+    /// Premultiplied draws use a fragment entry point that treats colours as premultiplied.
+    pub(crate) fn fragment_entry_point(self) -> &'static str {
+        match self {
+            BlendMode::Alpha | BlendMode::Additive => "fs_main",
+            BlendMode::Premultiplied => "fs_premultiplied",
+        }
+    }
+}
+
+/// Named sorting layers, drawn back to front in this order. Within a layer objects are
+/// sorted by `z_index`, then by the order they were added.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RenderLayer {
+    Background,
+    #[default]
+    World,
+    Foreground,
+    Overlay,
+}
+
+impl RenderLayer {
+    pub const ALL: [RenderLayer; 4] = [
+        RenderLayer::Background,
+        RenderLayer::World,
+        RenderLayer::Foreground,
+        RenderLayer::Overlay,
+    ];
+
+    pub fn from_name(name: &str) -> Option<RenderLayer> {
+        match name {
+            "background" => Some(RenderLayer::Background),
+            "world" => Some(RenderLayer::World),
+            "foreground" => Some(RenderLayer::Foreground),
+            "overlay" => Some(RenderLayer::Overlay),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SpriteData {
     pub sprite_id: SpriteID,
     pub width: f32,
     pub height: f32,
     pub flip_h: bool,
-    pub flip_v: bool
+    pub flip_v: bool,
+    /// Atlas or texture to draw from. `None` uses the default atlas.
+    pub atlas: Option<String>,
+    /// Point placed on the object's position, from (0, 0) bottom-left to (1, 1) top-right.
+    /// `None` uses the atlas origin.
+    pub pivot: Option<[f32; 2]>,
+    /// Multiplied with the texture. White leaves it unchanged.
+    pub tint: RGBColor,
+    pub opacity: f32,
+    /// Pushes the texture towards white, from 0 (none) to 1 (solid white)
+    pub flash: f32,
+    pub blend_mode: BlendMode,
+    /// Draws with a custom shader instead of the sprite one
+    pub material: Option<MaterialInstance>,
+}
+
+impl SpriteData {
+    pub fn new(sprite_id: &str, width: f32, height: f32) -> SpriteData {
+        SpriteData {
+            sprite_id: sprite_id.to_string(),
+            width,
+            height,
+            flip_h: false,
+            flip_v: false,
+            atlas: None,
+            pivot: None,
+            tint: WHITE,
+            opacity: 1.0,
+            flash: 0.0,
+            blend_mode: BlendMode::Alpha,
+            material: None,
+        }
+    }
+    /// A sprite showing the whole of a texture loaded with `Engine::load_texture`.
+    pub fn texture(texture: &str, width: f32, height: f32) -> SpriteData {
+        SpriteData::new(texture, width, height).atlas(texture)
+    }
+    pub fn atlas(mut self, atlas: &str) -> SpriteData {
+        self.atlas = Some(atlas.to_string());
+        self
+    }
+    pub fn pivot(mut self, x: f32, y: f32) -> SpriteData {
+        self.pivot = Some([x, y]);
+        self
+    }
+    pub fn tint(mut self, tint: RGBColor) -> SpriteData {
+        self.tint = tint;
+        self
+    }
+    pub fn opacity(mut self, opacity: f32) -> SpriteData {
+        self.opacity = opacity;
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> SpriteData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn material(mut self, material: MaterialInstance) -> SpriteData {
+        self.material = Some(material);
+        self
+    }
+    /// Colours a quad of the sprite with its tint, faded by its opacity, and its flash.
+    pub fn color_vertices(&self, vertices: &mut [SpriteVertex]) {
+        let color = [
+            srgb_to_linear(self.tint.red),
+            srgb_to_linear(self.tint.green),
+            srgb_to_linear(self.tint.blue),
+            self.opacity.clamp(0.0, 1.0) * self.tint.alpha as f32 / 255.0,
+        ];
+        for vertex in vertices {
+            vertex.color = color;
+            vertex.flash = self.flash.clamp(0.0, 1.0);
+        }
+    }
+}
+#[derive(Clone)]
+pub struct AnimatedSpriteData {
+    pub clips: HashMap<String, AnimationClip>,
+    pub width: f32,
+    pub height: f32,
+    pub flip_h: bool,
+    pub flip_v: bool,
+    /// Playback speed multiplier applied on top of each clip's own speed
+    pub speed: f32,
+    pub atlas: Option<String>,
+    pub pivot: Option<[f32; 2]>,
+    pub tint: RGBColor,
+    pub opacity: f32,
+    pub flash: f32,
+    pub blend_mode: BlendMode,
+    pub material: Option<MaterialInstance>,
+    pub(crate) player: AnimationPlayer,
+}
+
+impl AnimatedSpriteData {
+    pub fn new(width: f32, height: f32) -> AnimatedSpriteData {
+        AnimatedSpriteData {
+            clips: HashMap::new(),
+            width,
+            height,
+            flip_h: false,
+            flip_v: false,
+            speed: 1.0,
+            atlas: None,
+            pivot: None,
+            tint: WHITE,
+            opacity: 1.0,
+            flash: 0.0,
+            blend_mode: BlendMode::Alpha,
+            material: None,
+            player: AnimationPlayer::default(),
+        }
+    }
+    pub fn clip(mut self, name: &str, clip: AnimationClip) -> AnimatedSpriteData {
+        self.clips.insert(name.to_string(), clip);
+        self
+    }
+    pub fn atlas(mut self, atlas: &str) -> AnimatedSpriteData {
+        self.atlas = Some(atlas.to_string());
+        self
+    }
+    pub fn pivot(mut self, x: f32, y: f32) -> AnimatedSpriteData {
+        self.pivot = Some([x, y]);
+        self
+    }
+    pub fn tint(mut self, tint: RGBColor) -> AnimatedSpriteData {
+        self.tint = tint;
+        self
+    }
+    pub fn opacity(mut self, opacity: f32) -> AnimatedSpriteData {
+        self.opacity = opacity;
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> AnimatedSpriteData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn material(mut self, material: MaterialInstance) -> AnimatedSpriteData {
+        self.material = Some(material);
+        self
+    }
+    /// Clip to start playing as soon as the object exists.
+    pub fn autoplay(mut self, name: &str) -> AnimatedSpriteData {
+        self.player.play(name);
+        self
+    }
+    pub fn play(&mut self, name: &str) {
+        self.player.play(name);
+    }
+    pub fn switch_clip(&mut self, name: &str) {
+        self.player.switch(name, &self.clips);
+    }
+    pub fn stop(&mut self) {
+        self.player.stop();
+    }
+    /// Advances playback by `delta` seconds of game time, returning frame and clip end events.
+    /// The engine calls this every frame.
+    pub fn advance(&mut self, delta: f32) -> Vec<UserEvent> {
+        self.player.advance(&self.clips, self.speed, delta)
+    }
+    pub fn player(&self) -> &AnimationPlayer {
+        &self.player
+    }
+    pub fn current_sprite_id(&self) -> Option<&SpriteID> {
+        let clip = self.clips.get(self.player.clip())?;
+        clip.frames.get(self.player.frame()).map(|frame| &frame.sprite_id)
+    }
 }
 #[derive(Clone)]
 pub struct CircleData {
     pub radius: f32,
     pub color: RGBColor,
+    /// Draws only an outline this thick instead of filling the shape
+    pub outline: Option<f32>,
+    pub blend_mode: BlendMode,
+    /// Draws with a custom shader, `in.uv` spanning the circle's bounding box
+    pub material: Option<MaterialInstance>,
+}
+
+impl CircleData {
+    pub fn new(radius: f32, color: RGBColor) -> CircleData {
+        CircleData {
+            radius,
+            color,
+            outline: None,
+            blend_mode: BlendMode::Alpha,
+            material: None,
+        }
+    }
+    pub fn outline(mut self, thickness: f32) -> CircleData {
+        self.outline = Some(thickness);
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> CircleData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn material(mut self, material: MaterialInstance) -> CircleData {
+        self.material = Some(material);
+        self
+    }
 }
 #[derive(Clone)]
 pub struct RectData {
     pub color: RGBColor,
     pub width: f32,
     pub height: f32,
+    /// Radius of the rounded corners, 0 for square ones
+    pub corner_radius: f32,
+    pub outline: Option<f32>,
+    pub blend_mode: BlendMode,
+    /// Draws with a custom shader, `in.uv` spanning the rect
+    pub material: Option<MaterialInstance>,
 }
+
+impl RectData {
+    pub fn new(width: f32, height: f32, color: RGBColor) -> RectData {
+        RectData {
+            color,
+            width,
+            height,
+            corner_radius: 0.0,
+            outline: None,
+            blend_mode: BlendMode::Alpha,
+            material: None,
+        }
+    }
+    pub fn corner_radius(mut self, radius: f32) -> RectData {
+        self.corner_radius = radius;
+        self
+    }
+    pub fn outline(mut self, thickness: f32) -> RectData {
+        self.outline = Some(thickness);
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> RectData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn material(mut self, material: MaterialInstance) -> RectData {
+        self.material = Some(material);
+        self
+    }
+}
+/// An equilateral triangle pointing up, sized by the distance from its centre to its corners.
 #[derive(Clone)]
 pub struct TriangleData {
     pub radius: f32,
     pub color: RGBColor,
+    pub outline: Option<f32>,
+    pub blend_mode: BlendMode,
+}
+
+impl TriangleData {
+    pub fn new(radius: f32, color: RGBColor) -> TriangleData {
+        TriangleData {
+            radius,
+            color,
+            outline: None,
+            blend_mode: BlendMode::Alpha,
+        }
+    }
+    pub fn outline(mut self, thickness: f32) -> TriangleData {
+        self.outline = Some(thickness);
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> TriangleData {
+        self.blend_mode = blend_mode;
+        self
+    }
+}
+/// Any simple polygon, convex or concave. Points are relative to the object's position.
+#[derive(Clone)]
+pub struct PolygonData {
+    pub points: Vec<[f32; 2]>,
+    pub color: RGBColor,
+    pub outline: Option<f32>,
+    pub blend_mode: BlendMode,
+}
+
+impl PolygonData {
+    pub fn new(points: &[[f32; 2]], color: RGBColor) -> PolygonData {
+        PolygonData {
+            points: points.to_vec(),
+            color,
+            outline: None,
+            blend_mode: BlendMode::Alpha,
+        }
+    }
+    pub fn outline(mut self, thickness: f32) -> PolygonData {
+        self.outline = Some(thickness);
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> PolygonData {
+        self.blend_mode = blend_mode;
+        self
+    }
+}
+/// A line segment or polyline. Points are relative to the object's position.
+#[derive(Clone)]
+pub struct LineData {
+    pub points: Vec<[f32; 2]>,
+    pub thickness: f32,
+    /// Joins the last point back to the first
+    pub closed: bool,
+    pub color: RGBColor,
+    pub blend_mode: BlendMode,
+}
+
+impl LineData {
+    pub fn segment(from: [f32; 2], to: [f32; 2], thickness: f32, color: RGBColor) -> LineData {
+        LineData::polyline(&[from, to], thickness, color)
+    }
+    pub fn polyline(points: &[[f32; 2]], thickness: f32, color: RGBColor) -> LineData {
+        LineData {
+            points: points.to_vec(),
+            thickness,
+            closed: false,
+            color,
+            blend_mode: BlendMode::Alpha,
+        }
+    }
+    pub fn closed(mut self) -> LineData {
+        self.closed = true;
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> LineData {
+        self.blend_mode = blend_mode;
+        self
+    }
 }
 #[derive(Clone)]
 pub enum GraphicsType {
     Sprite(SpriteData),
+    AnimatedSprite(AnimatedSpriteData),
     Circle(CircleData),
     Rect(RectData),
     Triangle(TriangleData),
+    Polygon(PolygonData),
+    Line(LineData),
+    Tilemap(TilemapData),
+    ParticleEmitter(ParticleEmitterData),
+}
+
+impl GraphicsType {
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        match self {
+            GraphicsType::Sprite(sprite) => sprite.blend_mode = blend_mode,
+            GraphicsType::AnimatedSprite(animated) => animated.blend_mode = blend_mode,
+            GraphicsType::Circle(circle) => circle.blend_mode = blend_mode,
+            GraphicsType::Rect(rect) => rect.blend_mode = blend_mode,
+            GraphicsType::Triangle(triangle) => triangle.blend_mode = blend_mode,
+            GraphicsType::Polygon(polygon) => polygon.blend_mode = blend_mode,
+            GraphicsType::Line(line) => line.blend_mode = blend_mode,
+            GraphicsType::Tilemap(tilemap) => tilemap.blend_mode = blend_mode,
+            GraphicsType::ParticleEmitter(emitter) => emitter.blend_mode = blend_mode,
+        }
+    }
+    /// Changes a parameter of the graphic's material, if it has one.
+    pub fn set_material_param(&mut self, name: &str, value: f32) {
+        let material = match self {
+            GraphicsType::Sprite(sprite) => &mut sprite.material,
+            GraphicsType::AnimatedSprite(animated) => &mut animated.material,
+            GraphicsType::Circle(circle) => &mut circle.material,
+            GraphicsType::Rect(rect) => &mut rect.material,
+            _ => return,
+        };
+        if let Some(material) = material {
+            material.set_param(name, value);
+        }
+    }
 }
 
 pub trait Graphics {
     fn add_graphics(&mut self, graphics_type: GraphicsType);
-    fn render(&mut self, buffer: &mut QuadBufferBuilder,sprite_verticies: &mut Vec<SpriteVertex>,
-              sprite_indicies: &mut Vec<u16>,atlas: &Option<SpriteAtlas>);
+    fn render(&mut self, buffer: &mut QuadBufferBuilder, atlases: &HashMap<String, SpriteAtlas>);
 }
 
 impl Graphics for GameObject {
     fn add_graphics(&mut self, graphics_type: GraphicsType) {
         self.graphics = Some(graphics_type);
     }
-    fn render(&mut self, buffer: &mut QuadBufferBuilder,sprite_verticies: &mut Vec<SpriteVertex>,
-              sprite_indicies: &mut Vec<u16>,atlas: &Option<SpriteAtlas>) {
+    fn render(&mut self, buffer: &mut QuadBufferBuilder, atlases: &HashMap<String, SpriteAtlas>) {
+        if let Some(GraphicsType::Tilemap(tilemap)) = &mut self.graphics {
+            if !tilemap.visible {
+                return;
+            }
+            match atlas_named(atlases, tilemap.atlas.as_deref()) {
+                Some(atlas) => tilemap.update_chunks(atlas),
+                None => {
+                    if !self.missing_graphics_logged.replace(true) {
+                        log::warn!("Not drawing tilemap from atlas {}, which isn't loaded", atlas_name(tilemap.atlas.as_deref()));
+                    }
+                    return;
+                }
+            }
+        }
         match &self.graphics {
             Some(graphics) => match graphics {
                 GraphicsType::Sprite(sprite) => {
-                    if atlas.is_none() {
-                        panic!("No atlas configured!"); //TODO: Proper error handling
+                    self.push_sprite(sprite, buffer, atlases);
+                }
+                GraphicsType::AnimatedSprite(animated) => {
+                    if let Some(sprite_id) = animated.current_sprite_id() {
+                        let sprite = SpriteData {
+                            sprite_id: sprite_id.clone(),
+                            width: animated.width,
+                            height: animated.height,
+                            flip_h: animated.flip_h,
+                            flip_v: animated.flip_v,
+                            atlas: animated.atlas.clone(),
+                            pivot: animated.pivot,
+                            tint: animated.tint.clone(),
+                            opacity: animated.opacity,
+                            flash: animated.flash,
+                            blend_mode: animated.blend_mode,
+                            material: animated.material.clone(),
+                        };
+                        self.push_sprite(&sprite, buffer, atlases);
                     }
-                    let safe_atlas = atlas.as_ref().unwrap();
-                    let sprite_data = safe_atlas.lookup_sprite_data_from_descriptor(&sprite.sprite_id);
-                    let sprite = safe_atlas.get_sprite_from_atlas(&sprite_data.position,&sprite_data.sourceSize,[self.pos_x,self.pos_y],[sprite.width,sprite.height],sprite.flip_h,sprite.flip_v);
-                    sprite_verticies.extend_from_slice(&sprite.0);
-                    sprite_indicies.extend_from_slice(&sprite.1)
                 }
                 GraphicsType::Circle(circle) => {
-                    buffer.push_circle(self.pos_x, self.pos_y, circle.radius, &circle.color, 60);
+                    buffer.set_blend_mode(circle.blend_mode);
+                    let radius = circle.radius * self.scale_x.max(self.scale_y);
+                    match (&circle.material, circle.outline) {
+                        (Some(material), outline) => {
+                            let points = circle_points(self.pos_x, self.pos_y, radius, 60);
+                            self.push_material_shape(buffer, &points, outline, &circle.color, material, circle.blend_mode);
+                        }
+                        (None, Some(thickness)) => {
+                            buffer.push_circle_outline(self.pos_x, self.pos_y, radius, thickness, &circle.color, 60)
+                        }
+                        (None, None) => buffer.push_circle(self.pos_x, self.pos_y, radius, &circle.color, 60),
+                    }
                 }
                 GraphicsType::Rect(square) => {
-                    buffer.push_rect(
-                        self.pos_x,
-                        self.pos_y,
-                        square.width,
-                        square.height,
-                        &square.color,
-                    );
+                    buffer.set_blend_mode(square.blend_mode);
+                    if let Some(material) = &square.material {
+                        let points = rect_points(
+                            self.pos_x,
+                            self.pos_y,
+                            square.width * self.scale_x,
+                            square.height * self.scale_y,
+                            square.corner_radius * self.scale_x.min(self.scale_y),
+                            8,
+                        );
+                        self.push_material_shape(buffer, &points, square.outline, &square.color, material, square.blend_mode);
+                    } else if square.corner_radius <= 0.0 && square.outline.is_none() {
+                        buffer.push_rotated_rect(
+                            self.pos_x,
+                            self.pos_y,
+                            square.width * self.scale_x,
+                            square.height * self.scale_y,
+                            self.rotation,
+                            &square.color,
+                        );
+                    } else {
+                        let mut points = rect_points(
+                            self.pos_x,
+                            self.pos_y,
+                            square.width * self.scale_x,
+                            square.height * self.scale_y,
+                            square.corner_radius * self.scale_x.min(self.scale_y),
+                            8,
+                        );
+                        rotate_points(&mut points, self.pos_x, self.pos_y, self.rotation);
+                        push_shape(buffer, &points, square.outline, &square.color);
+                    }
                 }
-                GraphicsType::Triangle(_triangle) => {
-                    todo!()
+                GraphicsType::Triangle(triangle) => {
+                    buffer.set_blend_mode(triangle.blend_mode);
+                    let corners = [90.0_f32, 210.0, 330.0].map(|degrees| {
+                        let (sin, cos) = degrees.to_radians().sin_cos();
+                        [triangle.radius * cos, triangle.radius * sin]
+                    });
+                    push_shape(buffer, &self.local_to_world(&corners), triangle.outline, &triangle.color);
                 }
+                GraphicsType::Polygon(polygon) => {
+                    buffer.set_blend_mode(polygon.blend_mode);
+                    push_shape(buffer, &self.local_to_world(&polygon.points), polygon.outline, &polygon.color);
+                }
+                GraphicsType::Line(line) => {
+                    buffer.set_blend_mode(line.blend_mode);
+                    buffer.push_polyline(&self.local_to_world(&line.points), line.thickness, line.closed, &line.color);
+                }
+                GraphicsType::Tilemap(tilemap) => self.push_tilemap(tilemap, buffer),
+                GraphicsType::ParticleEmitter(emitter) => push_particles(emitter, buffer, atlases),
             },
             None => {}
         }
     }
 }
+
+/// The atlas a graphic names, falling back to the default one.
+fn atlas_name(name: Option<&str>) -> &str {
+    name.unwrap_or(DEFAULT_ATLAS)
+}
+
+fn atlas_named<'a>(atlases: &'a HashMap<String, SpriteAtlas>, name: Option<&str>) -> Option<&'a SpriteAtlas> {
+    atlases.get(atlas_name(name))
+}
+
+/// Draws every live particle of an emitter in one batch, skipping it when none can be seen.
+fn push_particles(emitter: &ParticleEmitterData, buffer: &mut QuadBufferBuilder, atlases: &HashMap<String, SpriteAtlas>) {
+    let particles = emitter.particles();
+    let sizes: Vec<f32> = particles.iter().map(|particle| emitter.size_at(particle.progress())).collect();
+    let bounds = particles.iter().zip(&sizes).fold(
+        [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+        |bounds, (particle, size)| {
+            let [x, y] = particle.position;
+            [bounds[0].min(x - size), bounds[1].min(y - size), bounds[2].max(x + size), bounds[3].max(y + size)]
+        },
+    );
+    if particles.is_empty() || !buffer.in_view(bounds) {
+        return;
+    }
+    let colors = particles
+        .iter()
+        .map(|particle| linear_rgba(&emitter.color_at(particle.progress())));
+    let mut indices = vec![];
+    match &emitter.shape {
+        ParticleShape::Sprite(sprite_id) => {
+            let Some(atlas) = atlas_named(atlases, emitter.atlas.as_deref()) else {
+                if !emitter.missing_sprite_logged.replace(true) {
+                    log::warn!("Not drawing particles from atlas {}, which isn't loaded", atlas_name(emitter.atlas.as_deref()));
+                }
+                return;
+            };
+            let Some(sprite) = atlas.sprite(sprite_id) else {
+                if !emitter.missing_sprite_logged.replace(true) {
+                    log::warn!("Not drawing particles of sprite {}, which isn't in the atlas", sprite_id);
+                }
+                return;
+            };
+            let mut vertices = Vec::with_capacity(particles.len() * 4);
+            for ((particle, size), color) in particles.iter().zip(&sizes).zip(colors) {
+                let (quad, quad_indices) =
+                    atlas.get_sprite_from_atlas(sprite, particle.position, [*size, *size], Some([0.5, 0.5]), false, false);
+                let base = vertices.len() as u32;
+                vertices.extend(quad.map(|mut vertex| {
+                    vertex.color = color;
+                    vertex.flash = 0.0;
+                    vertex
+                }));
+                indices.extend(quad_indices.iter().map(|index| base + index));
+            }
+            buffer.push_sprites(atlas_name(emitter.atlas.as_deref()), emitter.blend_mode, &vertices, &indices);
+        }
+        shape => {
+            let corners: Vec<[f32; 2]> = match shape {
+                ParticleShape::Circle => circle_points(0.0, 0.0, 0.5, 12),
+                _ => vec![[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]],
+            };
+            let fan = triangulate(&corners);
+            let mut vertices = Vec::with_capacity(particles.len() * corners.len());
+            for ((particle, size), color) in particles.iter().zip(&sizes).zip(colors) {
+                let base = vertices.len() as u32;
+                vertices.extend(corners.iter().map(|[x, y]| Vertex {
+                    position: [particle.position[0] + x * size, particle.position[1] + y * size],
+                    color,
+                }));
+                indices.extend(fan.iter().map(|index| base + index));
+            }
+            buffer.set_blend_mode(emitter.blend_mode);
+            buffer.push_shapes(&vertices, &indices);
+        }
+    }
+}
+
+/// Fills `points`, or draws their outline when a thickness is given.
+fn push_shape(buffer: &mut QuadBufferBuilder, points: &[[f32; 2]], outline: Option<f32>, color: &RGBColor) {
+    match outline {
+        Some(thickness) => buffer.push_polygon_outline(points, thickness, color),
+        None => buffer.push_polygon(points, color),
+    }
+}
+
+impl GameObject {
+    /// Pushes the object's light, stopped by `occluders` if it casts shadows.
+    pub(crate) fn render_light(&self, buffer: &mut QuadBufferBuilder, occluders: &[Occluder]) {
+        let Some(light) = self.light.as_ref().filter(|light| light.enabled && light.radius > 0.0) else {
+            return;
+        };
+        let center = light.center([self.pos_x, self.pos_y], self.rotation);
+        let bounds = [center[0] - light.radius, center[1] - light.radius, center[0] + light.radius, center[1] + light.radius];
+        if !buffer.in_view(bounds) {
+            return;
+        }
+        let area = light.lit_area([self.pos_x, self.pos_y], self.rotation, self.id, occluders);
+        let (vertices, indices) = light_triangles(light, center, &area);
+        buffer.push_light(&vertices, &indices);
+    }
+    /// Scales, rotates and moves points relative to the object into world space.
+    fn local_to_world(&self, points: &[[f32; 2]]) -> Vec<[f32; 2]> {
+        let mut points: Vec<[f32; 2]> = points
+            .iter()
+            .map(|[x, y]| [self.pos_x + x * self.scale_x, self.pos_y + y * self.scale_y])
+            .collect();
+        rotate_points(&mut points, self.pos_x, self.pos_y, self.rotation);
+        points
+    }
+    fn push_sprite(&self, sprite: &SpriteData, buffer: &mut QuadBufferBuilder, atlases: &HashMap<String, SpriteAtlas>) {
+        let atlas_name = atlas_name(sprite.atlas.as_deref());
+        let Some(safe_atlas) = atlas_named(atlases, sprite.atlas.as_deref()) else {
+            if !self.missing_graphics_logged.replace(true) {
+                log::warn!("Not drawing sprite {} from atlas {}, which isn't loaded", sprite.sprite_id, atlas_name);
+            }
+            return;
+        };
+        let Some(sprite_data) = safe_atlas.lookup_sprite_data_from_descriptor(&sprite.sprite_id) else {
+            if !self.missing_graphics_logged.replace(true) {
+                log::warn!("Not drawing sprite {}, which isn't in atlas {}", sprite.sprite_id, atlas_name);
+            }
+            return;
+        };
+        let mut quad = safe_atlas.get_sprite_from_atlas(sprite_data,[self.pos_x,self.pos_y],[sprite.width * self.scale_x,sprite.height * self.scale_y],sprite.pivot,sprite.flip_h,sprite.flip_v);
+        if self.rotation != 0.0 {
+            let (sin, cos) = self.rotation.sin_cos();
+            for vertex in &mut quad.0 {
+                let x = vertex.position[0] - self.pos_x;
+                let y = vertex.position[1] - self.pos_y;
+                vertex.position[0] = self.pos_x + x * cos - y * sin;
+                vertex.position[1] = self.pos_y + x * sin + y * cos;
+            }
+        }
+        let offset = buffer.texel_offset([quad.0[0].position[0], quad.0[0].position[1]]);
+        for vertex in &mut quad.0 {
+            vertex.position[0] += offset[0];
+            vertex.position[1] += offset[1];
+        }
+        sprite.color_vertices(&mut quad.0);
+        match &sprite.material {
+            Some(material) => {
+                // Spread the atlas region over 0..1 so effects don't depend on where the
+                // sprite was packed
+                let min = [0, 1].map(|axis| quad.0.iter().map(|vertex| vertex.tex_coords[axis]).fold(f32::MAX, f32::min));
+                let max = [0, 1].map(|axis| quad.0.iter().map(|vertex| vertex.tex_coords[axis]).fold(f32::MIN, f32::max));
+                let vertices = quad.0.map(|vertex| MaterialVertex {
+                    position: vertex.position,
+                    tex_coords: vertex.tex_coords,
+                    // Texture coordinates run top to bottom
+                    uv: [
+                        (vertex.tex_coords[0] - min[0]) / (max[0] - min[0]).max(f32::EPSILON),
+                        (max[1] - vertex.tex_coords[1]) / (max[1] - min[1]).max(f32::EPSILON),
+                    ],
+                    color: vertex.color,
+                });
+                buffer.push_material(material, Some(atlas_name), sprite.blend_mode, &vertices, &quad.1);
+            }
+            None => buffer.push_sprite(atlas_name, sprite.blend_mode, &quad.0, &quad.1),
+        }
+    }
+    /// Draws the chunks of a tilemap the current view can see, each in one batch.
+    fn push_tilemap(&self, tilemap: &TilemapData, buffer: &mut QuadBufferBuilder) {
+        let atlas_name = atlas_name(tilemap.atlas.as_deref());
+        let position = [self.pos_x, self.pos_y];
+        let scale = [self.scale_x, self.scale_y];
+        // Tiles sit on a grid from the corner, so snapping it snaps every tile
+        let offset = buffer.texel_offset(position);
+        let mut color = linear_rgba(&tilemap.tint);
+        color[3] *= tilemap.opacity.clamp(0.0, 1.0);
+        let mut vertices = vec![];
+        for (bounds, chunk_vertices, indices) in tilemap.placed_chunks(position, scale) {
+            if !buffer.in_view(bounds) {
+                continue;
+            }
+            vertices.clear();
+            vertices.extend(chunk_vertices.iter().map(|vertex| {
+                let mut vertex = *vertex;
+                vertex.position[0] = position[0] + vertex.position[0] * scale[0] + offset[0];
+                vertex.position[1] = position[1] + vertex.position[1] * scale[1] + offset[1];
+                vertex.color = color;
+                vertex.flash = 0.0;
+                vertex
+            }));
+            buffer.push_sprites(atlas_name, tilemap.blend_mode, &vertices, indices);
+        }
+    }
+    /// Fills or outlines the shape around the object's position through `points`, rotated
+    /// with the object, with a material. `in.uv` spans the unrotated bounding box.
+    fn push_material_shape(
+        &self,
+        buffer: &mut QuadBufferBuilder,
+        points: &[[f32; 2]],
+        outline: Option<f32>,
+        color: &RGBColor,
+        material: &MaterialInstance,
+        blend_mode: BlendMode,
+    ) {
+        let (mut positions, indices) = match outline {
+            Some(thickness) => polyline(points, thickness, true),
+            None => (points.to_vec(), triangulate(points)),
+        };
+        let min = [0, 1].map(|axis| positions.iter().map(|point| point[axis]).fold(f32::MAX, f32::min));
+        let max = [0, 1].map(|axis| positions.iter().map(|point| point[axis]).fold(f32::MIN, f32::max));
+        let uvs: Vec<[f32; 2]> = positions
+            .iter()
+            .map(|point| [0, 1].map(|axis| (point[axis] - min[axis]) / (max[axis] - min[axis]).max(f32::EPSILON)))
+            .collect();
+        rotate_points(&mut positions, self.pos_x, self.pos_y, self.rotation);
+        let color = linear_rgba(color);
+        let vertices: Vec<MaterialVertex> = positions
+            .iter()
+            .zip(uvs)
+            .map(|(position, uv)| MaterialVertex {
+                position: [position[0], position[1], 0.0],
+                tex_coords: uv,
+                uv,
+                color,
+            })
+            .collect();
+        buffer.push_material(material, None, blend_mode, &vertices, &indices);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::game_object::graphics::{BlendMode, SpriteID};
+use crate::ui::frontend::RGBColor;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::Cell;
+
+/// What each particle is drawn as, centred on its position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParticleShape {
+    /// A sprite from the emitter's atlas
+    Sprite(SpriteID),
+    Circle,
+    Square,
+}
+
+/// One live particle, in world units.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Seconds since it was emitted
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// How far through its life it is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// Emits particles from the object's position into the world, so they stay behind when
+/// it moves. Particles live in a pool of `max_particles` that's allocated once; nothing is
+/// emitted while it's full. Every particle is drawn in a single batch.
+#[derive(Clone)]
+pub struct ParticleEmitterData {
+    /// Particles emitted each second while emitting
+    pub rate: f32,
+    /// Seconds each particle lives, picked between the two
+    pub lifetime: [f32; 2],
+    /// World units per second, picked between the two
+    pub speed: [f32; 2],
+    /// Direction in degrees counter-clockwise from the right, picked between the two and
+    /// turned with the object
+    pub angle: [f32; 2],
+    /// World units per second squared
+    pub gravity: [f32; 2],
+    /// Colours spread evenly over each particle's life, blended between
+    pub colors: Vec<RGBColor>,
+    /// Sizes in world units spread evenly over each particle's life, blended between
+    pub sizes: Vec<f32>,
+    pub shape: ParticleShape,
+    /// Atlas sprite particles come from. `None` uses the default atlas.
+    pub atlas: Option<String>,
+    pub blend_mode: BlendMode,
+    max_particles: usize,
+    particles: Vec<Particle>,
+    emitting: bool,
+    /// Particles owed by the rate that haven't been emitted yet
+    pending: f32,
+    burst: u32,
+    /// Boxed as it's far bigger than the rest of the emitter
+    rng: Box<StdRng>,
+    /// Set once a missing sprite has been reported, so it isn't every frame
+    pub(crate) missing_sprite_logged: Cell<bool>,
+}
+
+impl ParticleEmitterData {
+    /// A white square emitter holding up to `max_particles` at once, emitting 10 a second
+    /// in every direction.
+    pub fn new(max_particles: usize) -> ParticleEmitterData {
+        ParticleEmitterData {
+            rate: 10.0,
+            lifetime: [1.0, 1.0],
+            speed: [1.0, 1.0],
+            angle: [0.0, 360.0],
+            gravity: [0.0, 0.0],
+            colors: vec![RGBColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+                alpha: 255,
+            }],
+            sizes: vec![0.1],
+            shape: ParticleShape::Square,
+            atlas: None,
+            blend_mode: BlendMode::Alpha,
+            max_particles,
+            particles: Vec::with_capacity(max_particles),
+            emitting: true,
+            pending: 0.0,
+            burst: 0,
+            rng: Box::new(StdRng::from_entropy()),
+            missing_sprite_logged: Cell::new(false),
+        }
+    }
+    pub fn rate(mut self, rate: f32) -> ParticleEmitterData {
+        self.rate = rate;
+        self
+    }
+    pub fn lifetime(mut self, min: f32, max: f32) -> ParticleEmitterData {
+        self.lifetime = [min, max];
+        self
+    }
+    pub fn speed(mut self, min: f32, max: f32) -> ParticleEmitterData {
+        self.speed = [min, max];
+        self
+    }
+    pub fn angle(mut self, min: f32, max: f32) -> ParticleEmitterData {
+        self.angle = [min, max];
+        self
+    }
+    pub fn gravity(mut self, x: f32, y: f32) -> ParticleEmitterData {
+        self.gravity = [x, y];
+        self
+    }
+    pub fn colors(mut self, colors: &[RGBColor]) -> ParticleEmitterData {
+        self.colors = colors.to_vec();
+        self
+    }
+    pub fn sizes(mut self, sizes: &[f32]) -> ParticleEmitterData {
+        self.sizes = sizes.to_vec();
+        self
+    }
+    pub fn sprite(mut self, sprite_id: &str) -> ParticleEmitterData {
+        self.shape = ParticleShape::Sprite(sprite_id.to_string());
+        self.missing_sprite_logged.set(false);
+        self
+    }
+    pub fn circle(mut self) -> ParticleEmitterData {
+        self.shape = ParticleShape::Circle;
+        self
+    }
+    pub fn atlas(mut self, atlas: &str) -> ParticleEmitterData {
+        self.atlas = Some(atlas.to_string());
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> ParticleEmitterData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    /// Starts stopped when `false`, e.g. for emitters that only burst.
+    pub fn emitting(mut self, emitting: bool) -> ParticleEmitterData {
+        self.emitting = emitting;
+        self
+    }
+    /// Emits `count` particles on the first frame, e.g. for an explosion.
+    pub fn initial_burst(mut self, count: u32) -> ParticleEmitterData {
+        self.burst += count;
+        self
+    }
+    /// Makes emission repeatable, e.g. for tests and replays.
+    pub fn seed(mut self, seed: u64) -> ParticleEmitterData {
+        self.rng = Box::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Starts emitting at `rate`. Particles already emitted carry on either way.
+    pub fn start(&mut self) {
+        self.emitting = true;
+    }
+    pub fn stop(&mut self) {
+        self.emitting = false;
+        self.pending = 0.0;
+    }
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+    /// Emits `count` particles at once on the next frame, emitting or not.
+    pub fn burst(&mut self, count: u32) {
+        self.burst += count;
+    }
+    /// Removes every live particle.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    /// Ages and moves the live particles by `delta` seconds, then emits new ones from
+    /// `origin` for an object turned by `rotation` radians. The engine calls this every
+    /// frame.
+    pub fn update(&mut self, delta: f32, origin: [f32; 2], rotation: f32) {
+        let mut index = 0;
+        while index < self.particles.len() {
+            let particle = &mut self.particles[index];
+            particle.age += delta;
+            if particle.age >= particle.lifetime {
+                // Order doesn't matter, and this keeps the pool packed without moving the rest
+                self.particles.swap_remove(index);
+                continue;
+            }
+            particle.velocity[0] += self.gravity[0] * delta;
+            particle.velocity[1] += self.gravity[1] * delta;
+            particle.position[0] += particle.velocity[0] * delta;
+            particle.position[1] += particle.velocity[1] * delta;
+            index += 1;
+        }
+
+        if self.emitting {
+            self.pending += self.rate.max(0.0) * delta;
+        }
+        let count = self.pending.floor() as u32 + self.burst;
+        self.pending = self.pending.fract();
+        self.burst = 0;
+        for _ in 0..count {
+            if self.particles.len() >= self.max_particles {
+                break;
+            }
+            let angle = self.pick(self.angle).to_radians() + rotation;
+            let speed = self.pick(self.speed);
+            let lifetime = self.pick(self.lifetime).max(f32::EPSILON);
+            self.particles.push(Particle {
+                position: origin,
+                velocity: [angle.cos() * speed, angle.sin() * speed],
+                age: 0.0,
+                lifetime,
+            });
+        }
+    }
+    /// Colour of a particle `progress` of the way through its life.
+    pub fn color_at(&self, progress: f32) -> RGBColor {
+        let Some((from, to, amount)) = keyframes(&self.colors, progress) else {
+            return RGBColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+                alpha: 255,
+            };
+        };
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+        RGBColor {
+            red: channel(from.red, to.red),
+            green: channel(from.green, to.green),
+            blue: channel(from.blue, to.blue),
+            alpha: channel(from.alpha, to.alpha),
+        }
+    }
+    /// Size of a particle `progress` of the way through its life.
+    pub fn size_at(&self, progress: f32) -> f32 {
+        match keyframes(&self.sizes, progress) {
+            Some((from, to, amount)) => from + (to - from) * amount,
+            None => 0.0,
+        }
+    }
+
+    fn pick(&mut self, range: [f32; 2]) -> f32 {
+        let (min, max) = (range[0].min(range[1]), range[0].max(range[1]));
+        match max > min {
+            true => self.rng.gen_range(min..max),
+            false => min,
+        }
+    }
+}
+
+/// The two keys either side of `progress` through evenly spaced keys, and how far it is
+/// from the first to the second.
+fn keyframes<T>(keys: &[T], progress: f32) -> Option<(&T, &T, f32)> {
+    let last = keys.len().checked_sub(1)?;
+    let position = progress.clamp(0.0, 1.0) * last as f32;
+    let index = (position.floor() as usize).min(last);
+    let next = (index + 1).min(last);
+    Some((&keys[index], &keys[next], position - index as f32))
+}
@@ -0,0 +1,378 @@
+use crate::game_object::graphics::{BlendMode, SpriteID};
+use crate::physics::screen_units_to_physics_units;
+use crate::renderer::atlas::SpriteAtlas;
+use crate::renderer::sprite::SpriteVertex;
+use crate::ui::frontend::RGBColor;
+use hashbrown::{HashMap, HashSet};
+use log::warn;
+use rapier2d::geometry::{Collider, ColliderBuilder, ColliderHandle};
+use rapier2d::prelude::{vector, ActiveHooks};
+use std::sync::Arc;
+
+/// How a tile collides. Neighbouring tiles that collide the same way share a collider.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TileCollision {
+    Solid,
+    /// Only stops things landing on it from above
+    OneWay,
+    /// Reports overlaps without pushing anything
+    Sensor,
+}
+
+/// One cell of a tilemap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    pub sprite_id: SpriteID,
+    pub flip_h: bool,
+    pub flip_v: bool,
+}
+
+impl Tile {
+    pub fn new(sprite_id: &str) -> Tile {
+        Tile {
+            sprite_id: sprite_id.to_string(),
+            flip_h: false,
+            flip_v: false,
+        }
+    }
+    pub fn flip_h(mut self) -> Tile {
+        self.flip_h = true;
+        self
+    }
+    pub fn flip_v(mut self) -> Tile {
+        self.flip_v = true;
+        self
+    }
+}
+
+/// A block of tiles covered by one collider, in tiles from the top-left of the map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileRect {
+    pub collision: TileCollision,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Geometry of a square of tiles, relative to the map's top-left corner.
+#[derive(Clone, Default)]
+pub(crate) struct TileChunk {
+    vertices: Vec<SpriteVertex>,
+    indices: Vec<u32>,
+    dirty: bool,
+}
+
+/// A grid of atlas tiles drawn in chunks, each chunk a single batch that's only rebuilt
+/// when one of its tiles changes. The object's position is the map's top-left corner and
+/// row 0 is the top row. Tilemaps are scaled with their object but not rotated.
+#[derive(Clone)]
+pub struct TilemapData {
+    /// Atlas the tiles come from. `None` uses the default atlas.
+    pub atlas: Option<String>,
+    /// Size of a tile in world units
+    pub tile_width: f32,
+    pub tile_height: f32,
+    pub tint: RGBColor,
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+    /// Hidden maps still collide, e.g. for collision drawn in a level editor
+    pub visible: bool,
+    width: u32,
+    height: u32,
+    tiles: Vec<Option<Tile>>,
+    /// How tiles collide by sprite, set before the map is added to a scene
+    collision: HashMap<SpriteID, TileCollision>,
+    /// Tiles along each side of a chunk
+    chunk_size: u32,
+    chunks: Vec<TileChunk>,
+    /// Atlas the chunks were built from, so they're rebuilt when it's replaced
+    chunk_atlas: Option<u64>,
+    /// Sprites in that atlas, so scripts can't place tiles it doesn't have
+    pub(crate) atlas_sprites: Option<Arc<HashSet<SpriteID>>>,
+    pub(crate) colliders: Vec<ColliderHandle>,
+    pub(crate) colliders_dirty: bool,
+}
+
+impl TilemapData {
+    /// An empty map `width` by `height` tiles.
+    pub fn new(width: u32, height: u32, tile_width: f32, tile_height: f32) -> TilemapData {
+        TilemapData {
+            atlas: None,
+            tile_width,
+            tile_height,
+            tint: RGBColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+                alpha: 255,
+            },
+            opacity: 1.0,
+            blend_mode: BlendMode::Alpha,
+            visible: true,
+            width,
+            height,
+            tiles: vec![None; (width * height) as usize],
+            collision: HashMap::new(),
+            chunk_size: 16,
+            chunks: vec![],
+            chunk_atlas: None,
+            atlas_sprites: None,
+            colliders: vec![],
+            colliders_dirty: true,
+        }
+    }
+    pub fn atlas(mut self, atlas: &str) -> TilemapData {
+        self.atlas = Some(atlas.to_string());
+        self
+    }
+    /// Makes every tile showing `sprite_id` collide.
+    pub fn collision(mut self, sprite_id: &str, collision: TileCollision) -> TilemapData {
+        self.collision.insert(sprite_id.to_string(), collision);
+        self
+    }
+    pub fn chunk_size(mut self, chunk_size: u32) -> TilemapData {
+        self.chunk_size = chunk_size.max(1);
+        self.chunks.clear();
+        self
+    }
+    pub fn tile(mut self, x: u32, y: u32, tile: Tile) -> TilemapData {
+        self.set_tile(x, y, tile);
+        self
+    }
+    /// Fills the map row by row from the top, `None` leaving a gap.
+    pub fn tiles(mut self, tiles: &[Option<&str>]) -> TilemapData {
+        for (index, sprite_id) in tiles.iter().enumerate().take(self.tiles.len()) {
+            let (x, y) = (index as u32 % self.width, index as u32 / self.width);
+            match sprite_id {
+                Some(sprite_id) => self.set_tile(x, y, Tile::new(sprite_id)),
+                None => self.clear_tile(x, y),
+            }
+        }
+        self
+    }
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> TilemapData {
+        self.blend_mode = blend_mode;
+        self
+    }
+    pub fn visible(mut self, visible: bool) -> TilemapData {
+        self.visible = visible;
+        self
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn get_tile(&self, x: u32, y: u32) -> Option<&Tile> {
+        self.index(x, y).and_then(|index| self.tiles[index].as_ref())
+    }
+    /// Changes a tile, updating its chunk on the next frame and its colliders on the next
+    /// frame if how it collides changed. Tiles outside the map are ignored.
+    pub fn set_tile(&mut self, x: u32, y: u32, tile: Tile) {
+        self.replace_tile(x, y, Some(tile));
+    }
+    pub fn clear_tile(&mut self, x: u32, y: u32) {
+        self.replace_tile(x, y, None);
+    }
+    pub fn collision_at(&self, x: u32, y: u32) -> Option<TileCollision> {
+        self.get_tile(x, y)
+            .and_then(|tile| self.collision.get(&tile.sprite_id))
+            .copied()
+    }
+    /// Tile under a position relative to the object, if it's on the map.
+    pub fn tile_at(&self, local: [f32; 2]) -> Option<(u32, u32)> {
+        let x = (local[0] / self.tile_width).floor();
+        let y = (-local[1] / self.tile_height).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+    /// Centre of a tile relative to the object.
+    pub fn tile_center(&self, x: u32, y: u32) -> [f32; 2] {
+        [
+            (x as f32 + 0.5) * self.tile_width,
+            -(y as f32 + 0.5) * self.tile_height,
+        ]
+    }
+    /// The blocks colliders are made from. Solid and sensor tiles are merged into rows and
+    /// then rows of the same span are stacked. One-way tiles are only merged along rows,
+    /// so each platform keeps its own top surface.
+    pub fn collision_rects(&self) -> Vec<TileRect> {
+        let mut rects: Vec<TileRect> = vec![];
+        // Rects reaching down to the row above
+        let mut open: Vec<usize> = vec![];
+        for y in 0..self.height {
+            let mut next_open = vec![];
+            let mut x = 0;
+            while x < self.width {
+                let Some(collision) = self.collision_at(x, y) else {
+                    x += 1;
+                    continue;
+                };
+                let start = x;
+                while x < self.width && self.collision_at(x, y) == Some(collision) {
+                    x += 1;
+                }
+                let above = open.iter().copied().find(|&index| {
+                    let rect = &rects[index];
+                    rect.collision == collision && rect.x == start && rect.width == x - start
+                });
+                match above {
+                    Some(index) if collision != TileCollision::OneWay => {
+                        rects[index].height += 1;
+                        next_open.push(index);
+                    }
+                    _ => {
+                        rects.push(TileRect {
+                            collision,
+                            x: start,
+                            y,
+                            width: x - start,
+                            height: 1,
+                        });
+                        next_open.push(rects.len() - 1);
+                    }
+                }
+            }
+            open = next_open;
+        }
+        rects
+    }
+    /// Rapier colliders for `collision_rects`, for a map at `position` scaled by `scale`.
+    pub(crate) fn to_rapier(&self, position: [f32; 2], scale: [f32; 2], id: u128) -> Vec<Collider> {
+        self.collision_rects()
+            .iter()
+            .map(|rect| {
+                let width = rect.width as f32 * self.tile_width * scale[0].abs();
+                let height = rect.height as f32 * self.tile_height * scale[1].abs();
+                let centre = [
+                    position[0] + (rect.x as f32 * self.tile_width) * scale[0] + width * 0.5 * scale[0].signum(),
+                    position[1] - (rect.y as f32 * self.tile_height) * scale[1] - height * 0.5 * scale[1].signum(),
+                ];
+                let builder = ColliderBuilder::cuboid(
+                    screen_units_to_physics_units(width) / 2.0,
+                    screen_units_to_physics_units(height) / 2.0,
+                )
+                .translation(vector![
+                    screen_units_to_physics_units(centre[0]),
+                    screen_units_to_physics_units(centre[1])
+                ])
+                .user_data(id);
+                match rect.collision {
+                    TileCollision::Solid => builder.build(),
+                    TileCollision::OneWay => builder.active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS).build(),
+                    TileCollision::Sensor => builder.sensor(true).build(),
+                }
+            })
+            .collect()
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+    fn chunks_across(&self) -> u32 {
+        self.width.div_ceil(self.chunk_size)
+    }
+    fn replace_tile(&mut self, x: u32, y: u32, tile: Option<Tile>) {
+        let Some(index) = self.index(x, y) else {
+            return;
+        };
+        let collision_before = self.collision_at(x, y);
+        self.tiles[index] = tile;
+        if self.collision_at(x, y) != collision_before {
+            self.colliders_dirty = true;
+        }
+        let chunk = (y / self.chunk_size * self.chunks_across() + x / self.chunk_size) as usize;
+        if let Some(chunk) = self.chunks.get_mut(chunk) {
+            chunk.dirty = true;
+        }
+    }
+    /// Rebuilds the chunks whose tiles changed since they were last drawn.
+    pub(crate) fn update_chunks(&mut self, atlas: &SpriteAtlas) {
+        let count = (self.chunks_across() * self.height.div_ceil(self.chunk_size)) as usize;
+        if self.chunks.len() != count || self.chunk_atlas != Some(atlas.id) {
+            self.chunks = vec![
+                TileChunk {
+                    dirty: true,
+                    ..TileChunk::default()
+                };
+                count
+            ];
+            self.chunk_atlas = Some(atlas.id);
+            self.atlas_sprites = Some(Arc::new(atlas.sprite_ids().cloned().collect()));
+        }
+        for index in 0..count {
+            if self.chunks[index].dirty {
+                self.chunks[index] = self.build_chunk(index, atlas);
+            }
+        }
+    }
+    fn build_chunk(&self, index: usize, atlas: &SpriteAtlas) -> TileChunk {
+        let mut chunk = TileChunk::default();
+        let [min_x, min_y, max_x, max_y] = self.chunk_tiles(index);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let Some(tile) = self.get_tile(x, y) else {
+                    continue;
+                };
+                let Some(sprite) = atlas.sprite(&tile.sprite_id) else {
+                    warn!("Skipping tile {} at {}, {}, which isn't in the atlas", tile.sprite_id, x, y);
+                    continue;
+                };
+                let (vertices, indices) = atlas.get_sprite_from_atlas(
+                    sprite,
+                    [x as f32 * self.tile_width, -((y + 1) as f32) * self.tile_height],
+                    [self.tile_width, self.tile_height],
+                    Some([0.0, 0.0]),
+                    tile.flip_h,
+                    tile.flip_v,
+                );
+                let base = chunk.vertices.len() as u32;
+                chunk.vertices.extend_from_slice(&vertices);
+                chunk.indices.extend(indices.iter().map(|index| base + index));
+            }
+        }
+        chunk
+    }
+    /// First and one past the last tile of a chunk along each axis.
+    fn chunk_tiles(&self, index: usize) -> [u32; 4] {
+        let across = self.chunks_across();
+        let (chunk_x, chunk_y) = (index as u32 % across, index as u32 / across);
+        [
+            chunk_x * self.chunk_size,
+            chunk_y * self.chunk_size,
+            ((chunk_x + 1) * self.chunk_size).min(self.width),
+            ((chunk_y + 1) * self.chunk_size).min(self.height),
+        ]
+    }
+    /// Chunk geometry moved to `position` and scaled, with the world bounds it covers as
+    /// min x, min y, max x, max y, skipping empty chunks.
+    pub(crate) fn placed_chunks(
+        &self,
+        position: [f32; 2],
+        scale: [f32; 2],
+    ) -> impl Iterator<Item = ([f32; 4], &[SpriteVertex], &[u32])> + '_ {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| !chunk.indices.is_empty())
+            .map(move |(index, chunk)| {
+                let [min_x, min_y, max_x, max_y] = self.chunk_tiles(index);
+                let corners = [
+                    [min_x as f32 * self.tile_width, -(min_y as f32) * self.tile_height],
+                    [max_x as f32 * self.tile_width, -(max_y as f32) * self.tile_height],
+                ]
+                .map(|[x, y]| [position[0] + x * scale[0], position[1] + y * scale[1]]);
+                let bounds = [
+                    corners[0][0].min(corners[1][0]),
+                    corners[0][1].min(corners[1][1]),
+                    corners[0][0].max(corners[1][0]),
+                    corners[0][1].max(corners[1][1]),
+                ];
+                (bounds, chunk.vertices.as_slice(), chunk.indices.as_slice())
+            })
+    }
+}
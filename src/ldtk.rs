@@ -0,0 +1,597 @@
+use crate::game_object::graphics::{GraphicsType, SpriteData};
+use crate::game_object::tilemap::{Tile, TileCollision, TilemapData};
+use crate::game_object::GameObjectBuilder;
+use crate::physics::screen_units_to_physics_units;
+use crate::renderer::atlas::SpriteAtlas;
+use crate::scene::Scene;
+use crate::tiled::{array, boolean, number, string, unsigned};
+use hashbrown::HashMap;
+use log::warn;
+use rapier2d::prelude::vector;
+use rhai::Dynamic;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FLIPPED_HORIZONTALLY: u64 = 1;
+const FLIPPED_VERTICALLY: u64 = 2;
+
+/// A tileset from an LDtk project, cut from a single image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkTileset {
+    pub uid: i64,
+    /// Also the name of the atlas made from it
+    pub identifier: String,
+    /// Image the tiles are cut from. LDtk's embedded icons aren't supported.
+    pub image: Option<String>,
+    /// Size of a tile in pixels
+    pub grid_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Pixels around the grid and between tiles
+    pub padding: u32,
+    pub spacing: u32,
+    /// Enum values tagged on each tile, by tile id
+    pub tags: HashMap<u32, Vec<String>>,
+}
+
+impl LdtkTileset {
+    /// Atlas holding every tile, named by its id.
+    pub fn atlas(&self) -> Option<SpriteAtlas> {
+        let image = self.image.as_ref()?;
+        Some(SpriteAtlas::from_grid(
+            image,
+            self.grid_size as u64,
+            self.grid_size as u64,
+            self.columns as u64,
+            (self.columns * self.rows) as u64,
+            self.padding as u64,
+            self.spacing as u64,
+        ))
+    }
+    /// Id of the tile covering exactly the pixel rect x, y, width, height, if it's a
+    /// single tile.
+    pub fn tile_id(&self, rect: [u32; 4]) -> Option<u32> {
+        let [x, y, width, height] = rect;
+        let step = self.grid_size + self.spacing;
+        let (x, y) = (x.checked_sub(self.padding)?, y.checked_sub(self.padding)?);
+        if width != self.grid_size || height != self.grid_size || x % step != 0 || y % step != 0 {
+            return None;
+        }
+        Some(y / step * self.columns + x / step)
+    }
+}
+
+/// A tile placed in a tile, auto or IntGrid layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkTile {
+    /// Top-left corner in pixels from the layer's top-left
+    pub position: [f32; 2],
+    pub id: u32,
+    pub flip_h: bool,
+    pub flip_v: bool,
+}
+
+/// An entity instance, in LDtk's pixels with y pointing down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkEntity {
+    pub identifier: String,
+    pub iid: String,
+    /// Where the pivot sits, from the layer's top-left
+    pub position: [f32; 2],
+    /// 0 to 1 across and down the entity
+    pub pivot: [f32; 2],
+    pub width: f32,
+    pub height: f32,
+    pub tags: Vec<String>,
+    /// Tileset uid and the pixel rect x, y, width, height of the tile it's drawn with
+    pub tile: Option<(i64, [u32; 4])>,
+    /// Field values as LDtk saves them, by field name
+    pub fields: HashMap<String, Value>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LdtkLayerType {
+    IntGrid,
+    Entities,
+    Tiles,
+    AutoLayer,
+}
+
+/// A layer instance of a level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkLayer {
+    pub identifier: String,
+    pub layer_type: LdtkLayerType,
+    /// In cells
+    pub width: u32,
+    pub height: u32,
+    /// Size of a cell in pixels
+    pub grid_size: u32,
+    /// Offset in pixels from the level's top-left
+    pub offset: [f32; 2],
+    pub opacity: f32,
+    pub visible: bool,
+    /// Uid of the tileset its tiles come from
+    pub tileset: Option<i64>,
+    /// IntGrid values row by row from the top-left, 0 for an empty cell
+    pub int_grid: Vec<i64>,
+    /// Identifiers of the layer's IntGrid values
+    pub int_grid_names: HashMap<i64, String>,
+    /// Tiles in drawing order, including those placed by auto-layer rules
+    pub tiles: Vec<LdtkTile>,
+    pub entities: Vec<LdtkEntity>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkLevel {
+    /// Also the name of the scene made from it
+    pub identifier: String,
+    pub iid: String,
+    /// Top-left corner in the LDtk world, in pixels
+    pub world_position: [f32; 2],
+    /// In pixels
+    pub width: u32,
+    pub height: u32,
+    pub fields: HashMap<String, Value>,
+    /// Layers in drawing order, back to front. LDtk lists them front to back.
+    pub layers: Vec<LdtkLayer>,
+}
+
+/// A project made in the LDtk editor. Levels can be saved in the project or in their own
+/// files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdtkProject {
+    pub tilesets: Vec<LdtkTileset>,
+    pub levels: Vec<LdtkLevel>,
+    /// Folder the project was loaded from, for scripts named by relative paths
+    dir: Option<PathBuf>,
+}
+
+/// Builds the object spawned for an entity.
+pub type LdtkPrefab = fn(&LdtkEntity) -> GameObjectBuilder;
+
+/// Places levels in the world and says what to spawn for their entities.
+pub struct LdtkImport {
+    /// LDtk pixels in a world unit
+    pub pixels_per_unit: f32,
+    /// World position of each level's top-left corner
+    pub origin: [f32; 2],
+    prefabs: HashMap<String, LdtkPrefab>,
+    /// By name with case, spaces, dashes and underscores ignored
+    collisions: HashMap<String, TileCollision>,
+}
+
+impl LdtkImport {
+    /// IntGrid values and tile enum tags named "Solid", "OneWay" or "Sensor" collide that
+    /// way, whatever their case or separators.
+    pub fn new(pixels_per_unit: f32) -> LdtkImport {
+        LdtkImport {
+            pixels_per_unit,
+            origin: [0.0, 0.0],
+            prefabs: HashMap::new(),
+            collisions: HashMap::new(),
+        }
+        .collision("solid", TileCollision::Solid)
+        .collision("one_way", TileCollision::OneWay)
+        .collision("sensor", TileCollision::Sensor)
+    }
+    pub fn origin(mut self, x: f32, y: f32) -> LdtkImport {
+        self.origin = [x, y];
+        self
+    }
+    /// Builds entities named `identifier` with `prefab`. The level still places them and
+    /// passes in their fields.
+    pub fn prefab(mut self, identifier: &str, prefab: LdtkPrefab) -> LdtkImport {
+        self.prefabs.insert(identifier.to_string(), prefab);
+        self
+    }
+    /// Makes IntGrid values and tile enum tags called `name` collide.
+    pub fn collision(mut self, name: &str, collision: TileCollision) -> LdtkImport {
+        self.collisions.insert(normalise(name), collision);
+        self
+    }
+    fn collision_for(&self, name: &str) -> Option<TileCollision> {
+        self.collisions.get(&normalise(name)).copied()
+    }
+    fn to_world(&self, pixels: [f32; 2]) -> [f32; 2] {
+        [
+            self.origin[0] + pixels[0] / self.pixels_per_unit,
+            self.origin[1] - pixels[1] / self.pixels_per_unit,
+        ]
+    }
+}
+
+/// Why an LDtk project couldn't be loaded.
+#[derive(Debug)]
+pub enum LdtkError {
+    /// A project or level file couldn't be read
+    Io(PathBuf, std::io::Error),
+    /// A project or level isn't valid JSON
+    Parse(String),
+    /// A level is saved separately from a project parsed from a string
+    ExternalLevel(String),
+}
+
+impl fmt::Display for LdtkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LdtkError::Io(path, e) => write!(f, "Unable to read {}: {}", path.display(), e),
+            LdtkError::Parse(message) => write!(f, "Invalid LDtk file: {}", message),
+            LdtkError::ExternalLevel(level) => {
+                write!(f, "Level {} is saved separately, load the project with LdtkProject::load", level)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LdtkError {}
+
+impl LdtkProject {
+    /// Loads a `.ldtk` project along with any levels saved in separate `.ldtkl` files.
+    pub fn load(path: &str) -> Result<LdtkProject, LdtkError> {
+        let contents = fs::read_to_string(path).map_err(|e| LdtkError::Io(PathBuf::from(path), e))?;
+        let dir = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+        let value: Value = serde_json::from_str(&contents).map_err(|e| LdtkError::Parse(format!("{}: {}", path, e)))?;
+        LdtkProject::from_value(&value, Some(dir))
+    }
+    /// Parses a project. Its levels must be saved in it.
+    pub fn from_ldtk(contents: &str) -> Result<LdtkProject, LdtkError> {
+        let value: Value = serde_json::from_str(contents).map_err(|e| LdtkError::Parse(e.to_string()))?;
+        LdtkProject::from_value(&value, None)
+    }
+
+    /// An atlas for each tileset with an image, named after the tileset.
+    pub fn atlases(&self) -> Vec<(String, SpriteAtlas)> {
+        self.tilesets
+            .iter()
+            .filter_map(|tileset| Some((tileset.identifier.clone(), tileset.atlas()?)))
+            .collect()
+    }
+    pub fn tileset(&self, uid: i64) -> Option<&LdtkTileset> {
+        self.tilesets.iter().find(|tileset| tileset.uid == uid)
+    }
+    pub fn level(&self, identifier: &str) -> Option<&LdtkLevel> {
+        self.levels.iter().find(|level| level.identifier == identifier)
+    }
+
+    /// Objects for every visible layer of a level, back to front. Tiles become tilemaps,
+    /// more than one where tiles are stacked, and IntGrid values that collide become a
+    /// hidden tilemap of colliders. Entities are built by their prefab, or get a sprite if
+    /// they're drawn with a tile. A "script" field adds a behaviour, and every entity's
+    /// fields and `iid` are in its scripts' `properties`.
+    pub fn game_objects(&self, level: &LdtkLevel, import: &LdtkImport) -> Vec<GameObjectBuilder> {
+        let mut builders = vec![];
+        for (z_index, layer) in level.layers.iter().enumerate().filter(|(_, layer)| layer.visible) {
+            let position = import.to_world(layer.offset);
+            let mut tilemaps = self.tilemaps(layer, import);
+            tilemaps.extend(int_grid_colliders(layer, import));
+            for tilemap in tilemaps {
+                let mut builder = GameObjectBuilder::new()
+                    .graphics(GraphicsType::Tilemap(tilemap))
+                    .z_index(z_index as i32);
+                [builder.pos_x, builder.pos_y] = position;
+                builders.push(builder);
+            }
+            for entity in &layer.entities {
+                builders.push(self.entity(entity, layer.offset, import).z_index(z_index as i32));
+            }
+        }
+        builders
+    }
+    /// Registers a level's objects with `scene`, returning their ids. The tilesets' atlases
+    /// need adding to the engine too, e.g. with `Engine::add_ldtk_project`.
+    pub fn spawn(&self, level: &LdtkLevel, scene: &mut Scene, import: &LdtkImport) -> Vec<u128> {
+        self.game_objects(level, import)
+            .into_iter()
+            .map(|builder| scene.register_game_object(builder).id())
+            .collect()
+    }
+
+    fn tilemaps(&self, layer: &LdtkLayer, import: &LdtkImport) -> Vec<TilemapData> {
+        let Some(tileset) = layer.tileset.and_then(|uid| self.tileset(uid)) else {
+            return vec![];
+        };
+        let tile_size = layer.grid_size as f32 / import.pixels_per_unit;
+        let new_tilemap = || {
+            let mut tilemap = TilemapData::new(layer.width, layer.height, tile_size, tile_size).atlas(&tileset.identifier);
+            tilemap.opacity = layer.opacity;
+            for (id, tags) in &tileset.tags {
+                if let Some(collision) = tags.iter().find_map(|tag| import.collision_for(tag)) {
+                    tilemap = tilemap.collision(&id.to_string(), collision);
+                }
+            }
+            tilemap
+        };
+        let mut tilemaps: Vec<TilemapData> = vec![];
+        for tile in &layer.tiles {
+            let [x, y] = tile.position.map(|pixels| (pixels / layer.grid_size as f32).floor().max(0.0) as u32);
+            // Auto-layer rules can stack tiles in a cell, so later ones go on maps above
+            let index = match tilemaps.iter().position(|tilemap| tilemap.get_tile(x, y).is_none()) {
+                Some(index) => index,
+                None => {
+                    tilemaps.push(new_tilemap());
+                    tilemaps.len() - 1
+                }
+            };
+            let mut sprite = Tile::new(&tile.id.to_string());
+            sprite.flip_h = tile.flip_h;
+            sprite.flip_v = tile.flip_v;
+            tilemaps[index].set_tile(x, y, sprite);
+        }
+        tilemaps
+    }
+
+    fn entity(&self, entity: &LdtkEntity, offset: [f32; 2], import: &LdtkImport) -> GameObjectBuilder {
+        let size = [entity.width, entity.height].map(|size| size / import.pixels_per_unit);
+        // Objects sit on their centre rather than the entity's pivot
+        let position = import.to_world([
+            offset[0] + entity.position[0] + (0.5 - entity.pivot[0]) * entity.width,
+            offset[1] + entity.position[1] + (0.5 - entity.pivot[1]) * entity.height,
+        ]);
+
+        let mut builder = match import.prefabs.get(&entity.identifier) {
+            Some(prefab) => prefab(entity),
+            None => {
+                let mut builder = GameObjectBuilder::new();
+                let tile = entity
+                    .tile
+                    .and_then(|(uid, rect)| Some((self.tileset(uid)?, rect)));
+                if let Some((tileset, rect)) = tile {
+                    match tileset.tile_id(rect) {
+                        Some(id) => {
+                            let sprite = SpriteData::new(&id.to_string(), size[0], size[1])
+                                .atlas(&tileset.identifier)
+                                .pivot(0.5, 0.5);
+                            builder = builder.graphics(GraphicsType::Sprite(sprite));
+                        }
+                        None => warn!("Entity {} is drawn with more than one tile and won't be drawn", entity.iid),
+                    }
+                }
+                if let Some(script) = entity.fields.get("script").and_then(Value::as_str) {
+                    let path = match &self.dir {
+                        Some(dir) => dir.join(script).to_string_lossy().into_owned(),
+                        None => script.to_string(),
+                    };
+                    builder = builder.behaviour(&path);
+                }
+                builder
+            }
+        };
+        [builder.pos_x, builder.pos_y] = position;
+        if let Some(rigid_body) = &mut builder.rigid_body {
+            rigid_body.set_translation(
+                vector![
+                    screen_units_to_physics_units(position[0]),
+                    screen_units_to_physics_units(position[1])
+                ],
+                false,
+            );
+        }
+        for (name, value) in &entity.fields {
+            builder = builder.property(name, to_dynamic(value));
+        }
+        builder.property("iid", Dynamic::from(entity.iid.clone()))
+    }
+
+    fn from_value(value: &Value, dir: Option<PathBuf>) -> Result<LdtkProject, LdtkError> {
+        let defs = value.get("defs").unwrap_or(&Value::Null);
+        let tilesets = array(defs, "tilesets")
+            .iter()
+            .map(|tileset| parse_tileset(tileset, dir.as_deref()))
+            .collect();
+        // Projects with several worlds keep their levels in each world
+        let levels = array(value, "levels")
+            .iter()
+            .chain(array(value, "worlds").iter().flat_map(|world| array(world, "levels")))
+            .map(|level| parse_level(level, defs, dir.as_deref()))
+            .collect::<Result<_, _>>()?;
+        Ok(LdtkProject { tilesets, levels, dir })
+    }
+}
+
+/// A hidden tilemap of the cells whose IntGrid value collides, named after the value.
+fn int_grid_colliders(layer: &LdtkLayer, import: &LdtkImport) -> Option<TilemapData> {
+    let tile_size = layer.grid_size as f32 / import.pixels_per_unit;
+    let mut tilemap = TilemapData::new(layer.width, layer.height, tile_size, tile_size).visible(false);
+    let mut collides = false;
+    for (index, &value) in layer.int_grid.iter().enumerate().filter(|(_, &value)| value != 0) {
+        let name = layer.int_grid_names.get(&value).cloned().unwrap_or_else(|| value.to_string());
+        let Some(collision) = import.collision_for(&name) else {
+            continue;
+        };
+        tilemap = tilemap.collision(&name, collision);
+        let (x, y) = (index as u32 % layer.width, index as u32 / layer.width);
+        tilemap.set_tile(x, y, Tile::new(&name));
+        collides = true;
+    }
+    collides.then_some(tilemap)
+}
+
+fn normalise(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Field values for scripts. Points, entity references and tiles become maps.
+fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(value) => Dynamic::from(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => Dynamic::from(int),
+            None => Dynamic::from(number.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(value) => Dynamic::from(value.clone()),
+        Value::Array(values) => Dynamic::from_array(values.iter().map(to_dynamic).collect()),
+        Value::Object(values) => Dynamic::from_map(
+            values
+                .iter()
+                .map(|(key, value)| (key.as_str().into(), to_dynamic(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn parse_tileset(tileset: &Value, dir: Option<&Path>) -> LdtkTileset {
+    let identifier = string(tileset, "identifier").unwrap_or_default().to_string();
+    let image = string(tileset, "relPath").map(|image| match dir {
+        Some(dir) => dir.join(image).to_string_lossy().into_owned(),
+        None => image.to_string(),
+    });
+    if image.is_none() {
+        warn!("Tileset {} has no image and won't be drawn", identifier);
+    }
+    let mut tags: HashMap<u32, Vec<String>> = HashMap::new();
+    for tag in array(tileset, "enumTags") {
+        let Some(name) = string(tag, "enumValueId") else {
+            continue;
+        };
+        for id in array(tag, "tileIds").iter().filter_map(Value::as_u64) {
+            tags.entry(id as u32).or_default().push(name.to_string());
+        }
+    }
+    LdtkTileset {
+        uid: number(tileset, "uid").unwrap_or(0.0) as i64,
+        identifier,
+        image,
+        grid_size: unsigned(tileset, "tileGridSize"),
+        columns: unsigned(tileset, "__cWid"),
+        rows: unsigned(tileset, "__cHei"),
+        padding: unsigned(tileset, "padding"),
+        spacing: unsigned(tileset, "spacing"),
+        tags,
+    }
+}
+
+fn parse_level(level: &Value, defs: &Value, dir: Option<&Path>) -> Result<LdtkLevel, LdtkError> {
+    let identifier = string(level, "identifier").unwrap_or_default().to_string();
+    // Levels saved separately have no layers in the project
+    let external = match (level.get("layerInstances"), string(level, "externalRelPath")) {
+        (Some(Value::Array(_)), _) => None,
+        (_, Some(path)) => {
+            let Some(dir) = dir else {
+                return Err(LdtkError::ExternalLevel(identifier));
+            };
+            let path = dir.join(path);
+            let contents = fs::read_to_string(&path).map_err(|e| LdtkError::Io(path.clone(), e))?;
+            let level = serde_json::from_str::<Value>(&contents)
+                .map_err(|e| LdtkError::Parse(format!("{}: {}", path.display(), e)))?;
+            Some(level)
+        }
+        _ => None,
+    };
+    let level = external.as_ref().unwrap_or(level);
+    let mut layers: Vec<LdtkLayer> = array(level, "layerInstances")
+        .iter()
+        .filter_map(|layer| parse_layer(layer, defs))
+        .collect();
+    layers.reverse();
+    Ok(LdtkLevel {
+        identifier,
+        iid: string(level, "iid").unwrap_or_default().to_string(),
+        world_position: [
+            number(level, "worldX").unwrap_or(0.0) as f32,
+            number(level, "worldY").unwrap_or(0.0) as f32,
+        ],
+        width: unsigned(level, "pxWid"),
+        height: unsigned(level, "pxHei"),
+        fields: parse_fields(level),
+        layers,
+    })
+}
+
+fn parse_layer(layer: &Value, defs: &Value) -> Option<LdtkLayer> {
+    let identifier = string(layer, "__identifier").unwrap_or_default().to_string();
+    let layer_type = match string(layer, "__type") {
+        Some("IntGrid") => LdtkLayerType::IntGrid,
+        Some("Entities") => LdtkLayerType::Entities,
+        Some("Tiles") => LdtkLayerType::Tiles,
+        Some("AutoLayer") => LdtkLayerType::AutoLayer,
+        other => {
+            warn!("Skipping LDtk layer {} of type {:?}", identifier, other);
+            return None;
+        }
+    };
+    let definition = array(defs, "layers")
+        .iter()
+        .find(|definition| number(definition, "uid") == number(layer, "layerDefUid"));
+    let int_grid_names = definition
+        .map(|definition| array(definition, "intGridValues"))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|value| Some((number(value, "value")? as i64, string(value, "identifier")?.to_string())))
+        .collect();
+    let tiles = array(layer, "gridTiles")
+        .iter()
+        .chain(array(layer, "autoLayerTiles"))
+        .map(|tile| {
+            let position = tile.get("px").and_then(Value::as_array).map_or(&[][..], Vec::as_slice);
+            let flips = tile.get("f").and_then(Value::as_u64).unwrap_or(0);
+            LdtkTile {
+                position: [0, 1].map(|axis| position.get(axis).and_then(Value::as_f64).unwrap_or(0.0) as f32),
+                id: unsigned(tile, "t"),
+                flip_h: flips & FLIPPED_HORIZONTALLY != 0,
+                flip_v: flips & FLIPPED_VERTICALLY != 0,
+            }
+        })
+        .collect();
+    Some(LdtkLayer {
+        identifier,
+        layer_type,
+        width: unsigned(layer, "__cWid"),
+        height: unsigned(layer, "__cHei"),
+        grid_size: unsigned(layer, "__gridSize"),
+        offset: [
+            number(layer, "__pxTotalOffsetX").unwrap_or(0.0) as f32,
+            number(layer, "__pxTotalOffsetY").unwrap_or(0.0) as f32,
+        ],
+        opacity: number(layer, "__opacity").unwrap_or(1.0) as f32,
+        visible: boolean(layer, "visible").unwrap_or(true),
+        tileset: number(layer, "__tilesetDefUid").map(|uid| uid as i64),
+        int_grid: array(layer, "intGridCsv").iter().map(|value| value.as_i64().unwrap_or(0)).collect(),
+        int_grid_names,
+        tiles,
+        entities: array(layer, "entityInstances").iter().map(parse_entity).collect(),
+    })
+}
+
+fn parse_entity(entity: &Value) -> LdtkEntity {
+    let pair = |key: &str, default: f64| -> [f32; 2] {
+        let values = entity.get(key).and_then(Value::as_array).map_or(&[][..], Vec::as_slice);
+        [0, 1].map(|axis| values.get(axis).and_then(Value::as_f64).unwrap_or(default) as f32)
+    };
+    let tile = entity.get("__tile").filter(|tile| !tile.is_null()).map(|tile| {
+        (
+            number(tile, "tilesetUid").unwrap_or(0.0) as i64,
+            [unsigned(tile, "x"), unsigned(tile, "y"), unsigned(tile, "w"), unsigned(tile, "h")],
+        )
+    });
+    LdtkEntity {
+        identifier: string(entity, "__identifier").unwrap_or_default().to_string(),
+        iid: string(entity, "iid").unwrap_or_default().to_string(),
+        position: pair("px", 0.0),
+        pivot: pair("__pivot", 0.0),
+        width: number(entity, "width").unwrap_or(0.0) as f32,
+        height: number(entity, "height").unwrap_or(0.0) as f32,
+        tags: array(entity, "__tags")
+            .iter()
+            .filter_map(|tag| Some(tag.as_str()?.to_string()))
+            .collect(),
+        tile,
+        fields: parse_fields(entity),
+    }
+}
+
+fn parse_fields(value: &Value) -> HashMap<String, Value> {
+    array(value, "fieldInstances")
+        .iter()
+        .filter_map(|field| {
+            let name = string(field, "__identifier")?.to_string();
+            Some((name, field.get("__value").cloned().unwrap_or(Value::Null)))
+        })
+        .collect()
+}
@@ -1,11 +1,25 @@
 pub mod audio;
+pub mod camera;
 mod events;
 pub mod game_object;
+pub mod ldtk;
+pub mod light;
 pub mod physics;
 mod renderer;
 pub mod scene;
+pub mod tiled;
+pub mod timers;
+pub mod tween;
 pub mod ui;
 
+pub use crate::renderer::atlas::{AtlasPackOptions, SpriteAtlas, DEFAULT_ATLAS};
+pub use crate::renderer::buffer::RenderStats;
+pub use crate::renderer::shapes::triangulate;
+pub use crate::renderer::sprite::SpriteVertex;
+pub use crate::renderer::material::{Material, MaterialInstance, MAX_MATERIAL_PARAMS};
+pub use crate::renderer::post::{PostEffect, PostPass, MAX_POST_PARAMS};
+pub use crate::renderer::target::RenderTarget;
+
 use crate::renderer::buffer::QuadBufferBuilder;
 use hashbrown::{HashMap, HashSet};
 use instant::Instant;
@@ -22,6 +36,7 @@ use ui::frontend::RGBColor;
 
 use crate::events::EngineEvent;
 use crate::game_object::behaviours::EngineView;
+use crate::game_object::create_rhai_engine;
 use crate::renderer::Render;
 use rapier2d::prelude::{
     vector, BroadPhase, CCDSolver, ColliderHandle, ImpulseJointSet, IntegrationParameters,
@@ -34,15 +49,22 @@ use winit::event::{
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
 
+use crate::camera::{Camera2D, DesignResolution, PixelPerfect, UiMode, VIEW_HEIGHT};
+use crate::ldtk::{LdtkImport, LdtkProject};
 use crate::scene::Scene;
+use crate::tiled::TiledMap;
+use crate::timers::{Clock, Timer};
+use crate::tween::Tween;
+use crate::game_object::animation::AnimationClip;
 
 use rhai::{Engine as RhaiEngine};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
-use crate::renderer::atlas::{get_file_as_byte_vector, SpriteAtlas};
-use crate::renderer::sprite::{SpriteVertex};
+use crate::game_object::graphics::{Graphics, RenderLayer};
+use crate::physics::OneWayPlatforms;
 
+#[derive(Clone)]
 pub struct MouseData {
     is_middle_pressed: bool,
     is_left_pressed: bool,
@@ -64,12 +86,25 @@ pub struct Engine {
     query_pipeline: QueryPipeline,
     physics_pipeline: PhysicsPipeline,
     config: EngineConfig,
-    audio_manager: AudioManager,
+    /// `None` without an audio device, e.g. when rendering headless
+    audio_manager: Option<AudioManager>,
     renderer: Option<Render>,
     last_delta: Duration,
     last_frame_end: Instant,
-    sprite_atlas: Option<SpriteAtlas>,
-    engine: RhaiEngine
+    sprite_atlases: HashMap<String, SpriteAtlas>,
+    engine: RhaiEngine,
+    clock: Clock,
+    /// Seconds of scaled game time so far, which stops while paused
+    game_time: f32,
+    timers: Vec<Timer>,
+    tweens: Vec<Tween>,
+    hidden_layers: HashSet<RenderLayer>,
+    /// Drawn in this order, before the window
+    render_targets: Vec<RenderTarget>,
+    materials: HashMap<String, Material>,
+    design_resolution: Option<DesignResolution>,
+    pixel_perfect: Option<PixelPerfect>,
+    capture_path: Option<String>,
 }
 
 pub struct EngineConfig {
@@ -79,14 +114,15 @@ pub struct EngineConfig {
 
 impl Engine {
     pub fn new(window_width: i32, window_height: i32, config: EngineConfig) -> Self {
-        let (event_tx, event_rx) = kanal::bounded(60); //TODO: Set to frame rate
+        let (event_tx, event_rx) = kanal::unbounded();
 
         let query_pipeline = QueryPipeline::new();
 
         let mut physics_pipeline = PhysicsPipeline::new();
 
-        let mut audio_manager =
-            AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()).unwrap();
+        let audio_manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
+            .map_err(|e| warn!("Running without sound: {}", e))
+            .ok();
         Engine {
             scenes: HashMap::new(),
             event_tx,
@@ -110,14 +146,231 @@ impl Engine {
             },
             last_delta: Duration::from_millis(0),
             last_frame_end: Instant::now(),
-            sprite_atlas: None,
-            engine: RhaiEngine::new(),
+            sprite_atlases: HashMap::new(),
+            engine: create_rhai_engine(),
+            clock: Clock::default(),
+            game_time: 0.0,
+            timers: vec![],
+            tweens: vec![],
+            hidden_layers: HashSet::new(),
+            render_targets: vec![],
+            materials: HashMap::new(),
+            design_resolution: None,
+            pixel_perfect: None,
+            capture_path: None,
         }
     }
 
+    /// Loads the default atlas, used by sprites that don't name one.
     pub fn load_sprite_atlas(&mut self,descriptor_filename: &str,atlas_filename: &str) {
+        self.load_named_sprite_atlas(DEFAULT_ATLAS, descriptor_filename, atlas_filename);
+    }
+
+    pub fn load_named_sprite_atlas(&mut self, name: &str, descriptor_filename: &str, atlas_filename: &str) {
         let atlas = SpriteAtlas::new(atlas_filename,descriptor_filename);
-        self.sprite_atlas = Some(atlas)
+        self.add_sprite_atlas(name, atlas);
+    }
+
+    /// Loads a single image that sprites can draw with `SpriteData::texture(name, ..)`.
+    pub fn load_texture(&mut self, name: &str, texture_filename: &str) {
+        let atlas = SpriteAtlas::from_texture_file(name, texture_filename);
+        self.add_sprite_atlas(name, atlas);
+    }
+
+    /// Uses an atlas built in code, e.g. with `SpriteAtlas::pack_from_dir`. Replaces any
+    /// atlas already loaded under `name`.
+    pub fn add_sprite_atlas(&mut self, name: &str, atlas: SpriteAtlas) {
+        self.sprite_atlases.insert(name.to_string(), atlas);
+    }
+
+    /// Adds an atlas for each tileset of a Tiled map, named after the tileset.
+    pub fn add_tiled_tilesets(&mut self, map: &TiledMap) {
+        for (name, atlas) in map.atlases() {
+            self.add_sprite_atlas(&name, atlas);
+        }
+    }
+
+    /// Adds an atlas for each tileset of an LDtk project, and a scene for each of its
+    /// levels named after the level.
+    pub fn add_ldtk_project(&mut self, project: &LdtkProject, import: &LdtkImport) {
+        for (name, atlas) in project.atlases() {
+            self.add_sprite_atlas(&name, atlas);
+        }
+        for level in &project.levels {
+            let scene = self.register_scene(level.identifier.clone());
+            project.spawn(level, scene, import);
+        }
+    }
+
+    /// Uses an atlas built in code as the default atlas.
+    pub fn set_sprite_atlas(&mut self, atlas: SpriteAtlas) {
+        self.add_sprite_atlas(DEFAULT_ATLAS, atlas);
+    }
+
+    /// Draw calls, geometry and buffer sizes of the last frame. All zero before the window opens.
+    pub fn render_stats(&self) -> RenderStats {
+        self.renderer.as_ref().map(|renderer| renderer.stats()).unwrap_or_default()
+    }
+    /// Size of the window in pixels, or the size it was created with before it opens.
+    pub fn window_size(&self) -> [f32; 2] {
+        match &self.renderer {
+            Some(renderer) => [renderer.width(), renderer.height()],
+            None => [self.window_width as f32, self.window_height as f32],
+        }
+    }
+    /// Adds an offscreen texture for cameras with `Camera2D::with_target` and the target's
+    /// own UI to draw into. Sprites show it like a texture loaded under the target's name.
+    /// Replaces any render target or atlas with the same name.
+    pub fn add_render_target(&mut self, target: RenderTarget) {
+        let atlas = SpriteAtlas::render_target(&target.name, target.width, target.height);
+        self.add_sprite_atlas(&target.name, atlas);
+        match self.render_targets.iter_mut().find(|existing| existing.name == target.name) {
+            Some(existing) => *existing = target,
+            None => self.render_targets.push(target),
+        }
+    }
+    pub fn remove_render_target(&mut self, name: &str) {
+        self.render_targets.retain(|target| target.name != name);
+        self.unload_sprite_atlas(name);
+    }
+    pub fn render_target(&self, name: &str) -> Option<&RenderTarget> {
+        self.render_targets.iter().find(|target| target.name == name)
+    }
+    /// Size in pixels of what `camera` draws into, the window or its render target.
+    fn camera_surface_size(&self, camera: &Camera2D) -> [f32; 2] {
+        match &camera.target {
+            Some(name) => self.render_target(name).map_or([0.0, 0.0], |target| target.size()),
+            None => self.window_surface_size(),
+        }
+    }
+    /// How the window's cameras are scaled to the window. Pixel-perfect mode takes over from
+    /// the design resolution.
+    fn window_design(&self) -> Option<DesignResolution> {
+        match self.pixel_perfect {
+            Some(pixel) => Some(pixel.design()),
+            None => self.design_resolution,
+        }
+    }
+    /// Pixels the window's cameras lay their viewports out in.
+    fn window_surface_size(&self) -> [f32; 2] {
+        let window = self.window_size();
+        self.window_design().map_or(window, |design| design.visible(window))
+    }
+    /// Lays the world and the UI out for `design_resolution` and scales them to the window.
+    /// `None` goes back to laying them out in window pixels.
+    pub fn set_design_resolution(&mut self, design_resolution: Option<DesignResolution>) {
+        self.design_resolution = design_resolution;
+    }
+    pub fn design_resolution(&self) -> Option<DesignResolution> {
+        self.design_resolution
+    }
+    /// Draws the window's cameras at a low virtual resolution scaled up by whole pixels,
+    /// for pixel art. `None` goes back to drawing at the window's resolution.
+    pub fn set_pixel_perfect(&mut self, pixel_perfect: Option<PixelPerfect>) {
+        self.pixel_perfect = pixel_perfect;
+    }
+    pub fn pixel_perfect(&self) -> Option<PixelPerfect> {
+        self.pixel_perfect
+    }
+    /// Main camera of the active scene.
+    pub fn camera(&self) -> Option<&Camera2D> {
+        self.camera_at(0)
+    }
+    pub fn camera_mut(&mut self) -> Option<&mut Camera2D> {
+        self.camera_at_mut(0)
+    }
+    pub fn camera_at(&self, index: usize) -> Option<&Camera2D> {
+        self.active_scene.as_ref().and_then(|scene| scene.camera_at(index))
+    }
+    pub fn camera_at_mut(&mut self, index: usize) -> Option<&mut Camera2D> {
+        self.active_scene.as_mut().and_then(|scene| scene.camera_at_mut(index))
+    }
+    /// Adds a camera to the active scene and returns its index, or `None` without a scene.
+    pub fn add_camera(&mut self, camera: Camera2D) -> Option<usize> {
+        self.active_scene.as_mut().map(|scene| scene.add_camera(camera))
+    }
+    /// Converts a window position in pixels to a world position through the camera whose
+    /// viewport it's in, picking the last added camera where viewports overlap. Cameras
+    /// drawing into render targets are skipped.
+    pub fn screen_to_world(&self, screen: [f32; 2]) -> [f32; 2] {
+        let Some(scene) = &self.active_scene else {
+            return screen;
+        };
+        let screen = self
+            .window_design()
+            .map_or(screen, |design| design.window_to_design(screen, self.window_size()));
+        let window = self.window_surface_size();
+        scene
+            .cameras
+            .iter()
+            .rev()
+            .find(|camera| camera.target.is_none() && camera.viewport.contains(screen, window))
+            .or(scene.cameras.first())
+            .map_or(screen, |camera| camera.screen_to_world(screen, window))
+    }
+    /// Converts a world position to a window position in pixels using the active scene's main camera.
+    pub fn world_to_screen(&self, world: [f32; 2]) -> [f32; 2] {
+        let Some(camera) = self.camera() else {
+            return world;
+        };
+        let screen = camera.world_to_screen(world, self.window_surface_size());
+        self.window_design()
+            .map_or(screen, |design| design.design_to_window(screen, self.window_size()))
+    }
+    /// The mouse cursor in world coordinates.
+    pub fn mouse_world_position(&self) -> [f32; 2] {
+        let mouse = self.mouse_data.mouse_position;
+        self.screen_to_world([mouse.x as f32, mouse.y as f32])
+    }
+    /// Makes a material available to graphics as `MaterialInstance::new(name)`. Replaces any
+    /// material with the same name.
+    pub fn add_material(&mut self, material: Material) {
+        self.materials.insert(material.name.clone(), material);
+    }
+    /// Changing a material's defaults affects every graphic using it that doesn't set its
+    /// own values.
+    pub fn material_mut(&mut self, name: &str) -> Option<&mut Material> {
+        self.materials.get_mut(name)
+    }
+    /// Post-processing pass of the active scene, e.g. to change its parameters.
+    pub fn post_pass_mut(&mut self, name: &str) -> Option<&mut PostPass> {
+        self.active_scene.as_mut().and_then(|scene| scene.post_pass_mut(name))
+    }
+    /// Hidden layers are skipped when drawing, but their objects still run.
+    pub fn set_layer_visible(&mut self, layer: RenderLayer, visible: bool) {
+        if visible {
+            self.hidden_layers.remove(&layer);
+        } else {
+            self.hidden_layers.insert(layer);
+        }
+    }
+    pub fn is_layer_visible(&self, layer: RenderLayer) -> bool {
+        !self.hidden_layers.contains(&layer)
+    }
+    /// Ids of the active scene's objects in the order they're drawn, back to front.
+    pub fn draw_order(&self) -> Vec<u128> {
+        let Some(scene) = &self.active_scene else {
+            return vec![];
+        };
+        scene
+            .draw_order(&self.hidden_layers)
+            .into_iter()
+            .map(|index| scene.game_objects[index].id())
+            .collect()
+    }
+    /// Unloads an atlas or texture. Its GPU texture is freed on the next frame.
+    pub fn unload_sprite_atlas(&mut self, name: &str) {
+        self.sprite_atlases.remove(name);
+    }
+
+    /// Animation clip from the loaded atlases, e.g. an Aseprite frame tag. The default
+    /// atlas is searched first.
+    pub fn atlas_animation(&self, name: &str) -> Option<AnimationClip> {
+        self.sprite_atlases
+            .get(DEFAULT_ATLAS)
+            .and_then(|atlas| atlas.animation(name))
+            .or_else(|| self.sprite_atlases.values().find_map(|atlas| atlas.animation(name)))
+            .cloned()
     }
 
     pub fn set_current_scene(&mut self, new_scene: String) {
@@ -138,6 +391,10 @@ impl Engine {
             }
         }
 
+        // Timers and tweens belong to objects in the old scene
+        self.timers.clear();
+        self.tweens.clear();
+
         let new_scene = self.scenes.get(&new_scene).unwrap();
 
         self.active_scene = Some(new_scene.clone());
@@ -155,6 +412,7 @@ impl Engine {
                 &mut active_scene.collider_set,
                 &mut self.last_delta,
                 &mut self.collision_locks,
+                &mut self.engine,
             )
         }
     }
@@ -265,30 +523,8 @@ impl Engine {
                     }
                 }
 
-                let active_scene_unwrapped = self.active_scene.as_mut().unwrap();
-
                 while accumulator >= dt_physics {
-                    self.physics_pipeline.step(
-                        &vector![0.0, self.config.gravity],
-                        &active_scene_unwrapped.integration_params,
-                        &mut active_scene_unwrapped.island_manager,
-                        &mut active_scene_unwrapped.broad_phase,
-                        &mut active_scene_unwrapped.narrow_phase_collision,
-                        &mut active_scene_unwrapped.rigid_body_set,
-                        &mut active_scene_unwrapped.collider_set,
-                        &mut active_scene_unwrapped.impulse_joint_set,
-                        &mut active_scene_unwrapped.multibody_joint_set,
-                        &mut active_scene_unwrapped.ccd_solver,
-                        None,
-                        &(),
-                        &(),
-                    );
-
-                    self.query_pipeline.update(
-                        &active_scene_unwrapped.rigid_body_set,
-                        &active_scene_unwrapped.collider_set,
-                    );
-
+                    self.step_physics();
                     accumulator -= dt_physics;
                 }
 
@@ -305,16 +541,77 @@ impl Engine {
             _ => *control_flow = ControlFlow::Poll,
         });
     }
+    fn step_physics(&mut self) {
+        let active_scene_unwrapped = self.active_scene.as_mut().unwrap();
+
+        self.physics_pipeline.step(
+            &vector![0.0, self.config.gravity],
+            &active_scene_unwrapped.integration_params,
+            &mut active_scene_unwrapped.island_manager,
+            &mut active_scene_unwrapped.broad_phase,
+            &mut active_scene_unwrapped.narrow_phase_collision,
+            &mut active_scene_unwrapped.rigid_body_set,
+            &mut active_scene_unwrapped.collider_set,
+            &mut active_scene_unwrapped.impulse_joint_set,
+            &mut active_scene_unwrapped.multibody_joint_set,
+            &mut active_scene_unwrapped.ccd_solver,
+            None,
+            &OneWayPlatforms,
+            &(),
+        );
+
+        self.query_pipeline.update(
+            &active_scene_unwrapped.rigid_body_set,
+            &active_scene_unwrapped.collider_set,
+        );
+    }
+    /// Saves the next frame drawn to a PNG at `path`, e.g. for golden-image tests.
+    pub fn capture_frame(&mut self, path: &str) {
+        self.capture_path = Some(path.to_string());
+    }
+    /// Renders into an offscreen frame of `width` by `height` pixels instead of opening a
+    /// window, preferring a software adapter. False when there is no adapter at all.
+    pub fn init_headless(&mut self, width: u32, height: u32) -> bool {
+        self.renderer = pollster::block_on(renderer::Render::new_headless(width, height));
+        self.renderer.is_some()
+    }
+    /// Runs one physics step and one frame of the active scene, `delta` after the last one.
+    /// Nothing is drawn before the window opens or `init_headless`, so this also runs the
+    /// game without a GPU.
+    pub fn step(&mut self, delta: Duration) {
+        self.last_delta = delta;
+        self.step_physics();
+        self.draw();
+    }
+    /// Steps the game like `step` and returns what was drawn. Needs `init_headless` first.
+    pub fn render_headless_frame(&mut self, delta: Duration) -> image::RgbaImage {
+        self.renderer.as_mut().unwrap().capture_next_frame = true;
+        self.step(delta);
+        self.renderer.as_mut().unwrap().captured.take().unwrap()
+    }
     fn draw(&mut self) {
         let active_scene = self.active_scene.as_mut();
         let mut buffer = QuadBufferBuilder::new();
 
-        // Sprites
-        let mut sprite_verticies: Vec<SpriteVertex> = Vec::new();
-        let mut sprite_indicies: Vec<u16> = Vec::new();
-        //
-
         if active_scene.is_some() {
+            self.game_time += self.clock.scaled_delta(&self.last_delta);
+            self.update_timers();
+            self.update_tweens();
+
+            let view_bounds: Vec<[f32; 4]> = self
+                .active_scene
+                .as_ref()
+                .unwrap()
+                .cameras
+                .iter()
+                .map(|camera| {
+                    let bounds = camera.view_bounds(camera.aspect(self.camera_surface_size(camera)));
+                    // Cameras move after this, so leave some slack
+                    let margin = [bounds[2] - bounds[0], bounds[3] - bounds[1]].map(|size| size * 0.25);
+                    [bounds[0] - margin[0], bounds[1] - margin[1], bounds[2] + margin[0], bounds[3] + margin[1]]
+                })
+                .collect();
+
             {
                 let active_scene = self.active_scene.as_mut().unwrap();
 
@@ -323,26 +620,65 @@ impl Engine {
                         &mut active_scene.rigid_body_set,
                         &mut active_scene.narrow_phase_collision,
                         &mut self.event_tx,
-                        &mut buffer,
                         &mut self.keys_pressed,
                         &mut self.key_locks,
                         &mut self.query_pipeline,
                         &mut active_scene.collider_set,
                         &mut self.last_delta,
                         &mut self.collision_locks,
-                        &mut sprite_verticies,
-                        &mut sprite_indicies,
-                        &self.sprite_atlas,
-                        &mut self.engine
+                        &mut self.engine,
+                        &self.clock,
                     );
                 }
+                active_scene.update_tilemap_colliders();
+                buffer.set_ambient_light(active_scene.ambient_light.as_ref());
+                let occluders = match active_scene.ambient_light {
+                    Some(_) => light::occluders(&active_scene.collider_set),
+                    None => vec![],
+                };
+
+                let order = active_scene.draw_order(&self.hidden_layers);
+                // Each camera draws the scene again into its own viewport
+                for (index, camera) in active_scene.cameras.iter().enumerate() {
+                    buffer.set_view(index);
+                    buffer.set_view_bounds(Some(view_bounds[index]));
+                    let texel = match camera.target {
+                        Some(_) => None,
+                        None => self.pixel_perfect.map(|pixel| pixel.texel(camera.zoom)),
+                    };
+                    buffer.set_texel(texel);
+                    for &index in &order {
+                        let object = &mut active_scene.game_objects[index];
+                        if camera.sees(object.layer) {
+                            object.render(&mut buffer, &self.sprite_atlases);
+                        }
+                    }
+                    // Only the window is lit
+                    if active_scene.ambient_light.is_some() && camera.target.is_none() {
+                        for &index in &order {
+                            let object = &active_scene.game_objects[index];
+                            if camera.sees(object.layer) {
+                                object.render_light(&mut buffer, &occluders);
+                            }
+                        }
+                    }
+                }
             }
 
             self.handle_events();
 
+            self.update_cameras();
+
             let active_scene = self.active_scene.as_mut().unwrap();
 
-            self.renderer.as_mut().unwrap().render_buffer(
+            let Some(renderer) = self.renderer.as_mut() else {
+                return;
+            };
+            renderer.capture_next_frame |= self.capture_path.is_some();
+            renderer.design_resolution = self.design_resolution;
+            renderer.pixel_perfect = self.pixel_perfect;
+            renderer.game_time = self.game_time;
+            renderer.render_buffer(
                 buffer,
                 &active_scene.ui_ast,
                 &active_scene.data_map,
@@ -360,10 +696,53 @@ impl Engine {
                 },
                 &self.mouse_data,
                 &self.config.clear_color,
-                sprite_verticies,
-                sprite_indicies,
-                &self.sprite_atlas
+                &self.sprite_atlases,
+                &active_scene.cameras,
+                active_scene.ui_mode,
+                &self.render_targets,
+                &active_scene.post_passes,
+                &self.materials,
             );
+
+            if let Some(path) = self.capture_path.take() {
+                match &self.renderer.as_ref().unwrap().captured {
+                    Some(image) => image.save(&path).unwrap(),
+                    None => log::warn!("Frame wasn't captured to {}", path),
+                }
+            }
+        }
+    }
+    fn update_cameras(&mut self) {
+        let delta = self.clock.scaled_delta(&self.last_delta);
+        let sizes: Vec<[f32; 2]> = self
+            .active_scene
+            .as_ref()
+            .unwrap()
+            .cameras
+            .iter()
+            .map(|camera| self.camera_surface_size(camera))
+            .collect();
+        let window = self.window_size();
+        let view_height = match (self.pixel_perfect, self.design_resolution) {
+            (Some(pixel), _) => pixel.view_height(),
+            (None, Some(design)) => design.view_height(window),
+            (None, None) => VIEW_HEIGHT,
+        };
+        let scene = self.active_scene.as_mut().unwrap();
+        for (camera, size) in scene.cameras.iter_mut().zip(sizes) {
+            (camera.view_height, camera.pixels_per_unit) = match camera.target {
+                Some(_) => (VIEW_HEIGHT, None),
+                None => (view_height, self.pixel_perfect.map(|pixel| pixel.pixels_per_unit)),
+            };
+            let target = camera.follow.and_then(|follow| {
+                scene
+                    .game_objects
+                    .iter()
+                    .find(|object| object.id == follow.target)
+                    .map(|object| [object.pos_x, object.pos_y])
+            });
+            let aspect = camera.aspect(size);
+            camera.update(delta, target, aspect);
         }
     }
     pub fn register_scene(&mut self, scene_name: String) -> &mut Scene {
@@ -392,6 +771,10 @@ impl Engine {
                 function_map: HashMap::new(),
                 data_map: HashMap::new(),
                 current_game_object_id: 0,
+                cameras: vec![Camera2D::default()],
+                ui_mode: UiMode::Global,
+                post_passes: vec![],
+                ambient_light: None,
             },
         );
         self.scenes.get_mut(&scene_name).unwrap()
@@ -0,0 +1,247 @@
+use crate::physics::physics_units_to_pixels;
+use crate::ui::frontend::RGBColor;
+use rapier2d::geometry::{ColliderSet, TypedShape};
+use rapier2d::math::Point;
+use std::f32::consts::TAU;
+
+/// Sides of the circle a light's reach is drawn as
+const LIGHT_SIDES: usize = 32;
+/// Sides of the polygon a ball collider's shadow is cast from
+const BALL_SIDES: usize = 16;
+/// How far either side of an occluder's corner the extra rays go, in radians, so light
+/// carries on past it
+const CORNER_OFFSET: f32 = 1e-4;
+/// Lowest falloff a light is drawn with, since the shader's `pow(0, 0)` at the edge of a
+/// light with no falloff is undefined
+pub(crate) const MIN_FALLOFF: f32 = 0.01;
+
+/// Narrows a light to a wedge.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LightCone {
+    /// Degrees counter-clockwise from the right, turned with the object
+    pub direction: f32,
+    /// Width of the wedge in degrees
+    pub angle: f32,
+}
+
+/// Light given off by a game object while its scene has an ambient light. Everything in
+/// the window's cameras is multiplied by the ambient colour plus the lights reaching it,
+/// so lights only show in scenes darker than white.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Light2D {
+    pub color: RGBColor,
+    /// Multiplies the colour, above 1 to light things past it
+    pub intensity: f32,
+    /// How far the light reaches in world units
+    pub radius: f32,
+    /// How quickly the light fades towards its radius, 1 fading evenly. Drawn as at least
+    /// 0.01.
+    pub falloff: f32,
+    /// From the object's position in world units, turned with the object
+    pub offset: [f32; 2],
+    /// `None` lights every direction
+    pub cone: Option<LightCone>,
+    /// Whether colliders other than the object's own block the light
+    pub shadows: bool,
+    pub enabled: bool,
+}
+
+impl Light2D {
+    /// Light in every direction.
+    pub fn point(radius: f32, color: RGBColor) -> Light2D {
+        Light2D {
+            color,
+            intensity: 1.0,
+            radius,
+            falloff: 1.0,
+            offset: [0.0, 0.0],
+            cone: None,
+            shadows: false,
+            enabled: true,
+        }
+    }
+    /// Light in a wedge `angle` degrees wide, facing `direction` degrees counter-clockwise
+    /// from the right.
+    pub fn spot(radius: f32, color: RGBColor, direction: f32, angle: f32) -> Light2D {
+        Light2D {
+            cone: Some(LightCone { direction, angle }),
+            ..Light2D::point(radius, color)
+        }
+    }
+    pub fn intensity(mut self, intensity: f32) -> Light2D {
+        self.intensity = intensity;
+        self
+    }
+    pub fn falloff(mut self, falloff: f32) -> Light2D {
+        self.falloff = falloff.max(MIN_FALLOFF);
+        self
+    }
+    pub fn offset(mut self, x: f32, y: f32) -> Light2D {
+        self.offset = [x, y];
+        self
+    }
+    /// Stops the light at colliders, casting hard shadows behind them. Sensors let light
+    /// through.
+    pub fn cast_shadows(mut self) -> Light2D {
+        self.shadows = true;
+        self
+    }
+
+    /// Where the light is for an object at `position` turned by `rotation` radians.
+    pub fn center(&self, position: [f32; 2], rotation: f32) -> [f32; 2] {
+        let (sin, cos) = rotation.sin_cos();
+        let [x, y] = self.offset;
+        [position[0] + x * cos - y * sin, position[1] + x * sin + y * cos]
+    }
+
+    /// Outline of the area the light reaches, counter-clockwise, for an object `owner` at
+    /// `position` turned by `rotation` radians. Cones start at the light's centre.
+    pub fn lit_area(&self, position: [f32; 2], rotation: f32, owner: u128, occluders: &[Occluder]) -> Vec<[f32; 2]> {
+        let center = self.center(position, rotation);
+        let cone = self.cone.map(|cone| {
+            let start = cone.direction.to_radians() + rotation - cone.angle.to_radians() * 0.5;
+            [start, start + cone.angle.to_radians().clamp(0.0, TAU)]
+        });
+        let segments: Vec<[[f32; 2]; 2]> = match self.shadows {
+            true => occluders
+                .iter()
+                .filter(|occluder| occluder.owner != owner)
+                .map(|occluder| occluder.segment)
+                .collect(),
+            false => vec![],
+        };
+        let mut area = visibility_polygon(center, self.radius, &segments, cone);
+        if cone.is_some() {
+            area.insert(0, center);
+        }
+        area
+    }
+}
+
+/// An edge of a collider that blocks light, in world units.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Occluder {
+    /// Game object the collider belongs to
+    pub owner: u128,
+    pub segment: [[f32; 2]; 2],
+}
+
+/// Edges of every collider that isn't a sensor, where the colliders are now.
+pub fn occluders(colliders: &ColliderSet) -> Vec<Occluder> {
+    let mut occluders = vec![];
+    for (_, collider) in colliders.iter() {
+        if collider.is_sensor() {
+            continue;
+        }
+        let points: Vec<Point<f32>> = match collider.shape().as_typed_shape() {
+            TypedShape::Cuboid(cuboid) => {
+                let [x, y] = [cuboid.half_extents.x, cuboid.half_extents.y];
+                vec![Point::new(-x, -y), Point::new(x, -y), Point::new(x, y), Point::new(-x, y)]
+            }
+            TypedShape::Ball(ball) => (0..BALL_SIDES)
+                .map(|side| {
+                    let angle = side as f32 / BALL_SIDES as f32 * TAU;
+                    Point::new(angle.cos() * ball.radius, angle.sin() * ball.radius)
+                })
+                .collect(),
+            TypedShape::ConvexPolygon(polygon) => polygon.points().to_vec(),
+            TypedShape::Triangle(triangle) => vec![triangle.a, triangle.b, triangle.c],
+            TypedShape::Segment(segment) => vec![segment.a, segment.b],
+            TypedShape::Polyline(polyline) => {
+                let position = collider.position();
+                occluders.extend(polyline.segments().map(|segment| Occluder {
+                    owner: collider.user_data,
+                    segment: [segment.a, segment.b].map(|point| world_point(position * point)),
+                }));
+                continue;
+            }
+            _ => continue,
+        };
+        let position = collider.position();
+        let points: Vec<[f32; 2]> = points.iter().map(|point| world_point(position * point)).collect();
+        // A segment is its own outline, so it's not closed
+        let edges = match points.len() {
+            2 => 1,
+            count => count,
+        };
+        occluders.extend((0..edges).map(|index| Occluder {
+            owner: collider.user_data,
+            segment: [points[index], points[(index + 1) % points.len()]],
+        }));
+    }
+    occluders
+}
+
+fn world_point(point: Point<f32>) -> [f32; 2] {
+    [physics_units_to_pixels(point.x), physics_units_to_pixels(point.y)]
+}
+
+/// Outline, counter-clockwise, of what can be seen from `origin` out to `radius` past the
+/// `segments` in the way. `cone` limits it to the angles, in radians, between the two.
+pub fn visibility_polygon(
+    origin: [f32; 2],
+    radius: f32,
+    segments: &[[[f32; 2]; 2]],
+    cone: Option<[f32; 2]>,
+) -> Vec<[f32; 2]> {
+    let near = |&[from, to]: &[[f32; 2]; 2]| distance_to_segment(origin, from, to) < radius;
+    let segments: Vec<[[f32; 2]; 2]> = segments.iter().copied().filter(near).collect();
+
+    let (start, span) = match cone {
+        Some([start, end]) => (start, end - start),
+        None => (0.0, TAU),
+    };
+    // Rays round the circle so unblocked light is round, and either side of each corner
+    let mut angles: Vec<f32> = (0..=LIGHT_SIDES).map(|side| side as f32 / LIGHT_SIDES as f32 * span).collect();
+    for corner in segments.iter().flatten() {
+        let angle = (corner[1] - origin[1]).atan2(corner[0] - origin[0]);
+        for angle in [angle - CORNER_OFFSET, angle, angle + CORNER_OFFSET] {
+            let angle = (angle - start).rem_euclid(TAU);
+            if angle <= span {
+                angles.push(angle);
+            }
+        }
+    }
+    angles.sort_by(|a, b| a.total_cmp(b));
+    angles.dedup();
+    if cone.is_none() {
+        // The last ray is the first one again
+        angles.pop();
+    }
+
+    angles
+        .into_iter()
+        .map(|angle| {
+            let direction = [(start + angle).cos(), (start + angle).sin()];
+            let distance = segments
+                .iter()
+                .filter_map(|&[from, to]| ray_hit(origin, direction, from, to))
+                .fold(radius, f32::min);
+            [origin[0] + direction[0] * distance, origin[1] + direction[1] * distance]
+        })
+        .collect()
+}
+
+/// How far along a ray of unit `direction` it hits the segment, if it does.
+fn ray_hit(origin: [f32; 2], direction: [f32; 2], from: [f32; 2], to: [f32; 2]) -> Option<f32> {
+    let edge = [to[0] - from[0], to[1] - from[1]];
+    let denominator = direction[0] * edge[1] - direction[1] * edge[0];
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+    let offset = [from[0] - origin[0], from[1] - origin[1]];
+    let distance = (offset[0] * edge[1] - offset[1] * edge[0]) / denominator;
+    let along = (offset[0] * direction[1] - offset[1] * direction[0]) / denominator;
+    (distance >= 0.0 && (0.0..=1.0).contains(&along)).then_some(distance)
+}
+
+fn distance_to_segment(point: [f32; 2], from: [f32; 2], to: [f32; 2]) -> f32 {
+    let edge = [to[0] - from[0], to[1] - from[1]];
+    let length = edge[0] * edge[0] + edge[1] * edge[1];
+    let along = match length > 0.0 {
+        true => (((point[0] - from[0]) * edge[0] + (point[1] - from[1]) * edge[1]) / length).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+    let closest = [from[0] + edge[0] * along, from[1] + edge[1] * along];
+    ((point[0] - closest[0]).powi(2) + (point[1] - closest[1]).powi(2)).sqrt()
+}
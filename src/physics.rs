@@ -1,9 +1,15 @@
-use rapier2d::geometry::{Collider, ColliderBuilder};
+use rapier2d::geometry::{Collider, ColliderBuilder, ColliderHandle};
+use rapier2d::math::{Point, Vector};
+use rapier2d::pipeline::{ActiveHooks, ContactModificationContext, PhysicsHooks};
 use rapier2d::prelude::vector;
 
 pub enum AlcubierreColliderType {
     Rectangle((f32, f32)),
     Circle(f32),
+    /// Convex hull of points relative to the object
+    Polygon(Vec<[f32; 2]>),
+    /// Connected segments through points relative to the object
+    Polyline(Vec<[f32; 2]>),
     // Capsule(i32,i32)
 }
 
@@ -22,10 +28,14 @@ pub fn physics_units_to_pixels(units: f32) -> f32 {
     return units * 50.0;
 }
 
+fn physics_point([x, y]: [f32; 2]) -> Point<f32> {
+    Point::new(screen_units_to_physics_units(x), screen_units_to_physics_units(y))
+}
+
 impl AlcubierreCollider {
     pub fn to_rapier(&self, id: u128) -> Collider {
-        match self.collider_type {
-            AlcubierreColliderType::Rectangle((x, y)) => ColliderBuilder::cuboid(
+        match &self.collider_type {
+            &AlcubierreColliderType::Rectangle((x, y)) => ColliderBuilder::cuboid(
                 screen_units_to_physics_units(x) / 2.0,
                 screen_units_to_physics_units(y) / 2.0,
             )
@@ -35,7 +45,7 @@ impl AlcubierreCollider {
             .user_data(id)
             .build(),
 
-            AlcubierreColliderType::Circle(radius) => {
+            &AlcubierreColliderType::Circle(radius) => {
                 let pr = screen_units_to_physics_units(radius);
                 ColliderBuilder::ball(pr)
                     .sensor(self.sensor)
@@ -44,6 +54,48 @@ impl AlcubierreCollider {
                     .user_data(id)
                     .build()
             }
+
+            AlcubierreColliderType::Polygon(points) => {
+                let points: Vec<Point<f32>> = points.iter().map(|&point| physics_point(point)).collect();
+                ColliderBuilder::convex_hull(&points)
+                    .expect("A polygon collider needs at least three points that aren't in a line")
+                    .sensor(self.sensor)
+                    .friction(self.friction)
+                    .restitution(self.restitution)
+                    .user_data(id)
+                    .build()
+            }
+
+            AlcubierreColliderType::Polyline(points) => {
+                let points: Vec<Point<f32>> = points.iter().map(|&point| physics_point(point)).collect();
+                ColliderBuilder::polyline(points, None)
+                    .sensor(self.sensor)
+                    .friction(self.friction)
+                    .restitution(self.restitution)
+                    .user_data(id)
+                    .build()
+            }
+        }
+    }
+}
+
+/// Lets colliders with `ActiveHooks::MODIFY_SOLVER_CONTACTS`, such as one-way tiles, be
+/// passed through from anywhere but above.
+pub(crate) struct OneWayPlatforms;
+
+impl PhysicsHooks for OneWayPlatforms {
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let one_way = |handle: ColliderHandle| {
+            context.colliders[handle]
+                .active_hooks()
+                .contains(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+        };
+        let (first, second) = (one_way(context.collider1), one_way(context.collider2));
+        // The normal points out of the first collider
+        if first {
+            context.update_as_oneway_platform(&Vector::y(), 0.1);
+        } else if second {
+            context.update_as_oneway_platform(&-Vector::y(), 0.1);
         }
     }
 }
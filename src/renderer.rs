@@ -1,56 +1,135 @@
 pub(crate) mod buffer;
+pub(crate) mod shapes;
 pub(crate) mod sprite;
 pub mod camera;
 pub(crate) mod atlas;
+pub(crate) mod light;
+pub(crate) mod material;
+pub(crate) mod pixel;
+pub(crate) mod post;
+pub(crate) mod target;
 mod texture;
 
-use hashbrown::HashMap;
-use std::iter;
+use hashbrown::{HashMap, HashSet};
 use std::num::NonZeroU32;
 use cgmath::Rotation3;
-use wgpu::{BindGroup, BindGroupLayout, Buffer, Sampler, TextureView};
-use wgpu::util::DeviceExt;
+use wgpu::{BindGroup, BindGroupLayout, TextureView};
 use wgpu_glyph::ab_glyph::FontArc;
-use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, Section, Text};
+use wgpu_glyph::{ab_glyph, orthographic_projection, GlyphBrush, GlyphBrushBuilder, Region, Section, Text};
 
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
 use crate::game_object::behaviours::EngineView;
+use crate::game_object::graphics::BlendMode;
+use crate::camera::{Camera2D, DesignResolution, PixelPerfect, UiMode};
 use crate::renderer::camera::{Camera, CameraUniform};
 use crate::ui::backend::wgpu::render_from_hyperfoil_ast;
 use crate::ui::frontend::{HyperFoilAST, RGBColor};
 use crate::MouseData;
 use buffer::*;
 use crate::renderer::atlas::SpriteAtlas;
-use crate::renderer::sprite::{create_sprite_render_pipeline, SpriteVertex};
+use crate::renderer::sprite::create_sprite_render_pipelines;
+use crate::renderer::light::LightRenderer;
+use crate::renderer::material::{Material, MaterialRenderer};
+use crate::renderer::pixel::PixelUpscaler;
+use crate::renderer::post::{PostPass, PostProcessor};
+use crate::renderer::target::{read_texture, GpuRenderTarget, RenderTarget};
 use crate::renderer::texture::Texture;
 
+const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_BINDING_ARRAY
+    .union(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING);
+
 pub struct Render {
-    surface: wgpu::Surface,
+    /// `None` when rendering headless, into `offscreen_frame` instead
+    surface: Option<wgpu::Surface>,
+    offscreen_frame: Option<wgpu::Texture>,
     config: wgpu::SurfaceConfiguration,
     #[allow(dead_code)]
     adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    pipeline: wgpu::RenderPipeline,
-    sprite_render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    /// One per blend mode, in the order of `BlendMode::ALL`
+    pipelines: Vec<wgpu::RenderPipeline>,
+    sprite_render_pipelines: Vec<wgpu::RenderPipeline>,
+    frame_buffers: FrameBuffers,
+    stats: RenderStats,
     staging_belt: wgpu::util::StagingBelt,
-    camera_uniform: CameraUniform,
-    camera_buffer: wgpu::Buffer,
+    /// Fixed camera the UI is laid out with
     camera: camera::Camera,
-    projection: camera::Projection,
-    camera_bind_group: wgpu::BindGroup,
+    camera_bind_group_layout: BindGroupLayout,
+    /// One per view drawn last frame, grown as scenes add cameras
+    view_bindings: Vec<ViewBinding>,
     glyph_brush: GlyphBrush<()>,
     size: PhysicalSize<u32>,
     font: FontArc,
     sprite_bind_group_layout: BindGroupLayout,
-    sprite_vertex_buffer: Buffer,
-    sprite_index_buffer: Buffer,
-    diffuse_bind_group: BindGroup,
-    cached_sprite_atlas_texture: Option<Texture>,
+    sprite_textures: HashMap<String, CachedSpriteTexture>,
+    render_targets: HashMap<String, GpuRenderTarget>,
+    post: PostProcessor,
+    upscaler: PixelUpscaler,
+    materials: MaterialRenderer,
+    lights: LightRenderer,
+    /// Bound where a material has no texture
+    white: Texture,
+    /// Seconds of game time post passes and materials see, so they stop while paused
+    pub(crate) game_time: f32,
+    /// Names of materials already reported missing
+    missing_materials: HashSet<String>,
+    /// Lays the world and the UI out at this size and scales them to the window when set
+    pub(crate) design_resolution: Option<DesignResolution>,
+    /// Draws the window's cameras at a low resolution and scales them up when set, taking
+    /// over from the design resolution for the world
+    pub(crate) pixel_perfect: Option<PixelPerfect>,
+    /// Set to copy the next frame back from the GPU into `captured`
+    pub(crate) capture_next_frame: bool,
+    pub(crate) captured: Option<image::RgbaImage>,
+}
+
+/// Camera uniform of one view. Every view has its own buffer since they are all written
+/// before the frame is submitted.
+struct ViewBinding {
+    buffer: wgpu::Buffer,
+    bind_group: BindGroup,
+}
+
+/// A camera and the part of the window or render target, in pixels, it draws to.
+struct RenderView {
+    uniform: CameraUniform,
+    viewport: [f32; 4],
+    /// Index of the render target drawn into, or `None` for the window
+    target: Option<usize>,
+    /// UI isn't post-processed
+    ui: bool,
+}
+
+/// Where one render pass draws and which of the frame's views it includes.
+struct DrawPass<'a> {
+    target_view: &'a TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+    drawn_views: Vec<bool>,
+    /// Render target being drawn into, whose sprites are skipped
+    own_atlas: Option<&'a str>,
+}
+
+/// A UI drawn into part of the window or a render target.
+struct UiPass<'a> {
+    ast: &'a HyperFoilAST,
+    target: Option<usize>,
+    /// Pixels the UI is drawn in
+    viewport: [f32; 4],
+    /// Size the UI is laid out at, scaled to fit the viewport
+    layout: [f32; 2],
+    /// Size of the window or render target
+    target_size: [f32; 2],
+}
+
+/// GPU copy of an atlas, kept until the atlas is unloaded or replaced.
+struct CachedSpriteTexture {
+    atlas_id: u64,
+    /// `None` for render targets, whose texture is owned by `Render::render_targets`
+    texture: Option<Texture>,
+    bind_group: BindGroup,
 }
 
 impl Render {
@@ -87,17 +166,7 @@ impl Render {
             .await
             .unwrap();
         // warn!("Using adapter: {:?}", adapter.get_info());
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: None,
-                    features: wgpu::Features::TEXTURE_BINDING_ARRAY | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-                    limits: adapter.limits(),
-                },
-                None, // Trace path
-            )
-            .await
-            .unwrap();
+        let (device, queue) = request_device(&adapter).await;
 
         let surface_caps = surface.get_capabilities(&adapter);
 
@@ -111,8 +180,12 @@ impl Render {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
+        // Frames can only be captured where the surface allows copying out of it
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width: size.width,
             height: size.height,
@@ -121,9 +194,61 @@ impl Render {
             view_formats: vec![],
         };
 
-        // Camera system
+        surface.configure(&device, &config);
 
-        let mut camera_uniform = CameraUniform::new();
+        Self::with_device(Some(surface), adapter, device, queue, config)
+    }
+
+    /// Renders into an offscreen frame instead of a window, e.g. for golden-image tests.
+    /// Prefers a software adapter so results match across machines. `None` when there is
+    /// no adapter at all.
+    pub async fn new_headless(width: u32, height: u32) -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            dx12_shader_compiler: Default::default(),
+        });
+
+        let mut options = wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: true,
+        };
+        let adapter = match instance.request_adapter(&options).await {
+            Some(adapter) => adapter,
+            None => {
+                options.force_fallback_adapter = false;
+                instance.request_adapter(&options).await?
+            }
+        };
+        let (device, queue) = request_device(&adapter).await;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+
+        Some(Self::with_device(None, adapter, device, queue, config))
+    }
+
+    fn with_device(
+        surface: Option<wgpu::Surface>,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+    ) -> Self {
+        let size = PhysicalSize::new(config.width, config.height);
+        let offscreen_frame = match surface {
+            Some(_) => None,
+            None => Some(create_offscreen_frame(&device, &config)),
+        };
+
+        // Camera system
 
         let camera = Camera {
             eye: (0.0, 0.0, 6.0).into(),
@@ -131,17 +256,6 @@ impl Render {
             up: cgmath::Vector3::unit_y(),
         };
 
-        let projection =
-            camera::Projection::new(config.width, config.height, cgmath::Deg(90.0), 0.1, 100.0);
-
-        camera_uniform.update_view_proj(&camera, &projection);
-
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera_uniform]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
@@ -156,24 +270,13 @@ impl Render {
             label: Some("bind_group_layout"),
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
-        });
-
         // Textures
         let texture_count = 1;
-        let (sprite_render_pipeline,sprite_bind_group_layout) = create_sprite_render_pipeline(&device,&config,texture_count);
+        let (sprite_render_pipelines,sprite_bind_group_layout) = create_sprite_render_pipelines(&device,&config,texture_count,&bind_group_layout);
 
 
         //
 
-        surface.configure(&device, &config);
-
 
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -182,42 +285,23 @@ impl Render {
             label: Some("Pipeline Layout"),
         });
 
-        let pipeline = create_render_pipeline(
-            &device,
-            &pipeline_layout,
-            config.format,
-            &[Vertex::DESC],
-            wgpu::include_wgsl!("./renderer/shaders/quad.wgsl"),
-        );
-
-        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            size: Vertex::SIZE * 4 * 2000,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Index Buffer"),
-            size: U32_SIZE * 6 * 2000,
-            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let sprite_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Sprite Vertex Buffer"),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            size: Vertex::SIZE * 4 * 2000,
-            mapped_at_creation: false,
-        });
-
-        let sprite_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Sprite Index Buffer"),
-            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-            size: U32_SIZE * 6 * 2000,
-            mapped_at_creation: false,
-        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./renderer/shaders/quad.wgsl"));
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|blend_mode| {
+                create_render_pipeline(
+                    &device,
+                    &pipeline_layout,
+                    config.format,
+                    &[Vertex::DESC],
+                    &shader,
+                    *blend_mode,
+                )
+            })
+            .collect();
 
+        // Enough for 2000 quads to begin with, grown as scenes need more
+        let frame_buffers = FrameBuffers::new(&device, 2000);
 
         let staging_belt = wgpu::util::StagingBelt::new(1024);
 
@@ -227,64 +311,69 @@ impl Render {
 
         let glyph_brush = GlyphBrushBuilder::using_font(font.clone()).build(&device, config.format);
 
-       // sprite_diffuse_bind_group
-
-
-        let fake_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[],
-            label: Some("fake_bind_group_layout"),
-        });
-
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &fake_bind_group_layout,
-            entries: &[],
-            label: Some("fake_diffuse_bind_group"),
-        });
+        let post = PostProcessor::new(&device, config.format);
+        let upscaler = PixelUpscaler::new(&device, config.format);
+        let materials = MaterialRenderer::new(&device, config.format, &bind_group_layout);
+        let lights = LightRenderer::new(&device, config.format, &bind_group_layout);
+        let white = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])));
+        let white = Texture::from_image(&device, &queue, &white, Some("White Texture"));
 
         Self {
             surface,
+            offscreen_frame,
             adapter,
             device,
             queue,
             config,
-            pipeline,
-            vertex_buffer,
-            index_buffer,
+            pipelines,
+            frame_buffers,
+            stats: RenderStats::default(),
             staging_belt,
-            camera_bind_group: bind_group,
-            camera_buffer,
-            camera_uniform,
-            projection,
             camera,
+            camera_bind_group_layout: bind_group_layout,
+            view_bindings: vec![],
             glyph_brush,
             size,
             font,
-            sprite_render_pipeline,
+            sprite_render_pipelines,
             sprite_bind_group_layout,
-            sprite_vertex_buffer,
-            sprite_index_buffer,
-            diffuse_bind_group,
-            cached_sprite_atlas_texture: None
+            sprite_textures: HashMap::new(),
+            render_targets: HashMap::new(),
+            post,
+            upscaler,
+            materials,
+            lights,
+            white,
+            game_time: 0.0,
+            missing_materials: HashSet::new(),
+            design_resolution: None,
+            pixel_perfect: None,
+            capture_next_frame: false,
+            captured: None,
         }
     }
 
+    /// Stats from the last rendered frame.
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
     pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
-            self.projection.resize(new_size.width, new_size.height);
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            self.camera_uniform
-                .update_view_proj(&self.camera, &self.projection);
-            self.queue.write_buffer(
-                &self.camera_buffer,
-                0,
-                bytemuck::cast_slice(&[self.camera_uniform]),
-            );
+            match &self.surface {
+                Some(surface) => surface.configure(&self.device, &self.config),
+                None => {
+                    self.offscreen_frame = Some(create_offscreen_frame(&self.device, &self.config))
+                }
+            }
         }
     }
 
+    /// Draws everything in `buffer`. World geometry must have been pushed with the index of
+    /// the camera it is seen through as its view.
     pub fn render_buffer(
         &mut self,
         mut buffer: QuadBufferBuilder,
@@ -294,174 +383,678 @@ impl Render {
         engine_view: &mut EngineView,
         mouse_data: &MouseData,
         clear_color: &RGBColor,
-        sprite_vertices: Vec<SpriteVertex>,
-        sprite_indices: Vec<u16>,
-        sprite_atlas: &Option<SpriteAtlas>
+        sprite_atlases: &HashMap<String, SpriteAtlas>,
+        cameras: &[Camera2D],
+        ui_mode: UiMode,
+        render_targets: &[RenderTarget],
+        post_passes: &[PostPass],
+        materials: &HashMap<String, Material>,
     ) {
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let surface_frame = match &self.surface {
+            Some(surface) => match surface.get_current_texture() {
+                Ok(frame) => Some(frame),
+                Err(wgpu::SurfaceError::Outdated) => {
+                    println!("Outdated surface texture");
+                    surface.configure(&self.device, &self.config);
+                    return;
+                }
+                Err(e) => {
+                    panic!("Error: {}", e);
+                }
+            },
+            None => None,
+        };
+        // Taken for the frame so drawing can borrow the renderer mutably
+        let offscreen_frame = self.offscreen_frame.take();
+        let frame_texture = match &surface_frame {
+            Some(frame) => &frame.texture,
+            None => offscreen_frame.as_ref().unwrap(),
+        };
+        let window_view = frame_texture.create_view(&Default::default());
+
+        self.update_render_targets(render_targets);
+
+        let window = [self.width(), self.height()];
+        // With a design resolution or in pixel-perfect mode, the window's cameras lay out
+        // their viewports in `world` pixels and draw into a `frame` that is scaled into the
+        // letterbox. The frame is the letterbox's size, or the virtual resolution in
+        // pixel-perfect mode.
+        let pixel_perfect = self.pixel_perfect;
+        let world_design = pixel_perfect.map(|pixel| pixel.design()).or(self.design_resolution);
+        let world = world_design.map_or(window, |design| design.visible(window));
+        let letterbox = world_design.map_or([0.0, 0.0, window[0], window[1]], |design| design.letterbox(window));
+        let frame = pixel_perfect.map_or([letterbox[2], letterbox[3]], |pixel| pixel.size());
+        // The UI follows the design resolution even in pixel-perfect mode
+        let (ui_area, ui_layout) = match self.design_resolution {
+            Some(design) => (design.letterbox(window), design.visible(window)),
+            None => (letterbox, [letterbox[2], letterbox[3]]),
+        };
+        let target_index = |name: &Option<String>| {
+            name.as_ref()
+                .and_then(|name| render_targets.iter().position(|target| &target.name == name))
+        };
+        let mut views: Vec<RenderView> = cameras
+            .iter()
+            .map(|camera| {
+                // A camera whose target is missing draws nothing rather than into the window
+                let target = target_index(&camera.target);
+                let (layout, size) = match target {
+                    Some(index) => (render_targets[index].size(), render_targets[index].size()),
+                    None if camera.target.is_some() => ([0.0, 0.0], [0.0, 0.0]),
+                    None => (world, frame),
+                };
+                RenderView {
+                    uniform: CameraUniform {
+                        view_proj: camera.view_proj(camera.aspect(layout)).into(),
+                    },
+                    viewport: clamp_viewport(camera.viewport.to_pixels(size), size),
+                    target,
+                    ui: false,
+                }
+            })
+            .collect();
+
+        // Text goes on top of everything else in its target, so it's recorded separately
+        // and submitted after the target's pass
+        let mut text_encoders: Vec<(Option<usize>, wgpu::CommandEncoder)> = vec![];
+        let mut ui_passes: Vec<UiPass> = vec![];
+        for (index, target) in render_targets.iter().enumerate() {
+            if let Some(ui) = &target.ui {
+                let size = target.size();
+                ui_passes.push(UiPass {
+                    ast: ui,
+                    target: Some(index),
+                    viewport: [0.0, 0.0, size[0], size[1]],
+                    layout: size,
+                    target_size: size,
+                });
+            }
+        }
+        if let Some(ast) = ast {
+            match ui_mode {
+                UiMode::Global => ui_passes.push(UiPass {
+                    ast,
+                    target: None,
+                    viewport: ui_area,
+                    layout: ui_layout,
+                    target_size: window,
+                }),
+                UiMode::PerViewport => {
+                    for (camera, view) in cameras.iter().zip(&views) {
+                        if !camera.show_ui {
+                            continue;
+                        }
+                        let (viewport, layout) = match view.target {
+                            Some(_) => (view.viewport, [view.viewport[2], view.viewport[3]]),
+                            // Over the camera's part of the UI area, at the window's resolution
+                            None => {
+                                let [x, y, width, height] = camera.viewport.to_pixels([ui_area[2], ui_area[3]]);
+                                let [.., layout_width, layout_height] = camera.viewport.to_pixels(ui_layout);
+                                (
+                                    clamp_viewport([ui_area[0] + x, ui_area[1] + y, width, height], window),
+                                    [layout_width, layout_height],
+                                )
+                            }
+                        };
+                        ui_passes.push(UiPass {
+                            ast,
+                            target: view.target,
+                            viewport,
+                            layout,
+                            target_size: view.target.map_or(window, |index| render_targets[index].size()),
+                        });
+                    }
+                }
+            }
+        }
 
-        if ast.is_some() {
+        buffer.set_blend_mode(BlendMode::Alpha);
+        buffer.set_texel(None);
+        for UiPass {
+            ast,
+            target,
+            viewport: [x, y, width, height],
+            layout,
+            target_size,
+        } in ui_passes
+        {
+            if width < 1.0 || height < 1.0 || layout[0] < 1.0 || layout[1] < 1.0 {
+                continue;
+            }
+            // The UI is laid out as if the window were `layout` pixels, then scaled into the
+            // viewport
+            let size = PhysicalSize::new(layout[0] as u32, layout[1] as u32);
+            let scale = [width / layout[0], height / layout[1]];
+            let projection =
+                camera::Projection::new(size.width, size.height, cgmath::Deg(90.0), 0.1, 100.0);
+            let mut uniform = CameraUniform::new();
+            uniform.update_view_proj(&self.camera, &projection);
+            buffer.set_view(views.len());
+            views.push(RenderView {
+                uniform,
+                viewport: [x, y, width, height],
+                target,
+                ui: true,
+            });
+
+            let mut viewport_mouse = mouse_data.clone();
+            viewport_mouse.mouse_position.x = (viewport_mouse.mouse_position.x - x as f64) / scale[0] as f64;
+            viewport_mouse.mouse_position.y = (viewport_mouse.mouse_position.y - y as f64) / scale[1] as f64;
+            if target.is_some() {
+                // Only the window's UI can be hovered or clicked
+                viewport_mouse = MouseData {
+                    is_middle_pressed: false,
+                    is_left_pressed: false,
+                    is_right_pressed: false,
+                    mouse_position: nalgebra::Vector2::new(f64::MIN, f64::MIN),
+                };
+            }
             render_from_hyperfoil_ast(
-                ast.as_ref().unwrap(),
+                ast,
                 &mut self.glyph_brush,
-                self.size,
+                size,
                 &self.font,
                 data_map,
                 function_map,
                 &mut buffer,
                 engine_view,
-                &self.projection,
-                mouse_data,
+                &projection,
+                &viewport_mouse,
             );
+
+            let target_view = match target {
+                Some(index) => &self.render_targets[&render_targets[index].name].texture.view,
+                None => &window_view,
+            };
+            let mut text_encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Text Encoder") });
+            // Draw the text!
+            self.glyph_brush
+                .draw_queued_with_transform_and_scissoring(
+                    &self.device,
+                    &mut self.staging_belt,
+                    &mut text_encoder,
+                    target_view,
+                    viewport_text_transform(x, y, scale, target_size),
+                    Region {
+                        x: x as u32,
+                        y: y as u32,
+                        width: width as u32,
+                        height: height as u32,
+                    },
+                )
+                .expect("Draw queued");
+            text_encoders.push((target, text_encoder));
         }
 
-        let (stg_vertex, stg_index, num_indices) = buffer.build(&self.device);
+        let ambient_light = buffer.ambient_light();
+        let (mut commands, stats) = buffer.upload(&mut self.frame_buffers, &self.device, &self.queue);
+        self.stats = stats;
+        self.skip_missing_materials(&mut commands, materials);
 
-        stg_vertex.copy_to_buffer(&mut encoder, &self.vertex_buffer);
-        stg_index.copy_to_buffer(&mut encoder, &self.index_buffer);
+        self.update_view_bindings(&views);
+        self.update_sprite_textures(sprite_atlases);
+        self.update_materials(&commands, materials);
 
-        // Sprite stuff
+        // Targets are drawn first, in the order they were added, so the window and later
+        // targets show this frame's contents
+        let post_passes: Vec<&PostPass> = post_passes.iter().filter(|pass| pass.enabled).collect();
+        let mut command_buffers = vec![];
+        let passes = render_targets
+            .iter()
+            .enumerate()
+            .map(|(index, target)| (Some(index), &target.clear_color))
+            .chain(std::iter::once((None, clear_color)));
+        for (pass, pass_clear_color) in passes {
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            let clear = wgpu::LoadOp::Clear(wgpu::Color {
+                r: pass_clear_color.red as f64 / 255.0,
+                g: pass_clear_color.green as f64 / 255.0,
+                b: pass_clear_color.blue as f64 / 255.0,
+                a: pass_clear_color.alpha as f64 / 255.0,
+            });
+            let drawn_views = |ui: Option<bool>| -> Vec<bool> {
+                views
+                    .iter()
+                    .map(|view| view.target == pass && ui.is_none_or(|ui| view.ui == ui))
+                    .collect()
+            };
+            match pass {
+                Some(index) => {
+                    let target = &render_targets[index];
+                    let draw_pass = DrawPass {
+                        target_view: &self.render_targets[&target.name].texture.view,
+                        load: clear,
+                        drawn_views: drawn_views(None),
+                        own_atlas: Some(&target.name),
+                    };
+                    self.draw_commands(&mut encoder, draw_pass, &commands, &views);
+                }
+                None if !post_passes.is_empty() || world_design.is_some() || ambient_light.is_some() => {
+                    // The world is lit, then goes through the post passes and the upscale
+                    // into the frame, then the UI is drawn over the result
+                    let [width, height] = frame.map(|length| (length as u32).max(1));
+                    if world_design.is_some() {
+                        self.upscaler.prepare(&self.device, width, height);
+                    }
+                    if !post_passes.is_empty() {
+                        self.post.prepare(&self.device, width, height);
+                    }
+                    if ambient_light.is_some() {
+                        self.lights.prepare(&self.device, width, height);
+                    }
+                    let world_view = match (post_passes.is_empty(), world_design) {
+                        (false, _) => self.post.scene_view(),
+                        (true, Some(_)) => self.upscaler.frame_view(),
+                        (true, None) => &window_view,
+                    };
+                    let world_pass = DrawPass {
+                        target_view: world_view,
+                        load: clear,
+                        drawn_views: drawn_views(Some(false)),
+                        own_atlas: None,
+                    };
+                    self.draw_commands(&mut encoder, world_pass, &commands, &views);
+                    if let Some(ambient) = ambient_light {
+                        self.draw_lights(&mut encoder, world_view, ambient, &commands, &views);
+                    }
+                    if !post_passes.is_empty() {
+                        let output = match world_design {
+                            Some(_) => self.upscaler.frame_view(),
+                            None => &window_view,
+                        };
+                        let time = self.game_time;
+                        self.post
+                            .run(&self.device, &self.queue, &mut encoder, &post_passes, output, time);
+                    }
+                    if world_design.is_some() {
+                        self.upscaler.draw(&mut encoder, &window_view, letterbox);
+                    }
+                    let ui_pass = DrawPass {
+                        target_view: &window_view,
+                        load: wgpu::LoadOp::Load,
+                        drawn_views: drawn_views(Some(true)),
+                        own_atlas: None,
+                    };
+                    self.draw_commands(&mut encoder, ui_pass, &commands, &views);
+                }
+                None => {
+                    let draw_pass = DrawPass {
+                        target_view: &window_view,
+                        load: clear,
+                        drawn_views: drawn_views(None),
+                        own_atlas: None,
+                    };
+                    self.draw_commands(&mut encoder, draw_pass, &commands, &views);
+                }
+            }
+            command_buffers.push(encoder.finish());
+            let mut index = 0;
+            while index < text_encoders.len() {
+                if text_encoders[index].0 == pass {
+                    command_buffers.push(text_encoders.remove(index).1.finish());
+                } else {
+                    index += 1;
+                }
+            }
+        }
 
-        let stg_sprite_vertex = StagingBuffer::new(&self.device, sprite_vertices.as_slice(), false);
-        let stg_sprite_index =  StagingBuffer::new(&self.device, sprite_indices.as_slice(), true);
+        self.staging_belt.finish();
+        self.queue.submit(command_buffers);
+        if self.capture_next_frame {
+            self.capture_next_frame = false;
+            if frame_texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+                self.captured = Some(read_texture(&self.device, &self.queue, frame_texture));
+            } else {
+                log::warn!("This surface can't be copied from, so the frame wasn't captured");
+            }
+        }
+        if let Some(frame) = surface_frame {
+            frame.present();
+        }
+        self.offscreen_frame = offscreen_frame;
+        self.staging_belt.recall();
+    }
 
-        stg_sprite_vertex.copy_to_buffer(&mut encoder, &self.sprite_vertex_buffer);
-        stg_sprite_index.copy_to_buffer(&mut encoder, &self.sprite_index_buffer);
+    /// Records the commands whose views `pass` includes. Sprites showing the render target
+    /// being drawn into are skipped, since a texture can't be read and written in the same
+    /// pass.
+    fn draw_commands(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        pass: DrawPass,
+        commands: &[DrawCommand],
+        views: &[RenderView],
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Main Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: pass.target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    store: true,
+                    load: pass.load,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
 
-        //
-        match self.surface.get_current_texture() {
-            Ok(frame) => {
-                let view = frame.texture.create_view(&Default::default());
-
-                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("Main Render Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            store: true,
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: clear_color.red as f64 / 255.0,
-                                g: clear_color.green as f64 / 255.0,
-                                b: clear_color.blue as f64 / 255.0,
-                                a: 1.0,
-                            }),
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
+        // Shapes, sprites and materials in one pass, in draw order. Each switch between
+        // them rebinds the buffers since they use different vertex formats.
+        let mut material_draws = self.materials.draws.iter();
+        for command in commands {
+            match command {
+                DrawCommand::Shapes { blend_mode, view, indices } => {
+                    let [x, y, width, height] = views[*view].viewport;
+                    if !pass.drawn_views[*view] || width < 1.0 || height < 1.0 {
+                        continue;
+                    }
+                    render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                    render_pass.set_pipeline(&self.pipelines[*blend_mode as usize]);
+                    render_pass.set_bind_group(0, &self.view_bindings[*view].bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, self.frame_buffers.vertices.buffer.slice(..));
+                    render_pass.set_index_buffer(self.frame_buffers.indices.buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(indices.clone(), 0, 0..1);
+                }
+                DrawCommand::Sprites { atlas, blend_mode, view, indices } => {
+                    let [x, y, width, height] = views[*view].viewport;
+                    if !pass.drawn_views[*view] || width < 1.0 || height < 1.0 || pass.own_atlas == Some(atlas.as_str()) {
+                        continue;
+                    }
+                    render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                    render_pass.set_pipeline(&self.sprite_render_pipelines[*blend_mode as usize]);
+                    render_pass.set_bind_group(0, &self.sprite_textures[atlas].bind_group, &[]);
+                    render_pass.set_bind_group(1, &self.view_bindings[*view].bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, self.frame_buffers.sprite_vertices.buffer.slice(..));
+                    render_pass.set_index_buffer(self.frame_buffers.sprite_indices.buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(indices.clone(), 0, 0..1);
+                }
+                DrawCommand::Material { atlas, blend_mode, view, indices, .. } => {
+                    let draw = material_draws.next().unwrap();
+                    let [x, y, width, height] = views[*view].viewport;
+                    let reads_target = pass.own_atlas.is_some()
+                        && (atlas.as_deref() == pass.own_atlas || draw.texture.as_deref() == pass.own_atlas);
+                    if !pass.drawn_views[*view] || width < 1.0 || height < 1.0 || reads_target {
+                        continue;
+                    }
+                    render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                    render_pass.set_pipeline(self.materials.pipeline(&draw.shader, *blend_mode));
+                    render_pass.set_bind_group(0, &draw.bind_group, &[]);
+                    render_pass.set_bind_group(1, &self.view_bindings[*view].bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, self.frame_buffers.material_vertices.buffer.slice(..));
+                    render_pass.set_index_buffer(self.frame_buffers.material_indices.buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(indices.clone(), 0, 0..1);
+                }
+                DrawCommand::Lights { .. } => {}
+            }
+        }
+    }
 
-                // Setup
-                render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+    /// Adds the lights of the window's world views up over `ambient`, then multiplies the
+    /// world drawn into `world_view` by them. Render targets aren't lit.
+    fn draw_lights(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        world_view: &TextureView,
+        ambient: [f32; 4],
+        commands: &[DrawCommand],
+        views: &[RenderView],
+    ) {
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Light Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.lights.frame_view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: ambient[0] as f64,
+                            g: ambient[1] as f64,
+                            b: ambient[2] as f64,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            for command in commands {
+                let DrawCommand::Lights { view, indices } = command else {
+                    continue;
+                };
+                let [x, y, width, height] = views[*view].viewport;
+                if views[*view].target.is_some() || views[*view].ui || width < 1.0 || height < 1.0 {
+                    continue;
+                }
+                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                render_pass.set_pipeline(&self.lights.accumulate_pipeline);
+                render_pass.set_bind_group(0, &self.view_bindings[*view].bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.frame_buffers.light_vertices.buffer.slice(..));
+                render_pass.set_index_buffer(self.frame_buffers.light_indices.buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(indices.clone(), 0, 0..1);
+            }
+        }
+        self.lights.composite(encoder, world_view);
+    }
+}
 
-                // Geometry
-                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-                render_pass
-                    .set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                render_pass.set_pipeline(&self.pipeline);
-                render_pass.draw_indexed(0..num_indices, 0, 0..1);
+impl Render {
+    /// Writes each view's camera, creating buffers for views that don't have one yet.
+    fn update_view_bindings(&mut self, views: &[RenderView]) {
+        while self.view_bindings.len() < views.len() {
+            let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Camera Buffer"),
+                size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some("camera_bind_group"),
+            });
+            self.view_bindings.push(ViewBinding { buffer, bind_group });
+        }
+        for (view, binding) in views.iter().zip(&self.view_bindings) {
+            self.queue.write_buffer(&binding.buffer, 0, bytemuck::cast_slice(&[view.uniform]));
+        }
+    }
 
-                // Sprites
+    /// Drops draws of materials that were never added, warning once for each.
+    fn skip_missing_materials(&mut self, commands: &mut Vec<DrawCommand>, materials: &HashMap<String, Material>) {
+        commands.retain(|command| match command {
+            DrawCommand::Material { material, .. } if !materials.contains_key(material) => {
+                if self.missing_materials.insert(material.clone()) {
+                    log::warn!("Not drawing with material {}, which was never added", material);
+                }
+                false
+            }
+            _ => true,
+        });
+    }
 
-                // Sprite rendering
-                if sprite_atlas.is_some() {
-                    if self.cached_sprite_atlas_texture.is_none() {
-                        let atlas = sprite_atlas.as_ref().unwrap();
-                        let diffuse_texture = texture::Texture::from_bytes(&self.device, &self.queue, atlas.atlas.as_slice(), "sprite-atlas");
-                        self.cached_sprite_atlas_texture = Some(diffuse_texture);
-                    }
+    /// Compiles new materials and binds the parameters and textures of this frame's
+    /// material draws.
+    fn update_materials(&mut self, commands: &[DrawCommand], materials: &HashMap<String, Material>) {
+        let textures: HashMap<&str, &Texture> = self
+            .sprite_textures
+            .iter()
+            .filter_map(|(name, cached)| cached.texture.as_ref().map(|texture| (name.as_str(), texture)))
+            .chain(self.render_targets.iter().map(|(name, target)| (name.as_str(), &target.texture)))
+            .collect();
+        let texture = |name: &Option<String>| {
+            name.as_deref()
+                .and_then(|name| textures.get(name).copied())
+                .unwrap_or(&self.white)
+        };
 
-                    let safe_cached_sprite_atlas_texture = self.cached_sprite_atlas_texture.as_ref().unwrap();
-
-
-                    self.diffuse_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                        layout: &self.sprite_bind_group_layout,
-                        entries: &[
-                            wgpu::BindGroupEntry {
-                                binding: 0,
-                                resource: wgpu::BindingResource::TextureView(&safe_cached_sprite_atlas_texture.view),
-                            },
-                            wgpu::BindGroupEntry {
-                                binding: 1,
-                                resource: wgpu::BindingResource::Sampler(&safe_cached_sprite_atlas_texture.sampler),
-                            },
-                            wgpu::BindGroupEntry {
-                                binding: 2,
-                                resource: self.camera_buffer.as_entire_binding(),
-                            }
-                        ],
-                        label: Some("diffuse_bind_group"),
-                    });
-
-                    let sprite_indices_len = sprite_indices.len() as u32;
-
-                    render_pass.set_pipeline(&self.sprite_render_pipeline);
-                    render_pass.set_bind_group(2, &self.camera_bind_group, &[]);
-                    render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-                    render_pass.set_vertex_buffer(0, self.sprite_vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(self.sprite_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                    render_pass.draw_indexed(0..sprite_indices_len, 0, 0..1);
+        let draws: Vec<_> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Material { material, params, atlas, .. } => {
+                    let material = &materials[material];
+                    Some((material, material.param_values(params), texture(atlas), texture(&material.texture)))
                 }
+                _ => None,
+            })
+            .collect();
+        self.materials.prepare(&self.device, &self.queue, &draws, self.game_time);
+    }
 
+    /// Creates the textures of new or resized render targets and frees removed ones.
+    fn update_render_targets(&mut self, render_targets: &[RenderTarget]) {
+        self.render_targets.retain(|name, gpu_target| {
+            let current = render_targets.iter().any(|target| {
+                &target.name == name && target.width == gpu_target.width && target.height == gpu_target.height
+            });
+            if !current {
+                gpu_target.texture.texture.destroy();
+            }
+            current
+        });
 
-                drop(render_pass);
-
-                // Draw the text!
-                self.glyph_brush
-                    .draw_queued(
-                        &self.device,
-                        &mut self.staging_belt,
-                        &mut encoder,
-                        &view,
-                        self.size.width,
-                        self.size.height,
-                    )
-                    .expect("Draw queued");
-
-                self.staging_belt.finish();
-                self.queue.submit(iter::once(encoder.finish()));
-                frame.present();
-                self.staging_belt.recall();
+        for target in render_targets {
+            if !self.render_targets.contains_key(&target.name) {
+                // Sprites showing the old texture need binding to the new one
+                self.sprite_textures.remove(&target.name);
+                let gpu_target = GpuRenderTarget::new(&self.device, self.config.format, target);
+                self.render_targets.insert(target.name.clone(), gpu_target);
             }
-            Err(wgpu::SurfaceError::Outdated) => {
-                println!("Outdated surface texture");
-                self.surface.configure(&self.device, &self.config);
+        }
+    }
+
+    /// Uploads new or replaced atlases and frees the GPU textures of unloaded ones. Render
+    /// target atlases are bound to the target's texture instead.
+    fn update_sprite_textures(&mut self, sprite_atlases: &HashMap<String, SpriteAtlas>) {
+        self.sprite_textures.retain(|name, cached| {
+            let current = sprite_atlases
+                .get(name)
+                .is_some_and(|atlas| atlas.id == cached.atlas_id);
+            if !current {
+                if let Some(texture) = &cached.texture {
+                    texture.texture.destroy();
+                }
             }
-            Err(e) => {
-                panic!("Error: {}", e);
+            current
+        });
+
+        for (name, atlas) in sprite_atlases {
+            if self.sprite_textures.contains_key(name) {
+                continue;
             }
+            let texture = if atlas.render_target {
+                match self.render_targets.get(name) {
+                    Some(_) => None,
+                    // Not drawn yet, so there's nothing to show
+                    None => continue,
+                }
+            } else {
+                Some(texture::Texture::from_bytes(&self.device, &self.queue, atlas.atlas.as_slice(), name))
+            };
+            let bound = match &texture {
+                Some(texture) => texture,
+                None => &self.render_targets[name].texture,
+            };
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.sprite_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&bound.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&bound.sampler),
+                    },
+                ],
+                label: Some("diffuse_bind_group"),
+            });
+            self.sprite_textures.insert(name.clone(), CachedSpriteTexture {
+                atlas_id: atlas.id,
+                texture,
+                bind_group,
+            });
         }
     }
 }
 
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features: REQUIRED_FEATURES & adapter.features(),
+                limits: adapter.limits(),
+                label: None,
+            },
+            None, // Trace path
+        )
+        .await
+        .unwrap()
+}
+
+/// What a headless renderer draws into in place of a window's surface.
+fn create_offscreen_frame(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Frame"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    })
+}
+
+/// Keeps a viewport in pixels inside the window, since wgpu rejects ones that poke out.
+fn clamp_viewport([x, y, width, height]: [f32; 4], window: [f32; 2]) -> [f32; 4] {
+    let (x, y) = (x.clamp(0.0, window[0]), y.clamp(0.0, window[1]));
+    [x, y, width.min(window[0] - x).max(0.0), height.min(window[1] - y).max(0.0)]
+}
+
+/// Pixel to clip space transform for text laid out inside a viewport whose top-left is at
+/// (x, y), scaled by `scale` from layout to window pixels.
+fn viewport_text_transform(x: f32, y: f32, scale: [f32; 2], window: [f32; 2]) -> [f32; 16] {
+    let mut transform = orthographic_projection(window[0] as u32, window[1] as u32);
+    transform[0] *= scale[0];
+    transform[5] *= scale[1];
+    transform[12] += 2.0 * x / window[0];
+    transform[13] -= 2.0 * y / window[1];
+    transform
+}
+
 fn create_render_pipeline(
     device: &wgpu::Device,
     layout: &wgpu::PipelineLayout,
     color_format: wgpu::TextureFormat,
     vertex_layouts: &[wgpu::VertexBufferLayout],
-    src: wgpu::ShaderModuleDescriptor,
+    shader: &wgpu::ShaderModule,
+    blend_mode: BlendMode,
 ) -> wgpu::RenderPipeline {
-    let shader = device.create_shader_module(src);
-
     device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Render Pipeline"),
         layout: Some(&layout),
         vertex: wgpu::VertexState {
-            module: &shader,
+            module: shader,
             entry_point: "vs_main",
             buffers: &vertex_layouts,
         },
         fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: "fs_main",
+            module: shader,
+            entry_point: blend_mode.fragment_entry_point(),
             targets: &[Some(wgpu::ColorTargetState {
                 format: color_format,
-                blend: Some(wgpu::BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
+                blend: Some(blend_mode.blend_state()),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
@@ -5,20 +5,27 @@
 use std::fs;
 use std::fs::File;
 use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
 use hashbrown::HashMap;
+use image::GenericImageView;
 use serde_derive::{Deserialize, Serialize};
+use crate::game_object::animation::AnimationClip;
 use crate::game_object::graphics::SpriteID;
+use crate::tween::LoopMode;
 use crate::renderer::sprite::SpriteVertex;
 
+mod packer;
+pub use packer::AtlasPackOptions;
+
 #[derive(Deserialize,Serialize)]
 pub struct AtlasVector2 {
-    x: u64,
-    y: u64
+    pub x: u64,
+    pub y: u64
 }
 #[derive(Deserialize,Serialize)]
 pub struct AtlasSpriteSize {
-    width: u64,
-    height: u64
+    pub width: u64,
+    pub height: u64
 }
 #[derive(Deserialize,Serialize)]
 pub struct GenericAtlasData {
@@ -28,10 +35,10 @@ pub struct GenericAtlasData {
 }
 #[derive(Deserialize,Serialize)]
 pub struct AtlasSpriteTrimInfo {
-    x: u64,
-    y: u64,
-    width: u64,
-    height: u64
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64
 }
 #[derive(Deserialize,Serialize)]
 pub struct AtlasSprite {
@@ -58,9 +65,110 @@ pub struct AtlasDescriptor {
     atlas: GenericAtlasData,
     sprites: Vec<AtlasSprite>
 }
+
+// Aseprite's "Export Sprite Sheet" JSON, in either its hash or array form
+
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteRect {
+    x: u64,
+    y: u64,
+    w: u64,
+    h: u64
+}
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteSize {
+    w: u64,
+    h: u64
+}
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteFrame {
+    #[serde(default)]
+    filename: Option<String>,
+    frame: AsepriteRect,
+    #[serde(default)]
+    rotated: bool,
+    trimmed: bool,
+    spriteSourceSize: AsepriteRect,
+    sourceSize: AsepriteSize,
+    /// Milliseconds
+    duration: u64
+}
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteFrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: String,
+    #[serde(default)]
+    repeat: Option<String>
+}
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteMeta {
+    size: AsepriteSize,
+    #[serde(default)]
+    frameTags: Vec<AsepriteFrameTag>
+}
+/// Read by hand so the hash form keeps its frame order, which `frameTags` index into.
+#[derive(Serialize)]
+pub enum AsepriteFrames {
+    Array(Vec<AsepriteFrame>),
+    Hash(Vec<(String, AsepriteFrame)>)
+}
+#[derive(Deserialize,Serialize)]
+pub struct AsepriteDescriptor {
+    #[serde(deserialize_with = "deserialize_aseprite_frames")]
+    frames: AsepriteFrames,
+    meta: AsepriteMeta
+}
+
+fn deserialize_aseprite_frames<'de, D>(deserializer: D) -> Result<AsepriteFrames, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct FramesVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for FramesVisitor {
+        type Value = AsepriteFrames;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an array or map of Aseprite frames")
+        }
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut frames = vec![];
+            while let Some(frame) = seq.next_element()? {
+                frames.push(frame);
+            }
+            Ok(AsepriteFrames::Array(frames))
+        }
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut frames = vec![];
+            while let Some(entry) = map.next_entry()? {
+                frames.push(entry);
+            }
+            Ok(AsepriteFrames::Hash(frames))
+        }
+    }
+
+    deserializer.deserialize_any(FramesVisitor)
+}
+
+static NEXT_ATLAS_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Every atlas gets a new ID, so the renderer can tell when one has been replaced.
+pub(crate) fn next_atlas_id() -> u64 {
+    NEXT_ATLAS_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Name of the atlas used by sprites that don't name one.
+pub const DEFAULT_ATLAS: &str = "default";
+
 //
 pub struct SpriteAtlas {
-   pub width: u64, pub height: u64, pub atlas: Vec<u8>, sprites: HashMap<SpriteID,ParsedAtlasSprite>
+   pub width: u64, pub height: u64, pub atlas: Vec<u8>, sprites: HashMap<SpriteID,ParsedAtlasSprite>,
+   pub animations: HashMap<String,AnimationClip>,
+   pub(crate) id: u64,
+   /// Drawn on the GPU into a render target each frame rather than loaded from an image
+   pub(crate) render_target: bool
 }
 
 pub(crate) fn get_file_as_byte_vector(filename: &str) -> Vec<u8> {
@@ -77,6 +185,19 @@ impl SpriteAtlas {
         atlas_descriptor_file: &str
     ) -> Self {
         let atlas_descriptor_file_contents = fs::read_to_string(atlas_descriptor_file).unwrap();
+
+        // Aseprite exports always carry "frames" and "meta", rTexPacker ones "atlas" and "sprites".
+        // Sheets naming the app are Aseprite's, so their errors are about Aseprite's format.
+        let from_aseprite = serde_json::from_str::<serde_json::Value>(&atlas_descriptor_file_contents)
+            .is_ok_and(|descriptor| descriptor.pointer("/meta/app").is_some());
+        if from_aseprite {
+            let aseprite_descriptor: AsepriteDescriptor = serde_json::from_str(&atlas_descriptor_file_contents)
+                .unwrap_or_else(|e| panic!("Invalid Aseprite sprite sheet {}: {}", atlas_descriptor_file, e));
+            return Self::from_aseprite(aseprite_descriptor, get_file_as_byte_vector(atlas_file));
+        }
+        if let Ok(aseprite_descriptor) = serde_json::from_str::<AsepriteDescriptor>(&atlas_descriptor_file_contents) {
+            return Self::from_aseprite(aseprite_descriptor, get_file_as_byte_vector(atlas_file));
+        }
         let atlas_descriptor: AtlasDescriptor = serde_json::from_str(&atlas_descriptor_file_contents).unwrap();
 
         let mut sprites = HashMap::new();
@@ -96,75 +217,310 @@ impl SpriteAtlas {
             width: atlas_descriptor.atlas.width,
             height: atlas_descriptor.atlas.height,
             atlas: get_file_as_byte_vector(atlas_file),
-            sprites: sprites
+            sprites: sprites,
+            animations: HashMap::new(),
+            id: next_atlas_id(),
+            render_target: false
         }
     }
 
+    /// Wraps a single image as an atlas holding one sprite called `sprite_id`.
+    pub fn from_texture_file(sprite_id: &str, texture_file: &str) -> Self {
+        let bytes = get_file_as_byte_vector(texture_file);
+        let (width, height) = image::load_from_memory(&bytes).unwrap().dimensions();
+        let (width, height) = (width as u64, height as u64);
+
+        let mut sprites = HashMap::new();
+        sprites.insert(sprite_id.to_string(), ParsedAtlasSprite {
+            origin: AtlasVector2 { x: 0, y: 0 },
+            position: AtlasVector2 { x: 0, y: 0 },
+            sourceSize: AtlasSpriteSize { width, height },
+            padding: 0,
+            trimmed: false,
+            trimRec: AtlasSpriteTrimInfo { x: 0, y: 0, width, height },
+        });
+
+        Self {
+            width,
+            height,
+            atlas: bytes,
+            sprites,
+            animations: HashMap::new(),
+            id: next_atlas_id(),
+            render_target: false
+        }
+    }
+
+    /// Cuts an image into a grid of `count` tiles, `columns` across, named by their index
+    /// from the top-left. `margin` pixels surround the grid and `spacing` pixels sit
+    /// between tiles, like Tiled's tilesets.
+    pub fn from_grid(
+        texture_file: &str,
+        tile_width: u64,
+        tile_height: u64,
+        columns: u64,
+        count: u64,
+        margin: u64,
+        spacing: u64,
+    ) -> Self {
+        let bytes = get_file_as_byte_vector(texture_file);
+        let (width, height) = image::load_from_memory(&bytes).unwrap().dimensions();
+
+        let mut sprites = HashMap::new();
+        for index in 0..count {
+            let x = margin + (index % columns.max(1)) * (tile_width + spacing);
+            let y = margin + (index / columns.max(1)) * (tile_height + spacing);
+            sprites.insert(index.to_string(), ParsedAtlasSprite {
+                origin: AtlasVector2 { x: 0, y: 0 },
+                position: AtlasVector2 { x, y },
+                sourceSize: AtlasSpriteSize { width: tile_width, height: tile_height },
+                padding: 0,
+                trimmed: false,
+                trimRec: AtlasSpriteTrimInfo { x: 0, y: 0, width: tile_width, height: tile_height },
+            });
+        }
+
+        Self {
+            width: width as u64,
+            height: height as u64,
+            atlas: bytes,
+            sprites,
+            animations: HashMap::new(),
+            id: next_atlas_id(),
+            render_target: false
+        }
+    }
+
+    /// Stands in for a render target, so sprites can show what was drawn into it.
+    pub(crate) fn render_target(name: &str, width: u32, height: u32) -> Self {
+        let (width, height) = (width as u64, height as u64);
+        let mut sprites = HashMap::new();
+        sprites.insert(name.to_string(), ParsedAtlasSprite {
+            origin: AtlasVector2 { x: 0, y: 0 },
+            position: AtlasVector2 { x: 0, y: 0 },
+            sourceSize: AtlasSpriteSize { width, height },
+            padding: 0,
+            trimmed: false,
+            trimRec: AtlasSpriteTrimInfo { x: 0, y: 0, width, height },
+        });
+
+        Self {
+            width,
+            height,
+            atlas: vec![],
+            sprites,
+            animations: HashMap::new(),
+            id: next_atlas_id(),
+            render_target: true
+        }
+    }
+
+    fn from_aseprite(descriptor: AsepriteDescriptor, atlas: Vec<u8>) -> Self {
+        let frames: Vec<(String, AsepriteFrame)> = match descriptor.frames {
+            AsepriteFrames::Hash(frames) => frames,
+            AsepriteFrames::Array(frames) => frames
+                .into_iter()
+                .enumerate()
+                .map(|(i, frame)| (frame.filename.clone().unwrap_or_else(|| i.to_string()), frame))
+                .collect(),
+        };
+
+        let mut animations = HashMap::new();
+        for tag in &descriptor.meta.frameTags {
+            let mut clip_frames: Vec<&(String, AsepriteFrame)> = frames
+                .iter()
+                .skip(tag.from)
+                .take(tag.to.saturating_sub(tag.from) + 1)
+                .collect();
+            // Aseprite leaves out the repeat count, or sets it to 0, for tags that loop forever
+            let repeat = match tag.repeat.as_deref().map(str::parse::<usize>) {
+                None | Some(Ok(0)) => None,
+                Some(Ok(repeat)) => Some(repeat),
+                Some(Err(_)) => {
+                    log::warn!("Aseprite tag {} has an invalid repeat count, looping it instead", tag.name);
+                    None
+                }
+            };
+            if tag.direction == "reverse" || tag.direction == "pingpong_reverse" {
+                clip_frames.reverse();
+            }
+            let loop_mode = match (tag.direction.as_str(), repeat) {
+                ("pingpong" | "pingpong_reverse", None) => LoopMode::PingPong,
+                ("pingpong" | "pingpong_reverse", Some(repeat)) => {
+                    log::warn!("Aseprite tag {} repeats {} times, but ping-pong clips loop until stopped", tag.name, repeat);
+                    LoopMode::PingPong
+                }
+                // A tag repeated a few times plays through that many times and stops
+                (_, Some(repeat)) => {
+                    clip_frames = clip_frames.repeat(repeat);
+                    LoopMode::Once
+                }
+                (_, None) => LoopMode::Loop,
+            };
+            let clip = clip_frames
+                .into_iter()
+                .fold(AnimationClip::new(loop_mode), |clip, (name, frame)| {
+                    clip.frame(name, frame.duration as f32 / 1000.0)
+                });
+            animations.insert(tag.name.clone(), clip);
+        }
+
+        let mut sprites = HashMap::new();
+        for (name, frame) in frames {
+            if frame.rotated {
+                panic!("Rotated Aseprite frames are not supported: {}", name);
+            }
+            sprites.insert(name, ParsedAtlasSprite {
+                origin: AtlasVector2 { x: 0, y: 0 },
+                position: AtlasVector2 { x: frame.frame.x, y: frame.frame.y },
+                sourceSize: AtlasSpriteSize { width: frame.sourceSize.w, height: frame.sourceSize.h },
+                padding: 0,
+                trimmed: frame.trimmed,
+                trimRec: AtlasSpriteTrimInfo {
+                    x: frame.spriteSourceSize.x,
+                    y: frame.spriteSourceSize.y,
+                    width: frame.spriteSourceSize.w,
+                    height: frame.spriteSourceSize.h,
+                },
+            });
+        }
+
+        Self {
+            width: descriptor.meta.size.w,
+            height: descriptor.meta.size.h,
+            atlas,
+            sprites,
+            animations,
+            id: next_atlas_id(),
+            render_target: false
+        }
+    }
+
+    pub fn sprite(&self, id: &str) -> Option<&ParsedAtlasSprite> {
+        self.sprites.get(id)
+    }
+
+    pub(crate) fn sprite_ids(&self) -> impl Iterator<Item = &SpriteID> {
+        self.sprites.keys()
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&AnimationClip> {
+        self.animations.get(name)
+    }
+
+    /// Builds the quad for `sprite`. `local_sprite_scale` is the size of the untrimmed frame,
+    /// so trimmed frames keep their place within it.
+    ///
+    /// `pivot` is the point of the frame placed at `local_sprite_position`, from (0, 0) at the
+    /// bottom-left to (1, 1) at the top-right. Without one the atlas origin is used, and
+    /// sprites with no origin keep their bottom-left corner on the position.
     pub fn get_sprite_from_atlas(
         &self,
-        atlas_sprite_position: &AtlasVector2,
-        atlas_sprite_size: &AtlasSpriteSize,
+        sprite: &ParsedAtlasSprite,
         local_sprite_position: [f32; 2],
         local_sprite_scale: [f32; 2],
+        pivot: Option<[f32; 2]>,
         flip_h: bool,
         flip_v: bool,
-    ) -> ([SpriteVertex; 4], [u16; 6]) {
+    ) -> ([SpriteVertex; 4], [u32; 6]) {
+        let source_width = sprite.sourceSize.width.max(1) as f32;
+        let source_height = sprite.sourceSize.height.max(1) as f32;
+
+        // Region of the atlas holding the pixels, and where it sits in the frame (y down)
+        let (trim_x, trim_y, region_width, region_height) = if sprite.trimmed {
+            (
+                sprite.trimRec.x as f32,
+                sprite.trimRec.y as f32,
+                sprite.trimRec.width,
+                sprite.trimRec.height,
+            )
+        } else {
+            (0.0, 0.0, sprite.sourceSize.width, sprite.sourceSize.height)
+        };
+
+        // Everything below is in frame pixels with y up, like the world
+        let pivot = pivot.unwrap_or_else(|| {
+            if sprite.origin.x == 0 && sprite.origin.y == 0 {
+                [0.0, 0.0]
+            } else {
+                [
+                    sprite.origin.x as f32 / source_width,
+                    1.0 - sprite.origin.y as f32 / source_height,
+                ]
+            }
+        });
+        let mut pivot_x = pivot[0] * source_width;
+        let mut pivot_y = pivot[1] * source_height;
+        let mut left = trim_x;
+        let mut bottom = source_height - (trim_y + region_height as f32);
+        // Flipping mirrors the frame around its pivot
+        if flip_h {
+            left = source_width - (trim_x + region_width as f32);
+            pivot_x = source_width - pivot_x;
+        }
+        if flip_v {
+            bottom = trim_y;
+            pivot_y = source_height - pivot_y;
+        }
+
+        let scale_x = local_sprite_scale[0] / source_width;
+        let scale_y = local_sprite_scale[1] / source_height;
+        let min_x = local_sprite_position[0] + (left - pivot_x) * scale_x;
+        let min_y = local_sprite_position[1] + (bottom - pivot_y) * scale_y;
+        let max_x = min_x + region_width as f32 * scale_x;
+        let max_y = min_y + region_height as f32 * scale_y;
+
         let (u1, u2) = if flip_h {
-            let x = (atlas_sprite_position.x + atlas_sprite_size.width) as f32 / self.width as f32;
-            let y = atlas_sprite_position.x as f32 / self.width as f32;
+            let x = (sprite.position.x + region_width) as f32 / self.width as f32;
+            let y = sprite.position.x as f32 / self.width as f32;
             (x, y)
         } else {
-            let x = atlas_sprite_position.x as f32 / self.width as f32;
-            let y = (atlas_sprite_position.x + atlas_sprite_size.width) as f32 / self.width as f32;
+            let x = sprite.position.x as f32 / self.width as f32;
+            let y = (sprite.position.x + region_width) as f32 / self.width as f32;
             (x, y)
         };
 
         let (v1, v2) = if flip_v {
-            let x = atlas_sprite_position.y as f32 / self.height as f32;
-            let y = (atlas_sprite_position.y + atlas_sprite_size.height) as f32 / self.height as f32;
+            let x = sprite.position.y as f32 / self.height as f32;
+            let y = (sprite.position.y + region_height) as f32 / self.height as f32;
             (x, y)
         } else {
-            let x = (atlas_sprite_position.y + atlas_sprite_size.height) as f32 / self.height as f32;
-            let y = atlas_sprite_position.y as f32 / self.height as f32;
+            let x = (sprite.position.y + region_height) as f32 / self.height as f32;
+            let y = sprite.position.y as f32 / self.height as f32;
             (x, y)
         };
 
         let vertices = [
             SpriteVertex {
-                position: [local_sprite_position[0], local_sprite_position[1], 0.0],
+                position: [min_x, min_y, 0.0],
                 tex_coords: [u1, v1],
+                color: [1.0; 4],
+                flash: 0.0,
             }, // Bottom-Left
             SpriteVertex {
-                position: [
-                    local_sprite_position[0] + local_sprite_scale[0],
-                    local_sprite_position[1],
-                    0.0,
-                ],
+                position: [max_x, min_y, 0.0],
                 tex_coords: [u2, v1],
+                color: [1.0; 4],
+                flash: 0.0,
             }, // Bottom-Right
             SpriteVertex {
-                position: [
-                    local_sprite_position[0] + local_sprite_scale[0],
-                    local_sprite_position[1] + local_sprite_scale[1],
-                    0.0,
-                ],
+                position: [max_x, max_y, 0.0],
                 tex_coords: [u2, v2],
+                color: [1.0; 4],
+                flash: 0.0,
             }, // Top-Right
             SpriteVertex {
-                position: [
-                    local_sprite_position[0],
-                    local_sprite_position[1] + local_sprite_scale[1],
-                    0.0,
-                ],
+                position: [min_x, max_y, 0.0],
                 tex_coords: [u1, v2],
+                color: [1.0; 4],
+                flash: 0.0,
             }, // Top-Left
         ];
         let indices = [0, 1, 2, 0, 2, 3];
         (vertices, indices)
     }
 
-    pub fn lookup_sprite_data_from_descriptor(&self,id: &SpriteID) -> &ParsedAtlasSprite {
-        let sprite = self.sprites.get(id).unwrap();
-        return sprite;
+    pub fn lookup_sprite_data_from_descriptor(&self,id: &SpriteID) -> Option<&ParsedAtlasSprite> {
+        self.sprites.get(id)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,256 @@
+// Packs loose images into a single atlas at runtime using a simple shelf packer.
+
+use crate::renderer::atlas::{
+    next_atlas_id, AtlasSpriteSize, AtlasSpriteTrimInfo, AtlasVector2, ParsedAtlasSprite, SpriteAtlas,
+};
+use hashbrown::HashMap;
+use image::{DynamicImage, GenericImageView, ImageOutputFormat, RgbaImage};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug)]
+pub struct AtlasPackOptions {
+    /// Empty pixels between sprites, stops neighbours bleeding in when filtering
+    pub padding: u32,
+    /// Crops fully transparent borders. The original size is kept in the sprite's trim data.
+    pub trim: bool,
+    pub power_of_two: bool,
+    pub max_size: u32,
+}
+
+impl Default for AtlasPackOptions {
+    fn default() -> Self {
+        AtlasPackOptions {
+            padding: 2,
+            trim: false,
+            power_of_two: true,
+            max_size: 4096,
+        }
+    }
+}
+
+struct PackInput {
+    name: String,
+    image: RgbaImage,
+    source_width: u32,
+    source_height: u32,
+    trim_x: u32,
+    trim_y: u32,
+}
+
+/// Bounds of the non-transparent pixels, or `None` if the image is fully transparent.
+fn opaque_bounds(image: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    if min_x == u32::MAX {
+        None
+    } else {
+        Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+    }
+}
+
+/// Places every rect on shelves `width` wide. Returns positions and the used height.
+fn shelf_pack(sizes: &[(u32, u32)], order: &[usize], width: u32, padding: u32) -> Option<(Vec<(u32, u32)>, u32)> {
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height) = (padding, padding, 0);
+    for &i in order {
+        let (w, h) = sizes[i];
+        if w + padding * 2 > width {
+            return None;
+        }
+        if x + w + padding > width {
+            x = padding;
+            y += shelf_height + padding;
+            shelf_height = 0;
+        }
+        positions[i] = (x, y);
+        x += w + padding;
+        shelf_height = shelf_height.max(h);
+    }
+    Some((positions, y + shelf_height + padding))
+}
+
+impl SpriteAtlas {
+    /// Packs every PNG and JPEG in `path` into a new atlas. Sprites are named after their file stem.
+    pub fn pack_from_dir(path: &str, options: &AtlasPackOptions) -> Self {
+        let mut files: Vec<_> = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|file| {
+                file.extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| matches!(extension.to_ascii_lowercase().as_str(), "png" | "jpg" | "jpeg"))
+                    .unwrap_or(false)
+            })
+            .collect();
+        // Keep the layout stable between runs
+        files.sort();
+
+        let mut named: HashMap<String, &PathBuf> = HashMap::new();
+        let images = files
+            .iter()
+            .map(|file| {
+                let name = file.file_stem().unwrap().to_string_lossy().to_string();
+                if let Some(other) = named.insert(name.clone(), file) {
+                    panic!("{} and {} would both be sprite {}", other.display(), file.display(), name);
+                }
+                let image = image::open(file).unwrap_or_else(|e| panic!("Unable to open sprite {}: {}", file.display(), e));
+                (name, image)
+            })
+            .collect();
+        Self::pack_from_images(images, options)
+    }
+
+    pub fn pack_from_images(images: Vec<(String, DynamicImage)>, options: &AtlasPackOptions) -> Self {
+        let inputs: Vec<PackInput> = images
+            .into_iter()
+            .map(|(name, image)| {
+                let (source_width, source_height) = image.dimensions();
+                let image = image.to_rgba8();
+                let bounds = if options.trim {
+                    // Fully transparent images keep a single pixel so they still have a rect
+                    opaque_bounds(&image).unwrap_or((0, 0, 1, 1))
+                } else {
+                    (0, 0, source_width, source_height)
+                };
+                let (trim_x, trim_y, width, height) = bounds;
+                let image = if (width, height) == (source_width, source_height) {
+                    image
+                } else {
+                    image::imageops::crop_imm(&image, trim_x, trim_y, width, height).to_image()
+                };
+                PackInput {
+                    name,
+                    image,
+                    source_width,
+                    source_height,
+                    trim_x,
+                    trim_y,
+                }
+            })
+            .collect();
+
+        let sizes: Vec<(u32, u32)> = inputs.iter().map(|input| input.image.dimensions()).collect();
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by(|a, b| sizes[*b].1.cmp(&sizes[*a].1).then(sizes[*b].0.cmp(&sizes[*a].0)));
+
+        let padding = options.padding;
+        let area: u64 = sizes
+            .iter()
+            .map(|(w, h)| ((w + padding) as u64) * ((h + padding) as u64))
+            .sum();
+        let widest = sizes.iter().map(|(w, _)| w + padding * 2).max().unwrap_or(1);
+        let mut width = ((area as f64).sqrt().ceil() as u32).max(widest);
+        if options.power_of_two {
+            width = width.next_power_of_two();
+        }
+
+        let (positions, height) = loop {
+            if width > options.max_size {
+                panic!("Sprites do not fit in a {0}x{0} atlas", options.max_size);
+            }
+            // Round before checking the size, as rounding up can take it past the limit
+            let packed = shelf_pack(&sizes, &order, width, padding).map(|(positions, height)| {
+                let height = if options.power_of_two {
+                    height.next_power_of_two()
+                } else {
+                    height
+                };
+                (positions, height)
+            });
+            match packed {
+                Some((positions, height))
+                    if height <= options.max_size && (height <= width * 2 || width * 2 > options.max_size) =>
+                {
+                    break (positions, height);
+                }
+                _ => width *= 2,
+            }
+        };
+
+        let mut bitmap = RgbaImage::new(width, height);
+        let mut sprites = HashMap::new();
+        for (input, (x, y)) in inputs.into_iter().zip(positions) {
+            image::imageops::replace(&mut bitmap, &input.image, x as i64, y as i64);
+            let (trimmed_width, trimmed_height) = input.image.dimensions();
+            sprites.insert(
+                input.name,
+                ParsedAtlasSprite {
+                    origin: AtlasVector2 { x: 0, y: 0 },
+                    position: AtlasVector2 { x: x as u64, y: y as u64 },
+                    sourceSize: AtlasSpriteSize {
+                        width: input.source_width as u64,
+                        height: input.source_height as u64,
+                    },
+                    padding: padding as u64,
+                    trimmed: (trimmed_width, trimmed_height) != (input.source_width, input.source_height),
+                    trimRec: AtlasSpriteTrimInfo {
+                        x: input.trim_x as u64,
+                        y: input.trim_y as u64,
+                        width: trimmed_width as u64,
+                        height: trimmed_height as u64,
+                    },
+                },
+            );
+        }
+
+        let mut atlas = Vec::new();
+        DynamicImage::ImageRgba8(bitmap)
+            .write_to(&mut Cursor::new(&mut atlas), ImageOutputFormat::Png)
+            .unwrap();
+
+        SpriteAtlas {
+            width: width as u64,
+            height: height as u64,
+            atlas,
+            sprites,
+            animations: HashMap::new(),
+            id: next_atlas_id(),
+            render_target: false,
+        }
+    }
+
+    /// Writes the atlas PNG and an rTexPacker style descriptor, which `SpriteAtlas::new`
+    /// can load without packing again.
+    pub fn write_to_files(&self, atlas_file: &str, atlas_descriptor_file: &str) {
+        fs::write(atlas_file, &self.atlas).unwrap();
+        fs::write(atlas_descriptor_file, self.to_descriptor_json()).unwrap();
+    }
+
+    pub fn to_descriptor_json(&self) -> String {
+        let mut names: Vec<&String> = self.sprites.keys().collect();
+        names.sort();
+        let sprites: Vec<serde_json::Value> = names
+            .into_iter()
+            .map(|name| {
+                let sprite = &self.sprites[name];
+                serde_json::json!({
+                    "nameId": name,
+                    "origin": sprite.origin,
+                    "position": sprite.position,
+                    "sourceSize": sprite.sourceSize,
+                    "padding": sprite.padding,
+                    "trimmed": sprite.trimmed,
+                    "trimRec": sprite.trimRec,
+                })
+            })
+            .collect();
+        let descriptor = serde_json::json!({
+            "atlas": {
+                "width": self.width,
+                "height": self.height,
+                "spriteCount": sprites.len(),
+            },
+            "sprites": sprites,
+        });
+        serde_json::to_string_pretty(&descriptor).unwrap()
+    }
+}
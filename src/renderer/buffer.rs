@@ -1,8 +1,13 @@
+use crate::game_object::graphics::BlendMode;
+use crate::renderer::light::LightVertex;
+use crate::renderer::material::{MaterialInstance, MaterialVertex};
+use crate::renderer::shapes::{circle_points, polyline, rect_points, triangulate};
+use crate::renderer::sprite::SpriteVertex;
 use crate::ui::frontend::RGBColor;
 
 use cgmath::num_traits::Pow;
 use log::{info, warn};
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use std::ops::Range;
 
 pub const U32_SIZE: wgpu::BufferAddress = std::mem::size_of::<u32>() as wgpu::BufferAddress;
 
@@ -10,8 +15,9 @@ pub const U32_SIZE: wgpu::BufferAddress = std::mem::size_of::<u32>() as wgpu::Bu
 pub struct Vertex {
     #[allow(dead_code)]
     pub(crate) position: [f32; 2],
+    /// Linear RGBA
     #[allow(dead_code)]
-    pub(crate) color: [f32; 3],
+    pub(crate) color: [f32; 4],
 }
 
 #[derive(Copy, Clone)]
@@ -26,6 +32,21 @@ pub struct Color {
     pub(crate) a: f32,
 }
 
+/// Converts an sRGB colour channel to the linear value the shaders expect.
+pub(crate) fn srgb_to_linear(channel: u8) -> f32 {
+    ((channel as f32 / 255.0 + 0.055) / 1.055).pow(2.4)
+}
+
+/// Converts a colour to the linear RGBA the shaders expect. Alpha is already linear.
+pub(crate) fn linear_rgba(color: &RGBColor) -> [f32; 4] {
+    [
+        srgb_to_linear(color.red),
+        srgb_to_linear(color.green),
+        srgb_to_linear(color.blue),
+        color.alpha as f32 / 255.0,
+    ]
+}
+
 pub fn size_of_slice<T: Sized>(slice: &[T]) -> usize {
     std::mem::size_of::<T>() * slice.len()
 }
@@ -43,15 +64,63 @@ impl Vertex {
         step_mode: wgpu::VertexStepMode::Vertex,
         attributes: &wgpu::vertex_attr_array![
             0 => Float32x2,
-            1 => Float32x3
+            1 => Float32x4
         ],
     };
 }
 
+/// One draw call. Commands are drawn in the order they were pushed, so shapes and sprites
+/// can be layered in front of each other.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum DrawCommand {
+    Shapes {
+        blend_mode: BlendMode,
+        view: usize,
+        indices: Range<u32>,
+    },
+    Sprites {
+        atlas: String,
+        blend_mode: BlendMode,
+        view: usize,
+        indices: Range<u32>,
+    },
+    Material {
+        material: String,
+        /// The graphic's own parameter values
+        params: Vec<(String, f32)>,
+        /// Bound to the material's main texture, white when `None`
+        atlas: Option<String>,
+        blend_mode: BlendMode,
+        view: usize,
+        indices: Range<u32>,
+    },
+    /// Added to the light frame rather than drawn with the world
+    Lights {
+        view: usize,
+        indices: Range<u32>,
+    },
+}
+
 pub struct QuadBufferBuilder {
     vertex_data: Vec<Vertex>,
     index_data: Vec<u32>,
     current_vert: u32,
+    blend_mode: BlendMode,
+    view: usize,
+    sprite_vertices: Vec<SpriteVertex>,
+    sprite_indices: Vec<u32>,
+    material_vertices: Vec<MaterialVertex>,
+    material_indices: Vec<u32>,
+    light_vertices: Vec<LightVertex>,
+    light_indices: Vec<u32>,
+    /// Linear colour the window's world is lit with before any lights, `None` to leave it
+    /// unlit
+    ambient_light: Option<[f32; 4]>,
+    commands: Vec<DrawCommand>,
+    /// World size of a texel of the current view in pixel-perfect mode
+    texel: Option<f32>,
+    /// World area the current view can see, as min x, min y, max x, max y
+    view_bounds: Option<[f32; 4]>,
 }
 
 impl QuadBufferBuilder {
@@ -60,6 +129,194 @@ impl QuadBufferBuilder {
             vertex_data: vec![],
             index_data: vec![],
             current_vert: 0,
+            blend_mode: BlendMode::Alpha,
+            view: 0,
+            sprite_vertices: vec![],
+            sprite_indices: vec![],
+            material_vertices: vec![],
+            material_indices: vec![],
+            light_vertices: vec![],
+            light_indices: vec![],
+            ambient_light: None,
+            commands: vec![],
+            texel: None,
+            view_bounds: None,
+        }
+    }
+
+    /// Blend mode for shapes pushed from now on.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// View, a camera and viewport, that everything pushed from now on is drawn through.
+    pub(crate) fn set_view(&mut self, view: usize) {
+        self.view = view;
+    }
+
+    /// Texel size sprites pushed from now on are snapped to, `None` to stop snapping.
+    pub(crate) fn set_texel(&mut self, texel: Option<f32>) {
+        self.texel = texel;
+    }
+
+    /// World area the current view can see, `None` to draw everything.
+    pub(crate) fn set_view_bounds(&mut self, bounds: Option<[f32; 4]>) {
+        self.view_bounds = bounds;
+    }
+
+    /// Lights the world the window's cameras draw with `color` and the lights pushed,
+    /// `None` to leave it unlit.
+    pub(crate) fn set_ambient_light(&mut self, color: Option<&RGBColor>) {
+        self.ambient_light = color.map(linear_rgba);
+    }
+
+    pub(crate) fn ambient_light(&self) -> Option<[f32; 4]> {
+        self.ambient_light
+    }
+
+    /// Whether anything inside `bounds`, as min x, min y, max x, max y, can be seen.
+    pub(crate) fn in_view(&self, bounds: [f32; 4]) -> bool {
+        match self.view_bounds {
+            Some(view) => bounds[0] <= view[2] && bounds[2] >= view[0] && bounds[1] <= view[3] && bounds[3] >= view[1],
+            None => true,
+        }
+    }
+
+    /// How far to move a sprite with a corner at `corner` so its corners land on texels,
+    /// nothing outside pixel-perfect mode.
+    pub(crate) fn texel_offset(&self, corner: [f32; 2]) -> [f32; 2] {
+        match self.texel {
+            Some(texel) => corner.map(|axis| (axis / texel).round() * texel - axis),
+            None => [0.0, 0.0],
+        }
+    }
+
+    /// Adds the shape indices pushed since the last call to the current draw, or starts a
+    /// new one if something else was drawn in between.
+    fn extend_draw(&mut self) {
+        let end = self.index_data.len() as u32;
+        let start = self
+            .commands
+            .iter()
+            .rev()
+            .find_map(|command| match command {
+                DrawCommand::Shapes { indices, .. } => Some(indices.end),
+                _ => None,
+            })
+            .unwrap_or(0);
+        match self.commands.last_mut() {
+            Some(DrawCommand::Shapes { blend_mode, view, indices })
+                if *blend_mode == self.blend_mode && *view == self.view =>
+            {
+                indices.end = end
+            }
+            _ => self.commands.push(DrawCommand::Shapes {
+                blend_mode: self.blend_mode,
+                view: self.view,
+                indices: start..end,
+            }),
+        }
+    }
+
+    /// Adds a sprite quad whose indices are local to its four vertices. Consecutive sprites
+    /// from the same atlas share a draw.
+    pub(crate) fn push_sprite(
+        &mut self,
+        atlas: &str,
+        blend_mode: BlendMode,
+        vertices: &[SpriteVertex; 4],
+        indices: &[u32; 6],
+    ) {
+        self.push_sprites(atlas, blend_mode, vertices, indices);
+    }
+
+    /// Adds any number of sprite quads from one atlas, with indices local to `vertices`.
+    pub(crate) fn push_sprites(
+        &mut self,
+        atlas: &str,
+        blend_mode: BlendMode,
+        vertices: &[SpriteVertex],
+        indices: &[u32],
+    ) {
+        let base = self.sprite_vertices.len() as u32;
+        let start = self.sprite_indices.len() as u32;
+        self.sprite_vertices.extend_from_slice(vertices);
+        self.sprite_indices.extend(indices.iter().map(|index| base + index));
+        let end = self.sprite_indices.len() as u32;
+
+        match self.commands.last_mut() {
+            Some(DrawCommand::Sprites {
+                atlas: last_atlas,
+                blend_mode: last_blend_mode,
+                view,
+                indices,
+            }) if last_atlas == atlas && *last_blend_mode == blend_mode && *view == self.view => indices.end = end,
+            _ => self.commands.push(DrawCommand::Sprites {
+                atlas: atlas.to_string(),
+                blend_mode,
+                view: self.view,
+                indices: start..end,
+            }),
+        }
+    }
+
+    /// Adds triangles drawn with a material. Consecutive draws of the same material, with
+    /// the same parameters and texture, share a draw.
+    pub(crate) fn push_material(
+        &mut self,
+        material: &MaterialInstance,
+        atlas: Option<&str>,
+        blend_mode: BlendMode,
+        vertices: &[MaterialVertex],
+        indices: &[u32],
+    ) {
+        let base = self.material_vertices.len() as u32;
+        let start = self.material_indices.len() as u32;
+        self.material_vertices.extend_from_slice(vertices);
+        self.material_indices.extend(indices.iter().map(|index| base + index));
+        let end = self.material_indices.len() as u32;
+
+        match self.commands.last_mut() {
+            Some(DrawCommand::Material {
+                material: last_material,
+                params,
+                atlas: last_atlas,
+                blend_mode: last_blend_mode,
+                view,
+                indices,
+            }) if *last_material == material.material
+                && *params == material.params
+                && last_atlas.as_deref() == atlas
+                && *last_blend_mode == blend_mode
+                && *view == self.view =>
+            {
+                indices.end = end
+            }
+            _ => self.commands.push(DrawCommand::Material {
+                material: material.material.clone(),
+                params: material.params.clone(),
+                atlas: atlas.map(str::to_string),
+                blend_mode,
+                view: self.view,
+                indices: start..end,
+            }),
+        }
+    }
+
+    /// Adds a light's triangles, with indices local to `vertices`, to the current view.
+    pub(crate) fn push_light(&mut self, vertices: &[LightVertex], indices: &[u32]) {
+        let base = self.light_vertices.len() as u32;
+        let start = self.light_indices.len() as u32;
+        self.light_vertices.extend_from_slice(vertices);
+        self.light_indices.extend(indices.iter().map(|index| base + index));
+        let end = self.light_indices.len() as u32;
+
+        match self.commands.last_mut() {
+            Some(DrawCommand::Lights { view, indices }) if *view == self.view => indices.end = end,
+            _ => self.commands.push(DrawCommand::Lights {
+                view: self.view,
+                indices: start..end,
+            }),
         }
     }
 
@@ -73,30 +330,44 @@ impl QuadBufferBuilder {
         );
     }
 
+    pub fn push_rotated_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        rotation: f32,
+        color: &RGBColor,
+    ) {
+        let (sin, cos) = rotation.sin_cos();
+        let (half_width, half_height) = (width * 0.5, height * 0.5);
+        let corners = [
+            [-half_width, -half_height],
+            [half_width, -half_height],
+            [half_width, half_height],
+            [-half_width, half_height],
+        ]
+        .map(|[cx, cy]| [x + cx * cos - cy * sin, y + cx * sin + cy * cos]);
+        self.push_quad_points(corners, color);
+    }
+
     pub fn push_quad(&mut self, min_x: f32, min_y: f32, max_x: f32, max_y: f32, color: &RGBColor) {
-        let red = ((color.red as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
-        let green = ((color.green as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
-        let blue = ((color.blue as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
-
-        let vertices = &[
-            Vertex {
-                position: [min_x, min_y],
-                color: [red, green, blue],
-            },
-            Vertex {
-                position: [max_x, min_y],
-                color: [red, green, blue],
-            },
-            Vertex {
-                position: [max_x, max_y],
-                color: [red, green, blue],
-            },
-            Vertex {
-                position: [min_x, max_y],
-                color: [red, green, blue],
-            },
-        ];
-        self.vertex_data.extend(vertices);
+        self.push_quad_points(
+            [
+                [min_x, min_y],
+                [max_x, min_y],
+                [max_x, max_y],
+                [min_x, max_y],
+            ],
+            color,
+        );
+    }
+
+    /// Pushes a quad from its four corners in counter-clockwise order.
+    pub fn push_quad_points(&mut self, corners: [[f32; 2]; 4], color: &RGBColor) {
+        let color = linear_rgba(color);
+
+        self.vertex_data.extend(corners.map(|position| Vertex { position, color }));
 
         self.index_data.extend(&[
             self.current_vert + 0,
@@ -107,6 +378,7 @@ impl QuadBufferBuilder {
             self.current_vert + 3,
         ]);
         self.current_vert += 4;
+        self.extend_draw();
     }
 
     pub fn push_circle(
@@ -117,16 +389,14 @@ impl QuadBufferBuilder {
         color: &RGBColor,
         sides: u8,
     ) {
-        // Convert color to sRGB
-        let red = ((color.red as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
-        let green = ((color.green as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
-        let blue = ((color.blue as f32 / 255.0 + 0.055) / 1.055).pow(2.4);
+        let color = linear_rgba(color);
 
         let mut rot: f32 = 0.0;
+        let center = self.current_vert;
 
         self.vertex_data.push(Vertex {
             position: [pos_x, pos_y],
-            color: [red, green, blue],
+            color,
         });
         self.current_vert += 1;
 
@@ -135,53 +405,217 @@ impl QuadBufferBuilder {
             let ry = (i as f32 / sides as f32 * std::f32::consts::PI * 2. + rot).sin();
             self.vertex_data.push(Vertex {
                 position: [pos_x + radius * rx, pos_y + radius * ry],
-                color: [red, green, blue],
+                color,
             });
             self.current_vert += 1;
 
             if i != sides {
                 self.index_data
-                    .extend_from_slice(&[0, i as u32 + 1, i as u32 + 2]);
+                    .extend_from_slice(&[center, center + i as u32 + 1, center + i as u32 + 2]);
             }
         }
+        self.extend_draw();
+    }
+
+    /// Adds coloured triangles with indices local to `vertices`, in one go.
+    pub(crate) fn push_shapes(&mut self, vertices: &[Vertex], indices: &[u32]) {
+        let base = self.current_vert;
+        self.vertex_data.extend_from_slice(vertices);
+        self.index_data.extend(indices.iter().map(|index| base + index));
+        self.current_vert += vertices.len() as u32;
+        self.extend_draw();
+    }
+
+    /// Pushes triangles given as counter-clockwise indices into `points`.
+    pub fn push_triangles(&mut self, points: &[[f32; 2]], indices: &[u32], color: &RGBColor) {
+        let color = linear_rgba(color);
+        let base = self.current_vert;
+        self.vertex_data
+            .extend(points.iter().map(|position| Vertex { position: *position, color }));
+        self.index_data.extend(indices.iter().map(|index| base + index));
+        self.current_vert += points.len() as u32;
+        self.extend_draw();
+    }
+
+    pub fn push_triangle(&mut self, points: [[f32; 2]; 3], color: &RGBColor) {
+        self.push_polygon(&points, color);
+    }
+
+    /// Fills a simple polygon, which may be concave, wound either way.
+    pub fn push_polygon(&mut self, points: &[[f32; 2]], color: &RGBColor) {
+        let indices = triangulate(points);
+        self.push_triangles(points, &indices, color);
+    }
+
+    pub fn push_line(&mut self, from: [f32; 2], to: [f32; 2], thickness: f32, color: &RGBColor) {
+        self.push_polyline(&[from, to], thickness, false, color);
+    }
+
+    /// A line through `points`. Closed polylines join the last point back to the first.
+    pub fn push_polyline(&mut self, points: &[[f32; 2]], thickness: f32, closed: bool, color: &RGBColor) {
+        let (vertices, indices) = polyline(points, thickness, closed);
+        self.push_triangles(&vertices, &indices, color);
+    }
+
+    /// Outline of a polygon, centred on its edges.
+    pub fn push_polygon_outline(&mut self, points: &[[f32; 2]], thickness: f32, color: &RGBColor) {
+        self.push_polyline(points, thickness, true, color);
+    }
+
+    pub fn push_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32, color: &RGBColor) {
+        self.push_polygon(&rect_points(x, y, width, height, radius, 8), color);
+    }
+
+    pub fn push_rect_outline(&mut self, x: f32, y: f32, width: f32, height: f32, thickness: f32, color: &RGBColor) {
+        self.push_polygon_outline(&rect_points(x, y, width, height, 0.0, 0), thickness, color);
+    }
+
+    pub fn push_circle_outline(&mut self, x: f32, y: f32, radius: f32, thickness: f32, color: &RGBColor, sides: u8) {
+        self.push_polygon_outline(&circle_points(x, y, radius, sides), thickness, color);
+    }
+
+    /// Uploads everything pushed this frame into the renderer's buffers and returns the
+    /// draws to issue.
+    pub(crate) fn upload(self, buffers: &mut FrameBuffers, device: &wgpu::Device, queue: &wgpu::Queue) -> (Vec<DrawCommand>, RenderStats) {
+        let grown = [
+            buffers.vertices.write(device, queue, &self.vertex_data),
+            buffers.indices.write(device, queue, &self.index_data),
+            buffers.sprite_vertices.write(device, queue, &self.sprite_vertices),
+            buffers.sprite_indices.write(device, queue, &self.sprite_indices),
+            buffers.material_vertices.write(device, queue, &self.material_vertices),
+            buffers.material_indices.write(device, queue, &self.material_indices),
+            buffers.light_vertices.write(device, queue, &self.light_vertices),
+            buffers.light_indices.write(device, queue, &self.light_indices),
+        ];
+        let stats = RenderStats {
+            draw_calls: self.commands.len() as u32,
+            shape_vertices: self.vertex_data.len() as u32,
+            shape_indices: self.index_data.len() as u32,
+            sprite_vertices: self.sprite_vertices.len() as u32,
+            sprite_indices: self.sprite_indices.len() as u32,
+            material_vertices: self.material_vertices.len() as u32,
+            material_indices: self.material_indices.len() as u32,
+            buffer_bytes: buffers.capacity(),
+            buffers_grown: grown.iter().filter(|grew| **grew).count() as u32,
+        };
+        (self.commands, stats)
+    }
+}
+
+/// Numbers from the last rendered frame, for spotting when a scene gets too heavy.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    pub shape_vertices: u32,
+    pub shape_indices: u32,
+    pub sprite_vertices: u32,
+    pub sprite_indices: u32,
+    pub material_vertices: u32,
+    pub material_indices: u32,
+    /// Combined size of the vertex and index buffers on the GPU
+    pub buffer_bytes: u64,
+    /// How many buffers had to be reallocated to fit this frame
+    pub buffers_grown: u32,
+}
+
+/// Vertex and index buffers for shapes, sprites, materials and lights, kept between frames.
+pub(crate) struct FrameBuffers {
+    pub(crate) vertices: GrowableBuffer,
+    pub(crate) indices: GrowableBuffer,
+    pub(crate) sprite_vertices: GrowableBuffer,
+    pub(crate) sprite_indices: GrowableBuffer,
+    pub(crate) material_vertices: GrowableBuffer,
+    pub(crate) material_indices: GrowableBuffer,
+    pub(crate) light_vertices: GrowableBuffer,
+    pub(crate) light_indices: GrowableBuffer,
+}
+
+impl FrameBuffers {
+    /// Starts with room for `quads` shapes, sprites and material quads.
+    pub(crate) fn new(device: &wgpu::Device, quads: u64) -> FrameBuffers {
+        let vertex = wgpu::BufferUsages::VERTEX;
+        let index = wgpu::BufferUsages::INDEX;
+        FrameBuffers {
+            vertices: GrowableBuffer::new(device, "Vertex Buffer", vertex, Vertex::SIZE * 4 * quads),
+            indices: GrowableBuffer::new(device, "Vertex Index Buffer", index, U32_SIZE * 6 * quads),
+            sprite_vertices: GrowableBuffer::new(device, "Sprite Vertex Buffer", vertex, SpriteVertex::SIZE * 4 * quads),
+            sprite_indices: GrowableBuffer::new(device, "Sprite Index Buffer", index, U32_SIZE * 6 * quads),
+            material_vertices: GrowableBuffer::new(device, "Material Vertex Buffer", vertex, MaterialVertex::SIZE * 4 * quads),
+            material_indices: GrowableBuffer::new(device, "Material Index Buffer", index, U32_SIZE * 6 * quads),
+            // Scenes have far fewer lights than quads
+            light_vertices: GrowableBuffer::new(device, "Light Vertex Buffer", vertex, LightVertex::SIZE * 64),
+            light_indices: GrowableBuffer::new(device, "Light Index Buffer", index, U32_SIZE * 192),
+        }
     }
 
-    pub fn build(self, device: &wgpu::Device) -> (StagingBuffer, StagingBuffer, u32) {
-        (
-            StagingBuffer::new(device, &self.vertex_data, false),
-            StagingBuffer::new(device, &self.index_data, true),
-            self.index_data.len() as u32,
-        )
+    fn capacity(&self) -> u64 {
+        self.vertices.capacity()
+            + self.indices.capacity()
+            + self.sprite_vertices.capacity()
+            + self.sprite_indices.capacity()
+            + self.material_vertices.capacity()
+            + self.material_indices.capacity()
+            + self.light_vertices.capacity()
+            + self.light_indices.capacity()
     }
 }
 
-pub struct StagingBuffer {
-    buffer: wgpu::Buffer,
-    size: wgpu::BufferAddress,
+/// A GPU buffer reused from frame to frame. It doubles in size whenever a frame needs more
+/// room and never shrinks.
+pub(crate) struct GrowableBuffer {
+    pub(crate) buffer: wgpu::Buffer,
+    capacity: wgpu::BufferAddress,
+    usage: wgpu::BufferUsages,
+    label: &'static str,
 }
 
-impl StagingBuffer {
-    pub fn new<T: bytemuck::Pod + Sized>(
+impl GrowableBuffer {
+    pub(crate) fn new(
         device: &wgpu::Device,
-        data: &[T],
-        is_index_buffer: bool,
-    ) -> StagingBuffer {
-        StagingBuffer {
-            buffer: device.create_buffer_init(&BufferInitDescriptor {
-                contents: bytemuck::cast_slice(data),
-                usage: wgpu::BufferUsages::COPY_SRC
-                    | if is_index_buffer {
-                        wgpu::BufferUsages::INDEX
-                    } else {
-                        wgpu::BufferUsages::empty()
-                    },
-                label: Some("Staging Buffer"),
+        label: &'static str,
+        usage: wgpu::BufferUsages,
+        capacity: wgpu::BufferAddress,
+    ) -> GrowableBuffer {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        GrowableBuffer {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: capacity,
+                usage,
+                mapped_at_creation: false,
             }),
-            size: size_of_slice(data) as wgpu::BufferAddress,
+            capacity,
+            usage,
+            label,
         }
     }
 
-    pub fn copy_to_buffer(&self, encoder: &mut wgpu::CommandEncoder, other: &wgpu::Buffer) {
-        encoder.copy_buffer_to_buffer(&self.buffer, 0, other, 0, self.size)
+    pub(crate) fn capacity(&self) -> wgpu::BufferAddress {
+        self.capacity
+    }
+
+    /// Uploads `data` to the start of the buffer. Returns true if the buffer had to grow.
+    pub(crate) fn write<T: bytemuck::Pod>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[T],
+    ) -> bool {
+        let size = size_of_slice(data) as wgpu::BufferAddress;
+        let grew = size > self.capacity;
+        if grew {
+            self.buffer.destroy();
+            self.capacity = size.next_power_of_two();
+            self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(self.label),
+                size: self.capacity,
+                usage: self.usage,
+                mapped_at_creation: false,
+            });
+        }
+        if size > 0 {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(data));
+        }
+        grew
     }
 }
@@ -76,10 +76,6 @@ impl Projection {
         }
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.aspect = width as f32 / height as f32;
-    }
-
     pub fn calc_matrix(&self) -> Matrix4<f32> {
         // OPENGL_TO_WGPU_MATRIX * perspective(self.fovy, self.aspect, self.znear, self.zfar)
         OPENGL_TO_WGPU_MATRIX
@@ -0,0 +1,262 @@
+use crate::light::{Light2D, MIN_FALLOFF};
+use crate::renderer::buffer::linear_rgba;
+use crate::renderer::texture::Texture;
+
+/// Lights are added up in half floats so bright overlapping lights don't clip before
+/// they're applied
+const LIGHT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightVertex {
+    pub(crate) position: [f32; 2],
+    /// Where the light is, which it fades out from
+    pub(crate) center: [f32; 2],
+    /// Linear RGB times the intensity
+    pub(crate) color: [f32; 4],
+    /// Radius and falloff
+    pub(crate) params: [f32; 2],
+}
+
+impl LightVertex {
+    pub(crate) const SIZE: wgpu::BufferAddress = std::mem::size_of::<Self>() as wgpu::BufferAddress;
+    const DESC: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: Self::SIZE,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32x4,
+            3 => Float32x2
+        ],
+    };
+}
+
+/// Triangles fanning out from the light's centre over `area`, from `Light2D::lit_area`.
+pub(crate) fn light_triangles(light: &Light2D, center: [f32; 2], area: &[[f32; 2]]) -> (Vec<LightVertex>, Vec<u32>) {
+    let [red, green, blue, _] = linear_rgba(&light.color);
+    let vertex = |position: [f32; 2]| LightVertex {
+        position,
+        center,
+        color: [red * light.intensity, green * light.intensity, blue * light.intensity, 1.0],
+        params: [light.radius, light.falloff.max(MIN_FALLOFF)],
+    };
+    let mut vertices = vec![vertex(center)];
+    vertices.extend(area.iter().copied().map(vertex));
+    let count = area.len() as u32;
+    let indices = (0..count)
+        .flat_map(|index| [0, index + 1, (index + 1) % count + 1])
+        .collect();
+    (vertices, indices)
+}
+
+/// Adds the window's lights up over the ambient colour in a frame-sized texture, then
+/// multiplies the world by it.
+pub(crate) struct LightRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) accumulate_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    /// Created on first use and when its size changes
+    frame: Option<(Texture, wgpu::BindGroup)>,
+}
+
+impl LightRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("light_bind_group_layout"),
+        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/light.wgsl"));
+        let composite_shader = device.create_shader_module(wgpu::include_wgsl!("shaders/light_composite.wgsl"));
+
+        let accumulate_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Light Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let accumulate_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Light Pipeline"),
+            layout: Some(&accumulate_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_light",
+                buffers: &[LightVertex::DESC],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_light",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: LIGHT_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            // Shadows can flip a fan's triangles, so neither side is culled
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let composite_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Light Composite Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Light Composite Pipeline"),
+            layout: Some(&composite_layout),
+            vertex: wgpu::VertexState {
+                module: &composite_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    // What's drawn is multiplied by the light, keeping its alpha
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Dst,
+                            dst_factor: wgpu::BlendFactor::Zero,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        LightRenderer {
+            bind_group_layout,
+            accumulate_pipeline,
+            composite_pipeline,
+            frame: None,
+        }
+    }
+
+    /// Makes sure the light texture is `width` by `height` pixels, the size of the frame
+    /// the world is drawn into.
+    pub(crate) fn prepare(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let resized = self.frame.as_ref().is_none_or(|(frame, _)| {
+            frame.texture.width() != width || frame.texture.height() != height
+        });
+        if !resized {
+            return;
+        }
+        if let Some((frame, _)) = &self.frame {
+            frame.texture.destroy();
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Light Frame"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: LIGHT_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("light_bind_group"),
+        });
+        self.frame = Some((
+            Texture {
+                texture,
+                view,
+                sampler,
+            },
+            bind_group,
+        ));
+    }
+
+    /// Where the lights are added up. Needs `prepare` first.
+    pub(crate) fn frame_view(&self) -> &wgpu::TextureView {
+        &self.frame.as_ref().unwrap().0.view
+    }
+
+    /// Multiplies everything drawn to `output` by the added up light.
+    pub(crate) fn composite(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        let (_, bind_group) = self.frame.as_ref().unwrap();
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Light Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
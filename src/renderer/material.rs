@@ -0,0 +1,317 @@
+use crate::game_object::graphics::BlendMode;
+use crate::renderer::texture::Texture;
+use hashbrown::HashMap;
+
+/// Most parameters a material can have, packed into 4 `vec4`s on the GPU.
+pub const MAX_MATERIAL_PARAMS: usize = 16;
+
+const COMMON_SHADER: &str = include_str!("shaders/material.wgsl");
+
+/// A custom shader for sprites, rects and circles, e.g. water, dissolves or hit flashes.
+/// Registered once with `Engine::add_material` and used by graphics through a
+/// `MaterialInstance`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// WGSL defining `fs_main(in: VertexOutput) -> @location(0) vec4<f32>`. It can sample
+    /// `t_main` (the sprite's atlas, white for shapes) at `in.tex_coords` and `t_extra` (the
+    /// material's texture), and read `in.uv`, `in.color`, `in.world_position`,
+    /// `material.time` and its parameters with `param(index)`.
+    pub shader: String,
+    /// Defaults of the named uniforms, `param(0)` being the first
+    pub params: Vec<(String, f32)>,
+    /// Texture or atlas bound to `t_extra`, e.g. dissolve noise
+    pub texture: Option<String>,
+}
+
+impl Material {
+    pub fn new(name: &str, wgsl: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            shader: wgsl.to_string(),
+            params: vec![],
+            texture: None,
+        }
+    }
+    /// Adds a parameter, or changes its default if it exists.
+    pub fn param(mut self, name: &str, value: f32) -> Material {
+        self.set_param(name, value);
+        self
+    }
+    pub fn texture(mut self, texture: &str) -> Material {
+        self.texture = Some(texture.to_string());
+        self
+    }
+    /// Adds a parameter, or changes its default if it exists. Panics past
+    /// `MAX_MATERIAL_PARAMS`.
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        match self.params.iter_mut().find(|(param, _)| param == name) {
+            Some((_, current)) => *current = value,
+            None => {
+                assert!(
+                    self.params.len() < MAX_MATERIAL_PARAMS,
+                    "material '{}' can't have more than {} parameters",
+                    self.name,
+                    MAX_MATERIAL_PARAMS
+                );
+                self.params.push((name.to_string(), value));
+            }
+        }
+    }
+    pub fn get_param(&self, name: &str) -> Option<f32> {
+        self.params.iter().find(|(param, _)| param == name).map(|(_, value)| *value)
+    }
+
+    /// Parameter values in slot order, with `overrides` replacing the defaults they name.
+    /// Names the material doesn't have are ignored.
+    pub fn param_values(&self, overrides: &[(String, f32)]) -> [f32; MAX_MATERIAL_PARAMS] {
+        let mut values = [0.0; MAX_MATERIAL_PARAMS];
+        for (value, (name, default)) in values.iter_mut().zip(&self.params) {
+            *value = overrides
+                .iter()
+                .find(|(param, _)| param == name)
+                .map_or(*default, |(_, value)| *value);
+        }
+        values
+    }
+}
+
+/// A graphic's use of a material, with its own values for some of the parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialInstance {
+    pub material: String,
+    pub params: Vec<(String, f32)>,
+}
+
+impl MaterialInstance {
+    pub fn new(material: &str) -> MaterialInstance {
+        MaterialInstance {
+            material: material.to_string(),
+            params: vec![],
+        }
+    }
+    pub fn param(mut self, name: &str, value: f32) -> MaterialInstance {
+        self.set_param(name, value);
+        self
+    }
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        match self.params.iter_mut().find(|(param, _)| param == name) {
+            Some((_, current)) => *current = value,
+            None => self.params.push((name.to_string(), value)),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialVertex {
+    pub(crate) position: [f32; 3],
+    pub(crate) tex_coords: [f32; 2],
+    /// From (0, 0) bottom-left to (1, 1) top-right of the graphic
+    pub(crate) uv: [f32; 2],
+    /// Linear RGBA
+    pub(crate) color: [f32; 4],
+}
+
+impl MaterialVertex {
+    pub(crate) const SIZE: wgpu::BufferAddress = std::mem::size_of::<Self>() as wgpu::BufferAddress;
+    const DESC: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: Self::SIZE,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x2,
+            2 => Float32x2,
+            3 => Float32x4
+        ],
+    };
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct MaterialUniform {
+    time: f32,
+    _padding: [f32; 3],
+    values: [f32; MAX_MATERIAL_PARAMS],
+}
+
+/// Pipelines and per-draw bindings for materials. Pipelines are cached by shader source,
+/// one per blend mode, so a material compiles once however many graphics use it.
+pub(crate) struct MaterialRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    pipelines: HashMap<String, Vec<wgpu::RenderPipeline>>,
+    /// One per material draw of the frame, reused between frames
+    uniforms: Vec<wgpu::Buffer>,
+    /// This frame's material draws, in the order they are drawn
+    pub(crate) draws: Vec<MaterialDraw>,
+}
+
+pub(crate) struct MaterialDraw {
+    pub(crate) bind_group: wgpu::BindGroup,
+    /// Picks the pipeline
+    pub(crate) shader: String,
+    /// The material's own texture, which can't be drawn into at the same time
+    pub(crate) texture: Option<String>,
+}
+
+impl MaterialRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let sampler_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                sampler_entry(2),
+                texture_entry(3),
+                sampler_entry(4),
+            ],
+            label: Some("material_bind_group_layout"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Material Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        MaterialRenderer {
+            bind_group_layout,
+            pipeline_layout,
+            format,
+            pipelines: HashMap::new(),
+            uniforms: vec![],
+            draws: vec![],
+        }
+    }
+
+    pub(crate) fn pipeline(&self, shader: &str, blend_mode: BlendMode) -> &wgpu::RenderPipeline {
+        &self.pipelines[shader][blend_mode as usize]
+    }
+
+    fn create_pipelines(&mut self, device: &wgpu::Device, material: &Material) {
+        if self.pipelines.contains_key(&material.shader) {
+            return;
+        }
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&material.name),
+            source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", COMMON_SHADER, material.shader).into()),
+        });
+        let pipelines = BlendMode::ALL
+            .iter()
+            .map(|blend_mode| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Material Pipeline"),
+                    layout: Some(&self.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "vs_main",
+                        buffers: &[MaterialVertex::DESC],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "fs_main",
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: self.format,
+                            blend: Some(blend_mode.blend_state()),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                })
+            })
+            .collect();
+        self.pipelines.insert(material.shader.clone(), pipelines);
+    }
+
+    /// Compiles new materials and writes the uniforms and bindings of each material draw,
+    /// in the order they are drawn. `main_textures` are what each draw binds to `t_main`.
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        draws: &[(&Material, [f32; MAX_MATERIAL_PARAMS], &Texture, &Texture)],
+        time: f32,
+    ) {
+        while self.uniforms.len() < draws.len() {
+            self.uniforms.push(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Material Uniform Buffer"),
+                size: std::mem::size_of::<MaterialUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+
+        self.draws.clear();
+        for (index, (material, values, main, extra)) in draws.iter().enumerate() {
+            self.create_pipelines(device, material);
+            let uniform = MaterialUniform {
+                time,
+                _padding: [0.0; 3],
+                values: *values,
+            };
+            queue.write_buffer(&self.uniforms[index], 0, bytemuck::cast_slice(&[uniform]));
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.uniforms[index].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&main.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&main.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&extra.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::Sampler(&extra.sampler),
+                    },
+                ],
+                label: Some("material_bind_group"),
+            });
+            self.draws.push(MaterialDraw {
+                bind_group,
+                shader: material.shader.clone(),
+                texture: material.texture.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use crate::renderer::texture::Texture;
+use hashbrown::HashMap;
+
+/// Most parameters a pass can have, packed into 4 `vec4`s on the GPU.
+pub const MAX_POST_PARAMS: usize = 16;
+
+const COMMON_SHADER: &str = include_str!("shaders/post/common.wgsl");
+
+/// What a post-processing pass does to the scene.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PostEffect {
+    Crt,
+    Vignette,
+    ColorGrade,
+    Bloom,
+    Pixelate,
+    /// WGSL defining `fs_main(in: VertexOutput) -> @location(0) vec4<f32>`. It can read the
+    /// previous pass with `textureSample(t_input, s_input, in.uv)`, its parameters with
+    /// `param(index)`, and `params.resolution` and `params.time`.
+    Custom(String),
+}
+
+impl PostEffect {
+    fn fragment_source(&self) -> &str {
+        match self {
+            PostEffect::Crt => include_str!("shaders/post/crt.wgsl"),
+            PostEffect::Vignette => include_str!("shaders/post/vignette.wgsl"),
+            PostEffect::ColorGrade => include_str!("shaders/post/color_grade.wgsl"),
+            PostEffect::Bloom => include_str!("shaders/post/bloom.wgsl"),
+            PostEffect::Pixelate => include_str!("shaders/post/pixelate.wgsl"),
+            PostEffect::Custom(source) => source,
+        }
+    }
+}
+
+/// One step of a scene's post-processing chain. Passes run in the order they were added to
+/// `Scene::post_passes`, each reading the one before, and only affect the world, not the UI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostPass {
+    /// Used to change the pass at runtime
+    pub name: String,
+    pub effect: PostEffect,
+    /// Named uniforms, `param(0)` being the first
+    pub params: Vec<(String, f32)>,
+    pub enabled: bool,
+}
+
+impl PostPass {
+    pub fn new(name: &str, effect: PostEffect) -> PostPass {
+        PostPass {
+            name: name.to_string(),
+            effect,
+            params: vec![],
+            enabled: true,
+        }
+    }
+    /// Scanlines and a curved screen.
+    pub fn crt() -> PostPass {
+        PostPass::new("crt", PostEffect::Crt)
+            .param("scanlines", 0.25)
+            .param("curvature", 0.08)
+    }
+    /// Darkens the edges of the screen.
+    pub fn vignette() -> PostPass {
+        PostPass::new("vignette", PostEffect::Vignette)
+            .param("intensity", 0.5)
+            .param("radius", 0.5)
+            .param("softness", 0.5)
+    }
+    pub fn color_grade() -> PostPass {
+        PostPass::new("color_grade", PostEffect::ColorGrade)
+            .param("brightness", 0.0)
+            .param("contrast", 1.0)
+            .param("saturation", 1.0)
+            .param("tint_red", 1.0)
+            .param("tint_green", 1.0)
+            .param("tint_blue", 1.0)
+    }
+    /// Makes colours brighter than `threshold` glow.
+    pub fn bloom() -> PostPass {
+        PostPass::new("bloom", PostEffect::Bloom)
+            .param("threshold", 0.7)
+            .param("intensity", 0.8)
+            .param("radius", 4.0)
+    }
+    pub fn pixelate() -> PostPass {
+        PostPass::new("pixelate", PostEffect::Pixelate).param("size", 4.0)
+    }
+    pub fn custom(name: &str, wgsl: &str) -> PostPass {
+        PostPass::new(name, PostEffect::Custom(wgsl.to_string()))
+    }
+
+    /// Sets a parameter, adding it after the others if it's new.
+    pub fn param(mut self, name: &str, value: f32) -> PostPass {
+        self.set_param(name, value);
+        self
+    }
+    pub fn enabled(mut self, enabled: bool) -> PostPass {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets a parameter, adding it after the others if it's new. Panics past
+    /// `MAX_POST_PARAMS`.
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        assert!(
+            self.try_set_param(name, value),
+            "post pass '{}' can't have more than {} parameters",
+            self.name,
+            MAX_POST_PARAMS
+        );
+    }
+    /// Sets a parameter like `set_param`, but returns false instead of adding one past
+    /// `MAX_POST_PARAMS`.
+    pub fn try_set_param(&mut self, name: &str, value: f32) -> bool {
+        let full = self.params.len() >= MAX_POST_PARAMS;
+        match self.params.iter_mut().find(|(param, _)| param == name) {
+            Some((_, current)) => *current = value,
+            None if full => return false,
+            None => self.params.push((name.to_string(), value)),
+        }
+        true
+    }
+    pub fn get_param(&self, name: &str) -> Option<f32> {
+        self.params.iter().find(|(param, _)| param == name).map(|(_, value)| *value)
+    }
+
+    fn uniform(&self, resolution: [f32; 2], time: f32) -> PostUniform {
+        let mut values = [0.0; MAX_POST_PARAMS];
+        for (value, (_, param)) in values.iter_mut().zip(&self.params) {
+            *value = *param;
+        }
+        PostUniform {
+            resolution,
+            time,
+            _padding: 0.0,
+            values,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniform {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+    values: [f32; MAX_POST_PARAMS],
+}
+
+/// Runs post passes between two textures the size of the window, the last one drawing to
+/// the frame. Pipelines are cached by shader source, so custom passes compile once.
+pub(crate) struct PostProcessor {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<String, wgpu::RenderPipeline>,
+    uniforms: Vec<wgpu::Buffer>,
+    /// Ping-pong targets, created on first use and when the window is resized
+    textures: Option<[Texture; 2]>,
+    format: wgpu::TextureFormat,
+}
+
+impl PostProcessor {
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("post_bind_group_layout"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        PostProcessor {
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            uniforms: vec![],
+            textures: None,
+            format,
+        }
+    }
+
+    /// Makes sure the ping-pong textures match the window.
+    pub(crate) fn prepare(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let resized = self
+            .textures
+            .as_ref()
+            .is_none_or(|[texture, _]| texture.texture.width() != width || texture.texture.height() != height);
+        if resized {
+            if let Some(textures) = &self.textures {
+                for texture in textures {
+                    texture.texture.destroy();
+                }
+            }
+            self.textures = Some([0, 1].map(|_| self.create_texture(device, width, height)));
+        }
+    }
+
+    /// Where the scene is drawn before the first pass. Needs `prepare` first.
+    pub(crate) fn scene_view(&self) -> &wgpu::TextureView {
+        &self.textures.as_ref().unwrap()[0].view
+    }
+
+    fn create_texture(&self, device: &wgpu::Device, width: u32, height: u32) -> Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Post Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        Texture {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    fn pipeline(&mut self, device: &wgpu::Device, effect: &PostEffect) -> &wgpu::RenderPipeline {
+        let fragment = effect.fragment_source();
+        if !self.pipelines.contains_key(fragment) {
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Post Shader"),
+                source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", COMMON_SHADER, fragment).into()),
+            });
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Post Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: self.format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+            self.pipelines.insert(fragment.to_string(), pipeline);
+        }
+        &self.pipelines[fragment]
+    }
+
+    /// Runs the enabled `passes` over the scene drawn into `scene_view`, the last one
+    /// drawing into `output`.
+    pub(crate) fn run(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        passes: &[&PostPass],
+        output: &wgpu::TextureView,
+        time: f32,
+    ) {
+        while self.uniforms.len() < passes.len() {
+            self.uniforms.push(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Post Uniform Buffer"),
+                size: std::mem::size_of::<PostUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+
+        for (index, pass) in passes.iter().enumerate() {
+            let textures = self.textures.as_ref().unwrap();
+            let input = &textures[index % 2];
+            let resolution = [input.texture.width() as f32, input.texture.height() as f32];
+            queue.write_buffer(
+                &self.uniforms[index],
+                0,
+                bytemuck::cast_slice(&[pass.uniform(resolution, time)]),
+            );
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&input.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&input.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.uniforms[index].as_entire_binding(),
+                    },
+                ],
+                label: Some("post_bind_group"),
+            });
+
+            self.pipeline(device, &pass.effect);
+            let textures = self.textures.as_ref().unwrap();
+            let target = if index + 1 == passes.len() {
+                output
+            } else {
+                &textures[(index + 1) % 2].view
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&self.pipelines[pass.effect.fragment_source()]);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Adds each light's fan up in the light frame, over the ambient colour it was cleared to.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct LightInput {
+    @location(0) position: vec2<f32>,
+    @location(1) center: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    // Radius and falloff
+    @location(3) params: vec2<f32>,
+};
+
+struct LightOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+    @location(1) center: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) params: vec2<f32>,
+};
+
+@vertex
+fn vs_light(in: LightInput) -> LightOutput {
+    var out: LightOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(in.position, 0.0, 1.0);
+    out.position = in.position;
+    out.center = in.center;
+    out.color = in.color;
+    out.params = in.params;
+    return out;
+}
+
+@fragment
+fn fs_light(in: LightOutput) -> @location(0) vec4<f32> {
+    let reach = clamp(1.0 - distance(in.position, in.center) / in.params.x, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb * pow(reach, in.params.y), 0.0);
+}
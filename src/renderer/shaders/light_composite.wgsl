@@ -0,0 +1,26 @@
+// Multiplies what the world drew by the light frame.
+
+@group(0) @binding(0)
+var t_light: texture_2d<f32>;
+@group(0) @binding(1)
+var s_light: sampler;
+
+struct CompositeOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the whole frame
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> CompositeOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: CompositeOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: CompositeOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(t_light, s_light, in.uv).rgb, 1.0);
+}
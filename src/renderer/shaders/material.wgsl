@@ -0,0 +1,63 @@
+// Shared by every material. A material only provides `fs_main`, reading its texture from
+// `t_main`, an extra texture from `t_extra` and its parameters with `param(index)`.
+
+struct MaterialParams {
+    time: f32,
+    _padding0: f32,
+    _padding1: f32,
+    _padding2: f32,
+    values: array<vec4<f32>, 4>,
+};
+
+@group(0) @binding(0)
+var<uniform> material: MaterialParams;
+// The sprite's atlas, or plain white for shapes
+@group(0) @binding(1)
+var t_main: texture_2d<f32>;
+@group(0) @binding(2)
+var s_main: sampler;
+// The material's own texture, or plain white
+@group(0) @binding(3)
+var t_extra: texture_2d<f32>;
+@group(0) @binding(4)
+var s_extra: sampler;
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Into `t_main`
+    @location(0) tex_coords: vec2<f32>,
+    // Across the graphic, from (0, 0) bottom-left to (1, 1) top-right
+    @location(1) uv: vec2<f32>,
+    // Tint for sprites, fill colour for shapes
+    @location(2) color: vec4<f32>,
+    @location(3) world_position: vec2<f32>,
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.uv = model.uv;
+    out.color = model.color;
+    out.world_position = model.position.xy;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+fn param(index: u32) -> f32 {
+    return material.values[index / 4u][index % 4u];
+}
@@ -0,0 +1,21 @@
+// 0: brightness threshold, 1: intensity, 2: radius in pixels
+
+fn bright(uv: vec2<f32>) -> vec3<f32> {
+    let color = textureSample(t_input, s_input, uv).rgb;
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return color * step(param(0u), luma);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    let texel = param(2u) / params.resolution;
+    // Two rings of samples around the pixel
+    var glow = vec3<f32>(0.0);
+    for (var i = 0; i < 8; i = i + 1) {
+        let angle = f32(i) * 0.785398;
+        let direction = vec2<f32>(cos(angle), sin(angle)) * texel;
+        glow = glow + bright(in.uv + direction) * 0.6 + bright(in.uv + direction * 2.0) * 0.4;
+    }
+    return vec4<f32>(color.rgb + glow / 8.0 * param(1u), color.a);
+}
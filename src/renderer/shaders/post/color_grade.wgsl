@@ -0,0 +1,11 @@
+// 0: brightness, 1: contrast, 2: saturation, 3-5: tint
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    var rgb = color.rgb + param(0u);
+    rgb = (rgb - 0.5) * param(1u) + 0.5;
+    let luma = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    rgb = mix(vec3<f32>(luma), rgb, param(2u));
+    rgb = rgb * vec3<f32>(param(3u), param(4u), param(5u));
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
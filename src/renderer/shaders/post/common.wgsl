@@ -0,0 +1,35 @@
+// Shared by every post-processing pass. A pass only provides `fs_main`, reading the
+// previous pass's output from `t_input` and its parameters with `param(index)`.
+
+struct PostParams {
+    resolution: vec2<f32>,
+    time: f32,
+    _padding: f32,
+    values: array<vec4<f32>, 4>,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+@group(0) @binding(1)
+var s_input: sampler;
+@group(0) @binding(2)
+var<uniform> params: PostParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn param(index: u32) -> f32 {
+    return params.values[index / 4u][index % 4u];
+}
@@ -0,0 +1,12 @@
+// 0: scanline strength, 1: curvature
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let centered = in.uv * 2.0 - 1.0;
+    let bent = centered * (1.0 + param(1u) * dot(centered.yx, centered.yx));
+    let uv = bent * 0.5 + 0.5;
+    let color = textureSample(t_input, s_input, clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)));
+    let scanline = 1.0 - param(0u) * (0.5 + 0.5 * cos(uv.y * params.resolution.y * 3.14159));
+    // Black outside the bent screen
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), vec4<f32>(color.rgb * scanline, color.a), inside);
+}
@@ -0,0 +1,8 @@
+// 0: size of the pixels in screen pixels
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = max(param(0u), 1.0);
+    let pixel = (floor(in.uv * params.resolution / size) + 0.5) * size;
+    let coords = clamp(vec2<i32>(pixel), vec2<i32>(0), vec2<i32>(params.resolution) - 1);
+    return textureLoad(t_input, coords, 0);
+}
@@ -0,0 +1,8 @@
+// 0: intensity, 1: radius where darkening starts, 2: softness
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, in.uv);
+    let distance = length(in.uv - 0.5) * 1.41421;
+    let shade = smoothstep(param(1u), param(1u) + max(param(2u), 0.001), distance);
+    return vec4<f32>(color.rgb * (1.0 - shade * param(0u)), color.a);
+}
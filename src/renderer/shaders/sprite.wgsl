@@ -3,18 +3,22 @@
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) flash: f32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) tex_coords: vec2<f32>
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) flash: f32,
 }
 
 struct CameraUniform {
     view_proj: mat4x4<f32>,
 };
 
-@group(0) @binding(2)
+@group(1) @binding(0)
 var<uniform> camera: CameraUniform;
 
 @vertex
@@ -23,6 +27,8 @@ fn vs_main(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
+    out.color = model.color;
+    out.flash = model.flash;
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
     return out;
 }
@@ -38,5 +44,15 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let texel = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let flashed = mix(texel.rgb, vec3<f32>(1.0, 1.0, 1.0), in.flash);
+    return vec4<f32>(flashed * in.color.rgb, texel.a * in.color.a);
+}
+
+// For textures whose colours are already multiplied by their alpha
+@fragment
+fn fs_premultiplied(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let flashed = mix(texel.rgb, vec3<f32>(texel.a, texel.a, texel.a), in.flash);
+    return vec4<f32>(flashed * in.color.rgb * in.color.a, texel.a * in.color.a);
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+// Geometry helpers for building shapes out of triangles.
+
+/// Corners are pushed out at most this many half-thicknesses at sharp polyline joins.
+const MITER_LIMIT: f32 = 4.0;
+
+fn cross(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+/// Twice the signed area, positive when the points wind counter-clockwise.
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum()
+}
+
+fn in_triangle(p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+/// Splits a simple polygon, convex or concave and in either winding, into triangles using
+/// ear clipping. Returns counter-clockwise indices into `points`.
+pub fn triangulate(points: &[[f32; 2]]) -> Vec<u32> {
+    if points.len() < 3 {
+        return vec![];
+    }
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let (prev, current, next) = (
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            );
+            let (a, b, c) = (points[prev], points[current], points[next]);
+            cross(a, b, c) > 0.0
+                && !remaining
+                    .iter()
+                    .filter(|&&other| other != prev && other != current && other != next)
+                    .any(|&other| in_triangle(points[other], a, b, c))
+        });
+        match ear {
+            Some(i) => {
+                indices.extend([
+                    remaining[(i + count - 1) % count] as u32,
+                    remaining[i] as u32,
+                    remaining[(i + 1) % count] as u32,
+                ]);
+                remaining.remove(i);
+            }
+            // Self-intersecting or degenerate, fan out what is left rather than looping forever
+            None => break,
+        }
+    }
+    for i in 1..remaining.len() - 1 {
+        indices.extend([remaining[0] as u32, remaining[i] as u32, remaining[i + 1] as u32]);
+    }
+    indices
+}
+
+/// Vertices and counter-clockwise indices of a line `thickness` wide through `points`, with
+/// mitred joins.
+pub(crate) fn polyline(points: &[[f32; 2]], thickness: f32, closed: bool) -> (Vec<[f32; 2]>, Vec<u32>) {
+    let count = points.len();
+    if count < 2 {
+        return (vec![], vec![]);
+    }
+    let half = thickness * 0.5;
+    let normal = |a: [f32; 2], b: [f32; 2]| {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        [-dy / length, dx / length]
+    };
+
+    let mut vertices = Vec::with_capacity(count * 2);
+    for i in 0..count {
+        let previous = if i > 0 {
+            Some(points[i - 1])
+        } else if closed {
+            Some(points[count - 1])
+        } else {
+            None
+        };
+        let next = if i + 1 < count {
+            Some(points[i + 1])
+        } else if closed {
+            Some(points[0])
+        } else {
+            None
+        };
+        let offset = match (previous, next) {
+            (Some(previous), Some(next)) => {
+                let (a, b) = (normal(previous, points[i]), normal(points[i], next));
+                let miter = [a[0] + b[0], a[1] + b[1]];
+                let length = (miter[0] * miter[0] + miter[1] * miter[1]).sqrt();
+                if length < f32::EPSILON {
+                    // The line doubles back on itself
+                    [b[0] * half, b[1] * half]
+                } else {
+                    let miter = [miter[0] / length, miter[1] / length];
+                    let scale = (half / (miter[0] * b[0] + miter[1] * b[1])).min(half * MITER_LIMIT);
+                    [miter[0] * scale, miter[1] * scale]
+                }
+            }
+            (None, Some(next)) => {
+                let n = normal(points[i], next);
+                [n[0] * half, n[1] * half]
+            }
+            (Some(previous), None) => {
+                let n = normal(previous, points[i]);
+                [n[0] * half, n[1] * half]
+            }
+            (None, None) => unreachable!(),
+        };
+        let point = points[i];
+        vertices.push([point[0] + offset[0], point[1] + offset[1]]);
+        vertices.push([point[0] - offset[0], point[1] - offset[1]]);
+    }
+
+    let segments = if closed { count } else { count - 1 };
+    let mut indices = Vec::with_capacity(segments * 6);
+    for i in 0..segments {
+        let (left, right) = (i as u32 * 2, i as u32 * 2 + 1);
+        let j = ((i + 1) % count) as u32;
+        let (next_left, next_right) = (j * 2, j * 2 + 1);
+        indices.extend([right, next_right, next_left, right, next_left, left]);
+    }
+    (vertices, indices)
+}
+
+/// Points around a circle, counter-clockwise from the right.
+pub(crate) fn circle_points(x: f32, y: f32, radius: f32, sides: u8) -> Vec<[f32; 2]> {
+    (0..sides)
+        .map(|i| {
+            let angle = i as f32 / sides as f32 * std::f32::consts::TAU;
+            [x + radius * angle.cos(), y + radius * angle.sin()]
+        })
+        .collect()
+}
+
+/// Corners of a rectangle centred on (x, y), counter-clockwise from the bottom-left. Each
+/// corner is rounded with `segments` extra points when `radius` is above zero.
+pub(crate) fn rect_points(x: f32, y: f32, width: f32, height: f32, radius: f32, segments: u8) -> Vec<[f32; 2]> {
+    let (half_width, half_height) = (width * 0.5, height * 0.5);
+    let radius = radius.clamp(0.0, half_width.min(half_height));
+    if radius <= 0.0 {
+        return vec![
+            [x - half_width, y - half_height],
+            [x + half_width, y - half_height],
+            [x + half_width, y + half_height],
+            [x - half_width, y + half_height],
+        ];
+    }
+    let centres = [
+        [x - half_width + radius, y - half_height + radius],
+        [x + half_width - radius, y - half_height + radius],
+        [x + half_width - radius, y + half_height - radius],
+        [x - half_width + radius, y + half_height - radius],
+    ];
+    let mut points = Vec::with_capacity(4 * (segments as usize + 1));
+    for (corner, centre) in centres.iter().enumerate() {
+        // The bottom-left corner sweeps from pointing left to pointing down, and so on
+        let start = std::f32::consts::PI + corner as f32 * std::f32::consts::FRAC_PI_2;
+        for step in 0..=segments {
+            let angle = start + step as f32 / segments.max(1) as f32 * std::f32::consts::FRAC_PI_2;
+            points.push([centre[0] + radius * angle.cos(), centre[1] + radius * angle.sin()]);
+        }
+    }
+    points
+}
+
+/// Rotates `points` by `rotation` radians around (x, y) in place.
+pub(crate) fn rotate_points(points: &mut [[f32; 2]], x: f32, y: f32, rotation: f32) {
+    if rotation == 0.0 {
+        return;
+    }
+    let (sin, cos) = rotation.sin_cos();
+    for point in points {
+        let (dx, dy) = (point[0] - x, point[1] - y);
+        *point = [x + dx * cos - dy * sin, y + dx * sin + dy * cos];
+    }
+}
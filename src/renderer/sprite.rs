@@ -1,17 +1,24 @@
 use std::num::NonZeroU32;
 use cgmath::Rotation3;
 use wgpu::{BindGroupLayout, Device, RenderPipeline};
+use crate::game_object::graphics::BlendMode;
 
 pub(crate) mod texture;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct SpriteVertex {
-    pub(crate) position: [f32; 3],
-    pub(crate) tex_coords: [f32; 2],
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    /// Linear RGBA multiplied with the texture
+    pub color: [f32; 4],
+    /// How far the texture is pushed towards white
+    pub flash: f32,
 }
 
 impl SpriteVertex {
+    pub(crate) const SIZE: wgpu::BufferAddress = std::mem::size_of::<Self>() as wgpu::BufferAddress;
+
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         use std::mem;
         wgpu::VertexBufferLayout {
@@ -28,13 +35,24 @@ impl SpriteVertex {
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
 }
 
-
-pub(crate) fn create_sprite_render_pipeline(device : &Device,config: &wgpu::SurfaceConfiguration,texture_count: u32) -> (RenderPipeline,BindGroupLayout) {
+/// Creates one pipeline per blend mode, in the order of `BlendMode::ALL`. The atlas is bound
+/// to group 0 and the camera, shared with shapes, to group 1.
+pub(crate) fn create_sprite_render_pipelines(device : &Device,config: &wgpu::SurfaceConfiguration,texture_count: u32,camera_bind_group_layout: &BindGroupLayout) -> (Vec<RenderPipeline>,BindGroupLayout) {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Sprite Shader"),
         source: wgpu::ShaderSource::Wgsl(include_str!("../renderer/shaders/sprite.wgsl").into()),
@@ -59,16 +77,6 @@ pub(crate) fn create_sprite_render_pipeline(device : &Device,config: &wgpu::Surf
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }
             ],
             label: Some("texture_bind_group_layout"),
         });
@@ -76,11 +84,11 @@ pub(crate) fn create_sprite_render_pipeline(device : &Device,config: &wgpu::Surf
     let render_pipeline_layout =
         device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Sprite Render Pipeline Layout"),
-            bind_group_layouts: &[&texture_bind_group_layout],
+            bind_group_layouts: &[&texture_bind_group_layout, camera_bind_group_layout],
             push_constant_ranges: &[],
         });
 
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    let render_pipelines = BlendMode::ALL.iter().map(|blend_mode| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Sprite Render Pipeline"),
         layout: Some(&render_pipeline_layout),
         vertex: wgpu::VertexState {
@@ -90,13 +98,10 @@ pub(crate) fn create_sprite_render_pipeline(device : &Device,config: &wgpu::Surf
         },
         fragment: Some(wgpu::FragmentState {
             module: &shader,
-            entry_point: "fs_main",
+            entry_point: blend_mode.fragment_entry_point(),
             targets: &[Some(wgpu::ColorTargetState {
                 format: config.format,
-                blend: Some(wgpu::BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
+                blend: Some(blend_mode.blend_state()),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
@@ -122,7 +127,7 @@ pub(crate) fn create_sprite_render_pipeline(device : &Device,config: &wgpu::Surf
         // If the pipeline will be used with a multiview render pass, this
         // indicates how many array layers the attachments will have.
         multiview: None,
-    });
+    })).collect();
 
-    return (render_pipeline,texture_bind_group_layout)
+    return (render_pipelines,texture_bind_group_layout)
 }
\ No newline at end of file
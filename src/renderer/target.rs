@@ -0,0 +1,144 @@
+use crate::renderer::texture::Texture;
+use crate::ui::frontend::{HyperFoilAST, RGBColor};
+use crate::ui::parse_ui_blob;
+
+/// An offscreen texture that cameras and UI draw into instead of the window. Once added with
+/// `Engine::add_render_target`, sprites show it with `SpriteData::texture(name, ..)`, e.g. for
+/// minimaps, portals or screens inside the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderTarget {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub clear_color: RGBColor,
+    /// Drawn over whatever cameras draw into the target, laid out as if the target were
+    /// the window
+    pub ui: Option<HyperFoilAST>,
+}
+
+impl RenderTarget {
+    pub fn new(name: &str, width: u32, height: u32) -> RenderTarget {
+        RenderTarget {
+            name: name.to_string(),
+            width: width.max(1),
+            height: height.max(1),
+            clear_color: RGBColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+                alpha: 0,
+            },
+            ui: None,
+        }
+    }
+    pub fn clear_color(mut self, clear_color: RGBColor) -> RenderTarget {
+        self.clear_color = clear_color;
+        self
+    }
+    pub fn ui(mut self, blob: &str) -> RenderTarget {
+        self.ui = Some(parse_ui_blob(blob));
+        self
+    }
+    pub(crate) fn size(&self) -> [f32; 2] {
+        [self.width as f32, self.height as f32]
+    }
+}
+
+/// GPU side of a render target, recreated when its size changes.
+pub(crate) struct GpuRenderTarget {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) texture: Texture,
+}
+
+impl GpuRenderTarget {
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat, target: &RenderTarget) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&target.name),
+            size: wgpu::Extent3d {
+                width: target.width,
+                height: target.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        GpuRenderTarget {
+            width: target.width,
+            height: target.height,
+            texture: Texture {
+                texture,
+                view,
+                sampler,
+            },
+        }
+    }
+}
+
+/// Copies `texture` back from the GPU. Rows are padded to wgpu's copy alignment on the way.
+pub(crate) fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> image::RgbaImage {
+    let (width, height) = (texture.width(), texture.height());
+    let unpadded_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row = unpadded_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: (padded_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+
+    let mut pixels = Vec::with_capacity((unpadded_row * height) as usize);
+    for row in slice.get_mapped_range().chunks(padded_row as usize) {
+        pixels.extend_from_slice(&row[..unpadded_row as usize]);
+    }
+    buffer.unmap();
+
+    if matches!(
+        texture.format(),
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    ) {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    image::RgbaImage::from_raw(width, height, pixels).unwrap()
+}
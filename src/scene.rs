@@ -1,14 +1,19 @@
+use crate::camera::{Camera2D, UiMode};
 use crate::game_object::behaviours::EngineView;
+use crate::game_object::graphics::{GraphicsType, RenderLayer};
 use crate::game_object::physics::PhysicsData;
+use crate::game_object::tilemap::TilemapData;
 use crate::game_object::{GameObject, GameObjectBuilder};
-use crate::ui::frontend::HyperFoilAST;
+use crate::renderer::post::PostPass;
+use crate::ui::frontend::{HyperFoilAST, RGBColor};
 use crate::ui::parse_ui_blob;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use rapier2d::geometry::{ColliderHandle, ColliderSet};
 use rapier2d::prelude::{
     BroadPhase, CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager,
     MultibodyJointSet, NarrowPhase, RigidBodyHandle, RigidBodySet,
 };
+use std::cell::Cell;
 
 #[derive(Clone)]
 pub struct Scene {
@@ -25,9 +30,27 @@ pub struct Scene {
     pub ui_ast: Option<HyperFoilAST>,
     pub function_map: HashMap<String, fn(&mut EngineView)>,
     pub data_map: HashMap<String, String>,
+    /// The first camera is the main one, used by the single-camera helpers. There's always
+    /// at least one.
+    pub(crate) cameras: Vec<Camera2D>,
+    pub ui_mode: UiMode,
+    /// Applied in order to what the cameras draw to the window
+    pub post_passes: Vec<PostPass>,
+    /// Colour the window's world is lit with where no light reaches. `None` turns lighting
+    /// off, drawing everything as it is.
+    pub ambient_light: Option<RGBColor>,
     pub(crate) current_game_object_id: u128,
 }
 impl Scene {
+    /// Indices of the objects to draw back to front, skipping `hidden` layers. The sort is
+    /// stable so ties keep the order objects were added.
+    pub(crate) fn draw_order(&self, hidden: &HashSet<RenderLayer>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.game_objects.len())
+            .filter(|&index| !hidden.contains(&self.game_objects[index].layer))
+            .collect();
+        order.sort_by_key(|&index| (self.game_objects[index].layer, self.game_objects[index].z_index));
+        order
+    }
     pub fn register_game_object(&mut self, game_object_builder: GameObjectBuilder) -> &GameObject {
         let mut collider_handle: Option<ColliderHandle> = None;
         let mut rigid_body_handle: Option<RigidBodyHandle> = None;
@@ -59,29 +82,110 @@ impl Scene {
             }
         }
 
-        let (event_tx, event_rx) = kanal::bounded(60);
+        let (event_tx, event_rx) = kanal::unbounded();
+
+        let mut behaviours = game_object_builder.behaviours;
+        for behaviour in &mut behaviours {
+            behaviour
+                .scope
+                .push_constant("properties", game_object_builder.properties.clone());
+        }
 
         let game_object = GameObject {
             graphics: game_object_builder.graphics,
-            behaviours: game_object_builder.behaviours,
+            behaviours,
+            user_behaviours: game_object_builder.user_behaviours,
             pos_x: game_object_builder.pos_x,
             pos_y: game_object_builder.pos_y,
+            rotation: game_object_builder.rotation,
+            scale_x: game_object_builder.scale_x,
+            scale_y: game_object_builder.scale_y,
+            layer: game_object_builder.layer,
+            z_index: game_object_builder.z_index,
             physics: PhysicsData {
                 collider_handle: collider_handle,
                 rigid_body_handle: rigid_body_handle,
             },
+            light: game_object_builder.light,
             id: self.current_game_object_id,
+            subscriptions: game_object_builder.subscriptions,
             event_tx,
             event_rx,
+            missing_graphics_logged: Cell::new(false),
         };
 
         self.game_objects.push(game_object);
+        self.update_tilemap_colliders();
         let just_inserted = self.game_objects.get(self.current_game_object_id as usize);
 
         self.current_game_object_id += 1;
 
         just_inserted.unwrap()
     }
+    /// Tilemap drawn by an object, e.g. to read or change its tiles.
+    pub fn tilemap_mut(&mut self, id: u128) -> Option<&mut TilemapData> {
+        let object = self.game_objects.iter_mut().find(|object| object.id == id)?;
+        match &mut object.graphics {
+            Some(GraphicsType::Tilemap(tilemap)) => Some(tilemap),
+            _ => None,
+        }
+    }
+    /// Rebuilds the colliders of tilemaps whose collision changed. They're placed where
+    /// the map's object is at the time.
+    pub(crate) fn update_tilemap_colliders(&mut self) {
+        for object in &mut self.game_objects {
+            let Some(GraphicsType::Tilemap(tilemap)) = &mut object.graphics else {
+                continue;
+            };
+            if !tilemap.colliders_dirty {
+                continue;
+            }
+            for handle in tilemap.colliders.drain(..) {
+                self.collider_set.remove(handle, &mut self.island_manager, &mut self.rigid_body_set, false);
+            }
+            let colliders = tilemap.to_rapier([object.pos_x, object.pos_y], [object.scale_x, object.scale_y], object.id);
+            tilemap.colliders = colliders
+                .into_iter()
+                .map(|collider| self.collider_set.insert(collider))
+                .collect();
+            tilemap.colliders_dirty = false;
+        }
+    }
+    pub fn camera(&self) -> &Camera2D {
+        &self.cameras[0]
+    }
+    pub fn camera_mut(&mut self) -> &mut Camera2D {
+        &mut self.cameras[0]
+    }
+    pub fn cameras(&self) -> &[Camera2D] {
+        &self.cameras
+    }
+    pub fn camera_at(&self, index: usize) -> Option<&Camera2D> {
+        self.cameras.get(index)
+    }
+    pub fn camera_at_mut(&mut self, index: usize) -> Option<&mut Camera2D> {
+        self.cameras.get_mut(index)
+    }
+    /// Adds another camera, e.g. for split-screen or a minimap, and returns its index.
+    pub fn add_camera(&mut self, camera: Camera2D) -> usize {
+        self.cameras.push(camera);
+        self.cameras.len() - 1
+    }
+    /// Removes a camera, moving the ones after it down an index. The last camera can't be
+    /// removed, so this returns `None` for it as well as for indices past the end.
+    pub fn remove_camera(&mut self, index: usize) -> Option<Camera2D> {
+        (self.cameras.len() > 1 && index < self.cameras.len()).then(|| self.cameras.remove(index))
+    }
+    pub fn add_post_pass(&mut self, pass: PostPass) {
+        self.post_passes.push(pass);
+    }
+    pub fn post_pass_mut(&mut self, name: &str) -> Option<&mut PostPass> {
+        self.post_passes.iter_mut().find(|pass| pass.name == name)
+    }
+    /// Turns lighting on with `color` where no light reaches, or off with `None`.
+    pub fn set_ambient_light(&mut self, color: Option<RGBColor>) {
+        self.ambient_light = color;
+    }
     pub fn register_ui(&mut self, blob: &str) {
         let ui_ast = parse_ui_blob(blob);
         self.ui_ast = Some(ui_ast)
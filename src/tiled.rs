@@ -0,0 +1,882 @@
+use crate::game_object::graphics::{GraphicsType, SpriteData};
+use crate::game_object::tilemap::{Tile, TileCollision, TilemapData};
+use crate::game_object::GameObjectBuilder;
+use crate::physics::{screen_units_to_physics_units, AlcubierreCollider, AlcubierreColliderType};
+use crate::renderer::atlas::SpriteAtlas;
+use crate::scene::Scene;
+use hashbrown::HashMap;
+use log::warn;
+use rapier2d::prelude::{vector, RigidBodyBuilder};
+use rhai::Dynamic;
+use serde_json::{Map as JsonMap, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod xml;
+
+use xml::{parse_xml, XmlElement};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+/// Tiles can only be mirrored, so tiles flipped this way, which includes Tiled's 90°
+/// rotations, are drawn with just their other flips
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// Flips, the diagonal flip and hexagonal rotation share the top bits of a gid
+const GID_FLAGS: u32 = 0xF000_0000;
+
+/// Why a Tiled map couldn't be loaded.
+#[derive(Debug)]
+pub enum TiledError {
+    /// A map or tileset file couldn't be read
+    Io(PathBuf, std::io::Error),
+    /// A map or tileset isn't valid JSON or XML
+    Parse(String),
+    /// The map uses something the importer doesn't handle
+    Unsupported(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TiledError::Io(path, e) => write!(f, "Unable to read {}: {}", path.display(), e),
+            TiledError::Parse(message) => write!(f, "Invalid Tiled file: {}", message),
+            TiledError::Unsupported(message) => write!(f, "Unsupported Tiled map: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {}
+
+/// A custom property from Tiled. Colours, files and objects are kept as strings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl TiledProperty {
+    pub fn to_dynamic(&self) -> Dynamic {
+        match self {
+            TiledProperty::Bool(value) => Dynamic::from(*value),
+            TiledProperty::Int(value) => Dynamic::from(*value),
+            TiledProperty::Float(value) => Dynamic::from(*value),
+            TiledProperty::String(value) => Dynamic::from(value.clone()),
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TiledProperty::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            TiledProperty::Int(value) => Some(*value as f32),
+            TiledProperty::Float(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+}
+
+pub type TiledProperties = HashMap<String, TiledProperty>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// Points in pixels relative to the object's position
+    Polygon(Vec<[f32; 2]>),
+    Polyline(Vec<[f32; 2]>),
+}
+
+/// An object from an object layer, in Tiled's pixels with y pointing down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    /// Called "type" before Tiled 1.9
+    pub class: String,
+    /// Top-left corner, or bottom-left for tile objects
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Degrees clockwise
+    pub rotation: f32,
+    /// Tile shown by a tile object, with its flip flags
+    pub gid: Option<u32>,
+    pub visible: bool,
+    pub shape: TiledShape,
+    pub properties: TiledProperties,
+}
+
+/// A tile of a tileset with a class, properties or collision shapes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TiledTile {
+    pub class: String,
+    pub properties: TiledProperties,
+    /// Shapes drawn in Tiled's collision editor
+    pub collision: Vec<TiledObject>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    /// Also the name of the atlas made from it
+    pub name: String,
+    /// Image the tiles are cut from. Collections of separate images aren't supported.
+    pub image: Option<String>,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// By tile index
+    pub tiles: HashMap<u32, TiledTile>,
+}
+
+impl TiledTileset {
+    /// Atlas holding every tile, named by its index.
+    pub fn atlas(&self) -> Option<SpriteAtlas> {
+        let image = self.image.as_ref()?;
+        Some(SpriteAtlas::from_grid(
+            image,
+            self.tile_width as u64,
+            self.tile_height as u64,
+            self.columns as u64,
+            self.tile_count as u64,
+            self.margin as u64,
+            self.spacing as u64,
+        ))
+    }
+    /// How a tile collides: its "collision" property, "solid", "one_way" or "sensor", or
+    /// solid when it has collision shapes.
+    pub fn collision(&self, index: u32) -> Option<TileCollision> {
+        let tile = self.tiles.get(&index)?;
+        match tile.properties.get("collision").and_then(TiledProperty::as_str) {
+            Some("solid") => Some(TileCollision::Solid),
+            Some("one_way") => Some(TileCollision::OneWay),
+            Some("sensor") => Some(TileCollision::Sensor),
+            Some(_) => None,
+            None => (!tile.collision.is_empty()).then_some(TileCollision::Solid),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledTileLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Offset in pixels, including any group layers it's in
+    pub offset: [f32; 2],
+    pub visible: bool,
+    /// Gids row by row from the top-left, 0 for no tile
+    pub data: Vec<u32>,
+    pub properties: TiledProperties,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledObjectLayer {
+    pub name: String,
+    pub offset: [f32; 2],
+    pub visible: bool,
+    pub objects: Vec<TiledObject>,
+    pub properties: TiledProperties,
+}
+
+/// Layers in drawing order. Group layers are flattened into their children.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledLayer {
+    Tiles(TiledTileLayer),
+    Objects(TiledObjectLayer),
+}
+
+/// An orthogonal map made in the Tiled editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMap {
+    /// In tiles
+    pub width: u32,
+    pub height: u32,
+    /// In pixels
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+    pub properties: TiledProperties,
+    /// Folder the map was loaded from, for scripts named by relative paths
+    dir: Option<PathBuf>,
+}
+
+/// Builds the object spawned for a Tiled object of a class.
+pub type TiledPrefab = fn(&TiledObject) -> GameObjectBuilder;
+
+/// Places a map in the world and says what to spawn for its objects.
+pub struct TiledImport {
+    /// Tiled pixels in a world unit
+    pub pixels_per_unit: f32,
+    /// World position of the map's top-left corner
+    pub origin: [f32; 2],
+    prefabs: HashMap<String, TiledPrefab>,
+}
+
+impl TiledImport {
+    pub fn new(pixels_per_unit: f32) -> TiledImport {
+        TiledImport {
+            pixels_per_unit,
+            origin: [0.0, 0.0],
+            prefabs: HashMap::new(),
+        }
+    }
+    pub fn origin(mut self, x: f32, y: f32) -> TiledImport {
+        self.origin = [x, y];
+        self
+    }
+    /// Builds objects of `class` with `prefab` instead of from their shape. The map still
+    /// places them and passes in their properties.
+    pub fn prefab(mut self, class: &str, prefab: TiledPrefab) -> TiledImport {
+        self.prefabs.insert(class.to_string(), prefab);
+        self
+    }
+    fn to_world(&self, pixels: [f32; 2]) -> [f32; 2] {
+        [
+            self.origin[0] + pixels[0] / self.pixels_per_unit,
+            self.origin[1] - pixels[1] / self.pixels_per_unit,
+        ]
+    }
+}
+
+impl TiledMap {
+    /// Loads a `.tmx` or `.tmj` map along with any external `.tsx` or `.tsj` tilesets it uses.
+    pub fn load(path: &str) -> Result<TiledMap, TiledError> {
+        let contents = fs::read_to_string(path).map_err(|e| TiledError::Io(PathBuf::from(path), e))?;
+        let dir = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+        let value = match path.ends_with(".tmx") {
+            true => tmx_to_json(&parse_tmx(&contents)?),
+            false => serde_json::from_str(&contents).map_err(|e| TiledError::Parse(format!("{}: {}", path, e)))?,
+        };
+        TiledMap::from_json(&value, Some(dir))
+    }
+    /// Parses a map in Tiled's JSON format. Tilesets must be embedded in the map.
+    pub fn from_tmj(contents: &str) -> Result<TiledMap, TiledError> {
+        let value: Value = serde_json::from_str(contents).map_err(|e| TiledError::Parse(e.to_string()))?;
+        TiledMap::from_json(&value, None)
+    }
+    /// Parses a map in Tiled's XML format. Tilesets must be embedded in the map.
+    pub fn from_tmx(contents: &str) -> Result<TiledMap, TiledError> {
+        TiledMap::from_json(&tmx_to_json(&parse_tmx(contents)?), None)
+    }
+
+    /// An atlas for each tileset with an image, named after the tileset.
+    pub fn atlases(&self) -> Vec<(String, SpriteAtlas)> {
+        self.tilesets
+            .iter()
+            .filter_map(|tileset| Some((tileset.name.clone(), tileset.atlas()?)))
+            .collect()
+    }
+    /// Tileset a gid comes from, and the tile's index in it.
+    pub fn tileset_for(&self, gid: u32) -> Option<(&TiledTileset, u32)> {
+        let gid = gid & !GID_FLAGS;
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.first_gid <= gid && gid > 0)
+            .max_by_key(|tileset| tileset.first_gid)
+            .map(|tileset| (tileset, gid - tileset.first_gid))
+    }
+
+    /// Objects for every visible layer, back to front. Each tile layer becomes a tilemap
+    /// for each tileset it uses, and each object is built by its class's prefab or from its
+    /// shape: tile objects get a sprite, other shapes a fixed collider. A "script" property
+    /// adds a behaviour, and every object's properties are in its scripts' `properties`.
+    pub fn game_objects(&self, import: &TiledImport) -> Vec<GameObjectBuilder> {
+        let mut builders = vec![];
+        for (z_index, layer) in self.layers.iter().enumerate() {
+            match layer {
+                TiledLayer::Tiles(layer) if layer.visible => {
+                    for tilemap in self.tilemaps(layer, import) {
+                        let mut builder = GameObjectBuilder::new()
+                            .graphics(GraphicsType::Tilemap(tilemap))
+                            .z_index(z_index as i32);
+                        [builder.pos_x, builder.pos_y] = import.to_world(layer.offset);
+                        builders.push(with_properties(builder, &layer.properties));
+                    }
+                }
+                TiledLayer::Objects(layer) if layer.visible => {
+                    for object in layer.objects.iter().filter(|object| object.visible) {
+                        builders.push(self.object(object, layer.offset, import).z_index(z_index as i32));
+                    }
+                }
+                _ => {}
+            }
+        }
+        builders
+    }
+    /// Registers the map's objects with `scene`, returning their ids. The tilesets' atlases
+    /// need adding to the engine too, e.g. with `Engine::add_tiled_tilesets`.
+    pub fn spawn(&self, scene: &mut Scene, import: &TiledImport) -> Vec<u128> {
+        self.game_objects(import)
+            .into_iter()
+            .map(|builder| scene.register_game_object(builder).id())
+            .collect()
+    }
+
+    fn tilemaps(&self, layer: &TiledTileLayer, import: &TiledImport) -> Vec<TilemapData> {
+        let tile_size = [self.tile_width, self.tile_height].map(|size| size as f32 / import.pixels_per_unit);
+        let mut tilemaps: Vec<(u32, TilemapData)> = vec![];
+        if layer.data.iter().any(|gid| gid & FLIPPED_DIAGONALLY != 0) {
+            warn!("Tiles in layer {} are rotated or flipped diagonally, which is drawn as just their other flips", layer.name);
+        }
+        for (index, &gid) in layer.data.iter().enumerate() {
+            let Some((tileset, tile_index)) = self.tileset_for(gid) else {
+                continue;
+            };
+            let position = tilemaps.iter().position(|(first_gid, _)| *first_gid == tileset.first_gid);
+            let tilemap = match position {
+                Some(position) => &mut tilemaps[position].1,
+                None => {
+                    let mut tilemap = TilemapData::new(layer.width, layer.height, tile_size[0], tile_size[1]).atlas(&tileset.name);
+                    for &tile_index in tileset.tiles.keys() {
+                        if let Some(collision) = tileset.collision(tile_index) {
+                            tilemap = tilemap.collision(&tile_index.to_string(), collision);
+                        }
+                    }
+                    tilemaps.push((tileset.first_gid, tilemap));
+                    &mut tilemaps.last_mut().unwrap().1
+                }
+            };
+            let mut tile = Tile::new(&tile_index.to_string());
+            tile.flip_h = gid & FLIPPED_HORIZONTALLY != 0;
+            tile.flip_v = gid & FLIPPED_VERTICALLY != 0;
+            let (x, y) = (index as u32 % layer.width, index as u32 / layer.width);
+            tilemap.set_tile(x, y, tile);
+        }
+        tilemaps.into_iter().map(|(_, tilemap)| tilemap).collect()
+    }
+
+    fn object(&self, object: &TiledObject, offset: [f32; 2], import: &TiledImport) -> GameObjectBuilder {
+        let size = [object.width, object.height].map(|size| size / import.pixels_per_unit);
+        let anchor = import.to_world([offset[0] + object.x, offset[1] + object.y]);
+        let rotation = -object.rotation.to_radians();
+        // Tiled rotates around the anchor, the engine around the object's position
+        let centre = match (&object.shape, object.gid) {
+            (_, Some(_)) => [size[0] * 0.5, size[1] * 0.5],
+            (TiledShape::Rectangle | TiledShape::Ellipse, None) => [size[0] * 0.5, -size[1] * 0.5],
+            _ => [0.0, 0.0],
+        };
+        let (sin, cos) = rotation.sin_cos();
+        let position = [
+            anchor[0] + centre[0] * cos - centre[1] * sin,
+            anchor[1] + centre[0] * sin + centre[1] * cos,
+        ];
+
+        let tile = object.gid.and_then(|gid| self.tileset_for(gid));
+        let mut properties = tile
+            .and_then(|(tileset, index)| tileset.tiles.get(&index))
+            .map(|tile| tile.properties.clone())
+            .unwrap_or_default();
+        properties.extend(object.properties.clone());
+
+        let mut builder = match import.prefabs.get(&object.class) {
+            Some(prefab) => prefab(object),
+            None => {
+                let mut builder = GameObjectBuilder::new();
+                match (tile, object.gid) {
+                    (Some((tileset, index)), Some(gid)) => {
+                        let mut sprite = SpriteData::new(&index.to_string(), size[0], size[1])
+                            .atlas(&tileset.name)
+                            .pivot(0.5, 0.5);
+                        sprite.flip_h = gid & FLIPPED_HORIZONTALLY != 0;
+                        sprite.flip_v = gid & FLIPPED_VERTICALLY != 0;
+                        builder = builder.graphics(GraphicsType::Sprite(sprite));
+                    }
+                    _ => {
+                        if let Some(collider) = collider(object, size, &properties, import) {
+                            builder = builder.collider(collider).rigid_body(RigidBodyBuilder::fixed().build());
+                        }
+                    }
+                }
+                if let Some(script) = properties.get("script").and_then(TiledProperty::as_str) {
+                    let path = match &self.dir {
+                        Some(dir) => dir.join(script).to_string_lossy().into_owned(),
+                        None => script.to_string(),
+                    };
+                    builder = builder.behaviour(&path);
+                }
+                builder
+            }
+        };
+        [builder.pos_x, builder.pos_y] = position;
+        builder.rotation = rotation;
+        if let Some(rigid_body) = &mut builder.rigid_body {
+            rigid_body.set_translation(
+                vector![
+                    screen_units_to_physics_units(position[0]),
+                    screen_units_to_physics_units(position[1])
+                ],
+                false,
+            );
+            rigid_body.set_rotation(rapier2d::math::Rotation::new(rotation), false);
+        }
+        with_properties(builder, &properties)
+    }
+
+    fn from_json(value: &Value, dir: Option<PathBuf>) -> Result<TiledMap, TiledError> {
+        if boolean(value, "infinite") == Some(true) {
+            return Err(TiledError::Unsupported("infinite maps can't be imported".to_string()));
+        }
+        if let Some(orientation) = string(value, "orientation").filter(|orientation| *orientation != "orthogonal") {
+            return Err(TiledError::Unsupported(format!("only orthogonal maps can be imported, not {}", orientation)));
+        }
+        let tilesets = array(value, "tilesets")
+            .iter()
+            .map(|tileset| parse_tileset(tileset, dir.as_deref()))
+            .collect::<Result<_, _>>()?;
+        let mut layers = vec![];
+        parse_layers(array(value, "layers"), [0.0, 0.0], &mut layers)?;
+        Ok(TiledMap {
+            width: unsigned(value, "width"),
+            height: unsigned(value, "height"),
+            tile_width: unsigned(value, "tilewidth"),
+            tile_height: unsigned(value, "tileheight"),
+            tilesets,
+            layers,
+            properties: parse_properties(value),
+            dir,
+        })
+    }
+}
+
+fn with_properties(mut builder: GameObjectBuilder, properties: &TiledProperties) -> GameObjectBuilder {
+    for (name, property) in properties {
+        builder = builder.property(name, property.to_dynamic());
+    }
+    builder
+}
+
+/// Collider for a shape object, in world units relative to its centre.
+fn collider(object: &TiledObject, size: [f32; 2], properties: &TiledProperties, import: &TiledImport) -> Option<AlcubierreCollider> {
+    let points = |points: &[[f32; 2]]| -> Vec<[f32; 2]> {
+        points
+            .iter()
+            .map(|[x, y]| [x / import.pixels_per_unit, -y / import.pixels_per_unit])
+            .collect()
+    };
+    let collider_type = match &object.shape {
+        TiledShape::Rectangle if size[0] > 0.0 && size[1] > 0.0 => AlcubierreColliderType::Rectangle((size[0], size[1])),
+        TiledShape::Ellipse => {
+            if size[0] != size[1] {
+                warn!("Ellipse {} collides as a circle", object.id);
+            }
+            AlcubierreColliderType::Circle(size[0].max(size[1]) * 0.5)
+        }
+        TiledShape::Polygon(polygon) => AlcubierreColliderType::Polygon(points(polygon)),
+        TiledShape::Polyline(polyline) => AlcubierreColliderType::Polyline(points(polyline)),
+        _ => return None,
+    };
+    Some(AlcubierreCollider {
+        collider_type,
+        sensor: matches!(properties.get("sensor"), Some(TiledProperty::Bool(true))),
+        restitution: properties.get("restitution").and_then(TiledProperty::as_f32).unwrap_or(0.0),
+        friction: properties.get("friction").and_then(TiledProperty::as_f32).unwrap_or(0.5),
+    })
+}
+
+fn parse_layers(layers: &[Value], offset: [f32; 2], parsed: &mut Vec<TiledLayer>) -> Result<(), TiledError> {
+    for layer in layers {
+        let name = string(layer, "name").unwrap_or_default().to_string();
+        let offset = [
+            offset[0] + number(layer, "offsetx").unwrap_or(0.0) as f32,
+            offset[1] + number(layer, "offsety").unwrap_or(0.0) as f32,
+        ];
+        let visible = boolean(layer, "visible").unwrap_or(true);
+        match string(layer, "type") {
+            Some("tilelayer") => parsed.push(TiledLayer::Tiles(TiledTileLayer {
+                name,
+                width: unsigned(layer, "width"),
+                height: unsigned(layer, "height"),
+                offset,
+                visible,
+                data: parse_tile_data(layer)?,
+                properties: parse_properties(layer),
+            })),
+            Some("objectgroup") => parsed.push(TiledLayer::Objects(TiledObjectLayer {
+                name,
+                offset,
+                visible,
+                objects: array(layer, "objects").iter().map(parse_object).collect(),
+                properties: parse_properties(layer),
+            })),
+            Some("group") => {
+                let start = parsed.len();
+                parse_layers(array(layer, "layers"), offset, parsed)?;
+                if !visible {
+                    for child in &mut parsed[start..] {
+                        match child {
+                            TiledLayer::Tiles(child) => child.visible = false,
+                            TiledLayer::Objects(child) => child.visible = false,
+                        }
+                    }
+                }
+            }
+            other => warn!("Skipping Tiled layer {} of type {:?}", name, other),
+        }
+    }
+    Ok(())
+}
+
+fn parse_tile_data(layer: &Value) -> Result<Vec<u32>, TiledError> {
+    let name = string(layer, "name").unwrap_or_default();
+    if layer.get("chunks").is_some() {
+        return Err(TiledError::Unsupported(format!("layer {} is from an infinite map", name)));
+    }
+    if let Some(compression) = string(layer, "compression").filter(|compression| !compression.is_empty()) {
+        return Err(TiledError::Unsupported(format!(
+            "layer {} is compressed with {}, save it as CSV or uncompressed base64",
+            name, compression
+        )));
+    }
+    let data = match layer.get("data") {
+        Some(Value::Array(gids)) => gids.iter().map(|gid| gid.as_u64().unwrap_or(0) as u32).collect(),
+        Some(Value::String(data)) => match string(layer, "encoding") {
+            Some("base64") => decode_base64(data)
+                .chunks_exact(4)
+                .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect(),
+            _ => data
+                .split(',')
+                .map(str::trim)
+                .filter(|gid| !gid.is_empty())
+                .map(|gid| gid.parse().map_err(|_| TiledError::Parse(format!("tile {} in layer {} isn't a gid", gid, name))))
+                .collect::<Result<_, _>>()?,
+        },
+        _ => vec![],
+    };
+    Ok(data)
+}
+
+fn parse_tileset(tileset: &Value, dir: Option<&Path>) -> Result<TiledTileset, TiledError> {
+    let first_gid = unsigned(tileset, "firstgid");
+    // External tilesets live in their own file, with paths relative to it
+    let (external, dir) = match string(tileset, "source") {
+        Some(source) => {
+            let Some(dir) = dir else {
+                return Err(TiledError::Unsupported(format!(
+                    "tileset {} is external, load the map with TiledMap::load",
+                    source
+                )));
+            };
+            let path = dir.join(source);
+            let contents = fs::read_to_string(&path).map_err(|e| TiledError::Io(path.clone(), e))?;
+            let value = match source.ends_with(".tsx") {
+                true => tileset_to_json(&parse_tmx(&contents)?),
+                false => serde_json::from_str(&contents)
+                    .map_err(|e| TiledError::Parse(format!("{}: {}", path.display(), e)))?,
+            };
+            (Some(value), path.parent().map(Path::to_path_buf))
+        }
+        None => (None, dir.map(Path::to_path_buf)),
+    };
+    let tileset = external.as_ref().unwrap_or(tileset);
+    let image = string(tileset, "image").map(|image| match &dir {
+        Some(dir) => dir.join(image).to_string_lossy().into_owned(),
+        None => image.to_string(),
+    });
+    if image.is_none() {
+        warn!("Tileset {} has no single image and won't be drawn", string(tileset, "name").unwrap_or_default());
+    }
+    let tiles = array(tileset, "tiles")
+        .iter()
+        .map(|tile| {
+            let collision = tile
+                .get("objectgroup")
+                .map(|group| array(group, "objects").iter().map(parse_object).collect())
+                .unwrap_or_default();
+            let class = string(tile, "class").or(string(tile, "type")).unwrap_or_default();
+            (
+                unsigned(tile, "id"),
+                TiledTile {
+                    class: class.to_string(),
+                    properties: parse_properties(tile),
+                    collision,
+                },
+            )
+        })
+        .collect();
+    Ok(TiledTileset {
+        first_gid,
+        name: string(tileset, "name").unwrap_or_default().to_string(),
+        image,
+        tile_width: unsigned(tileset, "tilewidth"),
+        tile_height: unsigned(tileset, "tileheight"),
+        columns: unsigned(tileset, "columns"),
+        tile_count: unsigned(tileset, "tilecount"),
+        margin: unsigned(tileset, "margin"),
+        spacing: unsigned(tileset, "spacing"),
+        tiles,
+    })
+}
+
+fn parse_object(object: &Value) -> TiledObject {
+    let points = |key: &str| -> Option<Vec<[f32; 2]>> {
+        match object.get(key)? {
+            Value::Array(points) => Some(
+                points
+                    .iter()
+                    .map(|point| [number(point, "x").unwrap_or(0.0) as f32, number(point, "y").unwrap_or(0.0) as f32])
+                    .collect(),
+            ),
+            // "x,y x,y" in XML
+            Value::String(points) => Some(
+                points
+                    .split_whitespace()
+                    .filter_map(|point| point.split_once(','))
+                    .map(|(x, y)| [x.parse().unwrap_or(0.0), y.parse().unwrap_or(0.0)])
+                    .collect(),
+            ),
+            _ => None,
+        }
+    };
+    let shape = if boolean(object, "ellipse") == Some(true) {
+        TiledShape::Ellipse
+    } else if boolean(object, "point") == Some(true) {
+        TiledShape::Point
+    } else if let Some(polygon) = points("polygon") {
+        TiledShape::Polygon(polygon)
+    } else if let Some(polyline) = points("polyline") {
+        TiledShape::Polyline(polyline)
+    } else {
+        TiledShape::Rectangle
+    };
+    if object.get("template").is_some() {
+        warn!("Tiled object templates are not supported");
+    }
+    TiledObject {
+        id: unsigned(object, "id"),
+        name: string(object, "name").unwrap_or_default().to_string(),
+        class: string(object, "class").or(string(object, "type")).unwrap_or_default().to_string(),
+        x: number(object, "x").unwrap_or(0.0) as f32,
+        y: number(object, "y").unwrap_or(0.0) as f32,
+        width: number(object, "width").unwrap_or(0.0) as f32,
+        height: number(object, "height").unwrap_or(0.0) as f32,
+        rotation: number(object, "rotation").unwrap_or(0.0) as f32,
+        gid: number(object, "gid").map(|gid| gid as u32).filter(|&gid| gid & !GID_FLAGS != 0),
+        visible: boolean(object, "visible").unwrap_or(true),
+        shape,
+        properties: parse_properties(object),
+    }
+}
+
+fn parse_properties(value: &Value) -> TiledProperties {
+    array(value, "properties")
+        .iter()
+        .filter_map(|property| {
+            let name = string(property, "name")?.to_string();
+            let parsed = match string(property, "type").unwrap_or("string") {
+                "bool" => TiledProperty::Bool(boolean(property, "value")?),
+                "int" => TiledProperty::Int(number(property, "value")? as i64),
+                "float" => TiledProperty::Float(number(property, "value")?),
+                _ => match property.get("value")? {
+                    Value::String(value) => TiledProperty::String(value.clone()),
+                    value => TiledProperty::String(value.to_string()),
+                },
+            };
+            Some((name, parsed))
+        })
+        .collect()
+}
+
+// Tiled's XML holds the same things as its JSON, so it's turned into the JSON layout and
+// read from there. Numbers stay strings, which the readers below accept.
+
+fn parse_tmx(contents: &str) -> Result<XmlElement, TiledError> {
+    parse_xml(contents).map_err(TiledError::Parse)
+}
+
+fn tmx_to_json(map: &XmlElement) -> Value {
+    let mut json = attributes_json(map);
+    json.insert("layers".to_string(), layers_json(map));
+    json.insert(
+        "tilesets".to_string(),
+        map.children.iter().filter(|child| child.name == "tileset").map(tileset_to_json).collect(),
+    );
+    json.insert("properties".to_string(), properties_json(map));
+    Value::Object(json)
+}
+
+fn attributes_json(element: &XmlElement) -> JsonMap<String, Value> {
+    element
+        .attributes
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect()
+}
+
+fn layers_json(parent: &XmlElement) -> Value {
+    parent
+        .children
+        .iter()
+        .filter_map(|layer| {
+            let mut json = attributes_json(layer);
+            let layer_type = match layer.name.as_str() {
+                "layer" => {
+                    if let Some(data) = layer.child("data") {
+                        json.extend(attributes_json(data));
+                        if data.child("chunk").is_some() {
+                            json.insert("chunks".to_string(), Value::Bool(true));
+                        }
+                        let data = match data.child("tile") {
+                            // The old format with one element per tile
+                            Some(_) => data
+                                .children
+                                .iter()
+                                .map(|tile| Value::from(tile.attribute("gid").and_then(|gid| gid.parse::<u32>().ok()).unwrap_or(0)))
+                                .collect(),
+                            None => Value::String(data.text.trim().to_string()),
+                        };
+                        json.insert("data".to_string(), data);
+                    }
+                    "tilelayer"
+                }
+                "objectgroup" => {
+                    json.insert("objects".to_string(), objects_json(layer));
+                    "objectgroup"
+                }
+                "group" => {
+                    json.insert("layers".to_string(), layers_json(layer));
+                    "group"
+                }
+                "imagelayer" => "imagelayer",
+                _ => return None,
+            };
+            json.insert("type".to_string(), Value::from(layer_type));
+            json.insert("properties".to_string(), properties_json(layer));
+            Some(Value::Object(json))
+        })
+        .collect()
+}
+
+fn objects_json(group: &XmlElement) -> Value {
+    group
+        .children
+        .iter()
+        .filter(|child| child.name == "object")
+        .map(|object| {
+            let mut json = attributes_json(object);
+            for shape in ["ellipse", "point"] {
+                if object.child(shape).is_some() {
+                    json.insert(shape.to_string(), Value::Bool(true));
+                }
+            }
+            for shape in ["polygon", "polyline"] {
+                if let Some(points) = object.child(shape).and_then(|shape| shape.attribute("points")) {
+                    json.insert(shape.to_string(), Value::from(points));
+                }
+            }
+            json.insert("properties".to_string(), properties_json(object));
+            Value::Object(json)
+        })
+        .collect()
+}
+
+fn tileset_to_json(tileset: &XmlElement) -> Value {
+    let mut json = attributes_json(tileset);
+    if let Some(image) = tileset.child("image") {
+        for (key, name) in [("source", "image"), ("width", "imagewidth"), ("height", "imageheight")] {
+            if let Some(value) = image.attribute(key) {
+                json.insert(name.to_string(), Value::from(value));
+            }
+        }
+    }
+    let tiles = tileset
+        .children
+        .iter()
+        .filter(|child| child.name == "tile")
+        .map(|tile| {
+            let mut json = attributes_json(tile);
+            json.insert("properties".to_string(), properties_json(tile));
+            if let Some(group) = tile.child("objectgroup") {
+                let mut objects = JsonMap::new();
+                objects.insert("objects".to_string(), objects_json(group));
+                json.insert("objectgroup".to_string(), Value::Object(objects));
+            }
+            Value::Object(json)
+        })
+        .collect();
+    json.insert("tiles".to_string(), tiles);
+    Value::Object(json)
+}
+
+fn properties_json(element: &XmlElement) -> Value {
+    let Some(properties) = element.child("properties") else {
+        return Value::Array(vec![]);
+    };
+    properties
+        .children
+        .iter()
+        .filter(|property| property.name == "property")
+        .map(|property| {
+            let mut json = attributes_json(property);
+            // Multi-line strings are the element's text instead
+            if property.attribute("value").is_none() {
+                json.insert("value".to_string(), Value::from(property.text.clone()));
+            }
+            Value::Object(json)
+        })
+        .collect()
+}
+
+pub(crate) fn number(value: &Value, key: &str) -> Option<f64> {
+    match value.get(key)? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(number) => number.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn unsigned(value: &Value, key: &str) -> u32 {
+    number(value, key).unwrap_or(0.0) as u32
+}
+
+pub(crate) fn string<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key)?.as_str()
+}
+
+pub(crate) fn boolean(value: &Value, key: &str) -> Option<bool> {
+    match value.get(key)? {
+        Value::Bool(value) => Some(*value),
+        Value::Number(value) => Some(value.as_f64() != Some(0.0)),
+        Value::String(value) => Some(value == "1" || value == "true"),
+        _ => None,
+    }
+}
+
+pub(crate) fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+fn decode_base64(text: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    let (mut buffer, mut bits) = (0u32, 0);
+    for character in text.bytes() {
+        let sextet = match character {
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => continue,
+        };
+        buffer = (buffer << 6 | sextet as u32) & 0xFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    bytes
+}
@@ -0,0 +1,154 @@
+use nom::branch::alt;
+use nom::bytes::complete::{is_not, tag, take_until, take_while1};
+use nom::character::complete::{char, multispace0};
+use nom::combinator::{map, opt, value};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
+use nom::IResult;
+
+/// Just enough XML for Tiled's files: elements, attributes and text. Doctypes and
+/// namespaces aren't understood.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct XmlElement {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<XmlElement>,
+    pub(crate) text: String,
+}
+
+impl XmlElement {
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+#[derive(Clone)]
+enum Content {
+    Element(XmlElement),
+    Text(String),
+    Skipped,
+}
+
+/// Parses a document into its root element.
+pub(crate) fn parse_xml(input: &str) -> Result<XmlElement, String> {
+    let (rest, _) = many0(alt((whitespace, prolog, comment)))(input).map_err(|e| e.to_string())?;
+    let (_, root) = element(rest).map_err(|e| format!("invalid XML: {}", e))?;
+    Ok(root)
+}
+
+fn whitespace(input: &str) -> IResult<&str, ()> {
+    value((), take_while1(char::is_whitespace))(input)
+}
+
+fn prolog(input: &str) -> IResult<&str, ()> {
+    value((), tuple((tag("<?"), take_until("?>"), tag("?>"))))(input)
+}
+
+fn comment(input: &str) -> IResult<&str, ()> {
+    value((), tuple((tag("<!--"), take_until("-->"), tag("-->"))))(input)
+}
+
+fn name(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.'))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, (String, String)> {
+    let quoted = alt((
+        delimited(char('"'), opt(is_not("\"")), char('"')),
+        delimited(char('\''), opt(is_not("'")), char('\'')),
+    ));
+    map(
+        preceded(
+            multispace0,
+            separated_pair(name, delimited(multispace0, char('='), multispace0), quoted),
+        ),
+        |(key, value)| (key.to_string(), unescape(value.unwrap_or(""))),
+    )(input)
+}
+
+fn element(input: &str) -> IResult<&str, XmlElement> {
+    let (input, (element_name, attributes)) = preceded(char('<'), pair(name, many0(attribute)))(input)?;
+    let (input, _) = multispace0(input)?;
+    let mut element = XmlElement {
+        name: element_name.to_string(),
+        attributes,
+        children: vec![],
+        text: String::new(),
+    };
+    if let Ok((input, _)) = tag::<_, _, nom::error::Error<&str>>("/>")(input) {
+        return Ok((input, element));
+    }
+    let (input, _) = char('>')(input)?;
+    let (input, contents) = many0(content)(input)?;
+    let (input, _) = delimited(
+        tag("</"),
+        tag(element_name),
+        terminated(multispace0, char('>')),
+    )(input)?;
+    for content in contents {
+        match content {
+            Content::Element(child) => element.children.push(child),
+            Content::Text(text) => element.text.push_str(&text),
+            Content::Skipped => {}
+        }
+    }
+    Ok((input, element))
+}
+
+fn content(input: &str) -> IResult<&str, Content> {
+    alt((
+        value(Content::Skipped, comment),
+        map(cdata, |text| Content::Text(text.to_string())),
+        map(element, Content::Element),
+        map(is_not("<"), |text: &str| Content::Text(unescape(text))),
+    ))(input)
+}
+
+fn cdata(input: &str) -> IResult<&str, &str> {
+    delimited(tag("<![CDATA["), take_until("]]>"), tag("]]>"))(input)
+}
+
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()).and_then(char::from_u32),
+            },
+        };
+        match character {
+            Some(character) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
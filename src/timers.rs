@@ -0,0 +1,159 @@
+use crate::game_object::behaviours::EngineView;
+use crate::game_object::events::{GameEvent, UserEvent};
+use crate::game_object::GameObjectIPC;
+use crate::Engine;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+static NEXT_TIMER_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Game time settings. Timers, coroutines and anything else driven by game time
+/// use the scaled delta, so pausing or slowing the game also slows them.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    pub time_scale: f32,
+    pub paused: bool,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl Clock {
+    pub fn scaled_delta(&self, delta: &Duration) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            delta.as_secs_f32() * self.time_scale
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle(pub(crate) u64);
+
+impl TimerHandle {
+    pub(crate) fn next() -> Self {
+        TimerHandle(NEXT_TIMER_HANDLE.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Clone)]
+pub enum TimerAction {
+    /// Sends the event to the owning GameObject.
+    Event(UserEvent),
+    /// Calls the named function on the owning GameObject's Rhai behaviours.
+    ScriptCallback(String),
+    Callback(Rc<dyn Fn(&mut EngineView)>),
+}
+
+impl TimerAction {
+    pub fn event<E: GameEvent>(event: E) -> Self {
+        TimerAction::Event(UserEvent::new(event))
+    }
+    pub fn callback<F>(callback: F) -> Self
+    where
+        F: Fn(&mut EngineView) + 'static,
+    {
+        TimerAction::Callback(Rc::new(callback))
+    }
+}
+
+pub(crate) struct Timer {
+    pub(crate) handle: TimerHandle,
+    pub(crate) owner: u128,
+    pub(crate) interval: f32,
+    pub(crate) remaining: f32,
+    pub(crate) repeating: bool,
+    pub(crate) action: TimerAction,
+}
+
+impl Timer {
+    pub(crate) fn new(owner: u128, seconds: f32, repeating: bool, action: TimerAction) -> Self {
+        Timer {
+            handle: TimerHandle::next(),
+            owner,
+            interval: seconds,
+            remaining: seconds,
+            repeating,
+            action,
+        }
+    }
+}
+
+impl Engine {
+    pub(crate) fn update_timers(&mut self) {
+        let delta = self.clock.scaled_delta(&self.last_delta);
+        if delta <= 0.0 {
+            return;
+        }
+
+        let mut fired = vec![];
+        self.timers.retain_mut(|timer| {
+            timer.remaining -= delta;
+            if timer.remaining > 0.0 {
+                return true;
+            }
+            fired.push((timer.owner, timer.action.clone()));
+            if timer.repeating && timer.interval > 0.0 {
+                // Keep the overshoot so repeating timers don't drift
+                while timer.remaining <= 0.0 {
+                    timer.remaining += timer.interval;
+                }
+                true
+            } else {
+                false
+            }
+        });
+
+        for (owner, action) in fired {
+            self.run_action(owner, action);
+        }
+    }
+    /// Runs a timer or completion action on behalf of `owner`.
+    pub(crate) fn run_action(&mut self, owner: u128, action: TimerAction) {
+        let scene = match self.active_scene.as_mut() {
+            Some(scene) => scene,
+            None => return,
+        };
+
+        match action {
+            TimerAction::Event(event) => {
+                if let Some(object) = scene.game_objects.iter().find(|o| o.id == owner) {
+                    object.notify_internal(event.with_sender(owner).with_target(owner));
+                }
+            }
+            TimerAction::ScriptCallback(function) => {
+                if let Some(object) = scene.game_objects.iter().find(|o| o.id == owner) {
+                    object
+                        .event_tx
+                        .send(GameObjectIPC::ScriptCall(function))
+                        .unwrap();
+                }
+            }
+            TimerAction::Callback(callback) => callback(&mut EngineView {
+                rigid_body_set: &mut scene.rigid_body_set,
+                narrow_phase: &mut scene.narrow_phase_collision,
+                event_tx: &mut self.event_tx,
+                keys_pressed: &mut self.keys_pressed,
+                key_locks: &mut self.key_locks,
+                query_pipeline: &mut self.query_pipeline,
+                collider_set: &mut scene.collider_set,
+                collision_locks: &mut self.collision_locks,
+                frame_delta: &self.last_delta,
+            }),
+        }
+    }
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.clock.time_scale = time_scale.max(0.0);
+    }
+    pub fn set_paused(&mut self, paused: bool) {
+        self.clock.paused = paused;
+    }
+}
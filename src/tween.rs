@@ -0,0 +1,799 @@
+use crate::game_object::events::UserEvent;
+use crate::game_object::graphics::GraphicsType;
+use crate::physics::screen_units_to_physics_units;
+use crate::scene::Scene;
+use crate::timers::TimerAction;
+use crate::ui::frontend::{Element, RGBColor, SpacingUnit};
+use crate::Engine;
+use rapier2d::math::Rotation;
+use rapier2d::prelude::vector;
+use rhai::{Dynamic, EvalAltResult, FnPtr, Map};
+use serde_json::Value;
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_TWEEN_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Easing curves, following Robert Penner's equations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    SineIn,
+    SineOut,
+    SineInOut,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    /// Maps linear progress `t` in 0..1 onto the curve.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::QuadIn => t.powi(2),
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => in_out(t, |t| t.powi(2)),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => 1.0 - (1.0 - t).powi(4),
+            Easing::QuartInOut => in_out(t, |t| t.powi(4)),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => 1.0 - (1.0 - t).powi(5),
+            Easing::QuintInOut => in_out(t, |t| t.powi(5)),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => 1.0 - expo_in(1.0 - t),
+            Easing::ExpoInOut => in_out(t, expo_in),
+            Easing::CircIn => 1.0 - (1.0 - t.powi(2)).sqrt(),
+            Easing::CircOut => (1.0 - (t - 1.0).powi(2)).sqrt(),
+            Easing::CircInOut => in_out(t, |t| 1.0 - (1.0 - t.powi(2)).sqrt()),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => 1.0 - back_in(1.0 - t),
+            Easing::BackInOut => in_out(t, back_in),
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => 1.0 - elastic_in(1.0 - t),
+            Easing::ElasticInOut => in_out(t, elastic_in),
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => in_out(t, |t| 1.0 - bounce_out(1.0 - t)),
+        }
+    }
+    /// Looks an easing up by its snake case name, e.g. `ease_out_quad` or `linear`.
+    pub fn from_name(name: &str) -> Option<Easing> {
+        let easing = match name {
+            "linear" => Easing::Linear,
+            "ease_in_sine" => Easing::SineIn,
+            "ease_out_sine" => Easing::SineOut,
+            "ease_in_out_sine" => Easing::SineInOut,
+            "ease_in_quad" => Easing::QuadIn,
+            "ease_out_quad" => Easing::QuadOut,
+            "ease_in_out_quad" => Easing::QuadInOut,
+            "ease_in_cubic" => Easing::CubicIn,
+            "ease_out_cubic" => Easing::CubicOut,
+            "ease_in_out_cubic" => Easing::CubicInOut,
+            "ease_in_quart" => Easing::QuartIn,
+            "ease_out_quart" => Easing::QuartOut,
+            "ease_in_out_quart" => Easing::QuartInOut,
+            "ease_in_quint" => Easing::QuintIn,
+            "ease_out_quint" => Easing::QuintOut,
+            "ease_in_out_quint" => Easing::QuintInOut,
+            "ease_in_expo" => Easing::ExpoIn,
+            "ease_out_expo" => Easing::ExpoOut,
+            "ease_in_out_expo" => Easing::ExpoInOut,
+            "ease_in_circ" => Easing::CircIn,
+            "ease_out_circ" => Easing::CircOut,
+            "ease_in_out_circ" => Easing::CircInOut,
+            "ease_in_back" => Easing::BackIn,
+            "ease_out_back" => Easing::BackOut,
+            "ease_in_out_back" => Easing::BackInOut,
+            "ease_in_elastic" => Easing::ElasticIn,
+            "ease_out_elastic" => Easing::ElasticOut,
+            "ease_in_out_elastic" => Easing::ElasticInOut,
+            "ease_in_bounce" => Easing::BounceIn,
+            "ease_out_bounce" => Easing::BounceOut,
+            "ease_in_out_bounce" => Easing::BounceInOut,
+            _ => return None,
+        };
+        Some(easing)
+    }
+}
+
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t == 0.0 {
+        0.0
+    } else {
+        2.0f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn back_in(t: f32) -> f32 {
+    const C1: f32 = 1.70158;
+    (C1 + 1.0) * t.powi(3) - C1 * t.powi(2)
+}
+
+fn elastic_in(t: f32) -> f32 {
+    if t == 0.0 || t == 1.0 {
+        t
+    } else {
+        -(2.0f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoopMode {
+    Once,
+    /// Restarts from the beginning after each cycle
+    Loop,
+    /// Plays forwards, then backwards
+    PingPong,
+}
+
+impl LoopMode {
+    pub fn from_name(name: &str) -> Option<LoopMode> {
+        match name {
+            "once" => Some(LoopMode::Once),
+            "loop" => Some(LoopMode::Loop),
+            "ping_pong" => Some(LoopMode::PingPong),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiStyleProperty {
+    Color,
+    BackgroundColor,
+    FontSize,
+    MarginTop,
+    MarginBottom,
+    MarginLeft,
+    MarginRight,
+}
+
+impl UiStyleProperty {
+    pub fn from_name(name: &str) -> Option<UiStyleProperty> {
+        match name {
+            "color" => Some(UiStyleProperty::Color),
+            "background_color" => Some(UiStyleProperty::BackgroundColor),
+            "font_size" => Some(UiStyleProperty::FontSize),
+            "margin_top" => Some(UiStyleProperty::MarginTop),
+            "margin_bottom" => Some(UiStyleProperty::MarginBottom),
+            "margin_left" => Some(UiStyleProperty::MarginLeft),
+            "margin_right" => Some(UiStyleProperty::MarginRight),
+            _ => None,
+        }
+    }
+}
+
+/// The value a tween animates. GameObjects are referenced by ID.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TweenTarget {
+    /// `[x, y]`
+    Position(u128),
+    /// `[radians]`
+    Rotation(u128),
+    /// `[x, y]`
+    Scale(u128),
+    /// `[red, green, blue]` or `[red, green, blue, alpha]` of a shape, or a sprite's tint
+    Color(u128),
+    /// `[opacity]` of a sprite, from 0 to 1
+    Opacity(u128),
+    /// `[flash]` of a sprite, from 0 to 1
+    Flash(u128),
+    /// `[value]` of a numeric data map entry
+    DataMap(String),
+    /// Style of every UI element with the class
+    UiStyle {
+        class: String,
+        property: UiStyleProperty,
+    },
+}
+
+impl TweenTarget {
+    /// How many values the target takes, e.g. 2 for a position.
+    pub fn arity(&self) -> RangeInclusive<usize> {
+        match self {
+            TweenTarget::Position(_) | TweenTarget::Scale(_) => 2..=2,
+            TweenTarget::Color(_)
+            | TweenTarget::UiStyle {
+                property: UiStyleProperty::Color | UiStyleProperty::BackgroundColor,
+                ..
+            } => 3..=4,
+            _ => 1..=1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TweenHandle(pub(crate) u64);
+
+impl TweenHandle {
+    pub(crate) fn next() -> Self {
+        TweenHandle(NEXT_TWEEN_HANDLE.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub struct TweenBuilder {
+    target: TweenTarget,
+    from: Option<Vec<f32>>,
+    to: Vec<f32>,
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+    loop_mode: LoopMode,
+    cycles: Option<u32>,
+    on_complete: Option<(u128, TimerAction)>,
+    next: Option<Tween>,
+}
+
+impl TweenBuilder {
+    pub fn new(target: TweenTarget) -> TweenBuilder {
+        TweenBuilder {
+            target,
+            from: None,
+            to: vec![],
+            duration: 1.0,
+            delay: 0.0,
+            easing: Easing::Linear,
+            loop_mode: LoopMode::Once,
+            cycles: None,
+            on_complete: None,
+            next: None,
+        }
+    }
+    pub fn to(mut self, to: &[f32]) -> TweenBuilder {
+        self.to = to.to_vec();
+        self
+    }
+    /// Start value. Defaults to the target's value when the tween starts.
+    pub fn from(mut self, from: &[f32]) -> TweenBuilder {
+        self.from = Some(from.to_vec());
+        self
+    }
+    /// Length of one cycle in seconds of game time
+    pub fn duration(mut self, seconds: f32) -> TweenBuilder {
+        self.duration = seconds;
+        self
+    }
+    pub fn delay(mut self, seconds: f32) -> TweenBuilder {
+        self.delay = seconds;
+        self
+    }
+    pub fn easing(mut self, easing: Easing) -> TweenBuilder {
+        self.easing = easing;
+        self
+    }
+    /// `cycles` of `None` repeats forever. A ping-pong cycle is there and back again.
+    pub fn looping(mut self, loop_mode: LoopMode, cycles: Option<u32>) -> TweenBuilder {
+        self.loop_mode = loop_mode;
+        self.cycles = cycles;
+        self
+    }
+    /// Runs `action` on behalf of `owner` once the tween finishes.
+    pub fn on_complete(mut self, owner: u128, action: TimerAction) -> TweenBuilder {
+        self.on_complete = Some((owner, action));
+        self
+    }
+    /// Starts `tween` once this one finishes. Both share a handle.
+    pub fn then(mut self, tween: Tween) -> TweenBuilder {
+        self.next = Some(tween);
+        self
+    }
+    /// Panics if `to` or `from` doesn't have as many values as the target takes.
+    pub fn build(self) -> Tween {
+        let arity = self.target.arity();
+        for values in std::iter::once(&self.to).chain(&self.from) {
+            assert!(
+                arity.contains(&values.len()),
+                "{:?} tweens need {:?} values, not {}",
+                self.target,
+                arity,
+                values.len()
+            );
+        }
+        Tween {
+            handle: TweenHandle::next(),
+            target: self.target,
+            from: self.from,
+            to: self.to,
+            duration: self.duration,
+            delay: self.delay,
+            easing: self.easing,
+            loop_mode: self.loop_mode,
+            cycles: self.cycles,
+            on_complete: self.on_complete,
+            next: self.next.map(Box::new),
+            start: None,
+            elapsed: 0.0,
+            completed_cycles: 0,
+            reversed: false,
+        }
+    }
+}
+
+pub struct Tween {
+    pub(crate) handle: TweenHandle,
+    target: TweenTarget,
+    from: Option<Vec<f32>>,
+    to: Vec<f32>,
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+    loop_mode: LoopMode,
+    cycles: Option<u32>,
+    on_complete: Option<(u128, TimerAction)>,
+    next: Option<Box<Tween>>,
+    start: Option<Vec<f32>>,
+    elapsed: f32,
+    completed_cycles: u32,
+    reversed: bool,
+}
+
+impl Tween {
+    pub fn handle(&self) -> TweenHandle {
+        self.handle
+    }
+    /// Advances the tween and writes the new value. Returns true once it has finished.
+    fn step(&mut self, scene: &mut Scene, mut delta: f32) -> bool {
+        if self.delay > 0.0 {
+            self.delay -= delta;
+            if self.delay > 0.0 {
+                return false;
+            }
+            delta = -self.delay;
+            self.delay = 0.0;
+        }
+        if self.start.is_none() {
+            let start = match self.from.clone().or_else(|| read_target(scene, &self.target)) {
+                Some(start) => start,
+                // The target is gone, nothing to animate
+                None => return true,
+            };
+            self.start = Some(start);
+        }
+
+        self.elapsed += delta;
+        let progress = if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).min(1.0)
+        };
+        let progress = if self.reversed { 1.0 - progress } else { progress };
+        let eased = self.easing.apply(progress);
+        let start = self.start.as_ref().unwrap();
+        let value: Vec<f32> = start
+            .iter()
+            .zip(&self.to)
+            .map(|(from, to)| from + (to - from) * eased)
+            .collect();
+        write_target(scene, &self.target, &value);
+
+        if self.elapsed < self.duration {
+            return false;
+        }
+        self.elapsed = (self.elapsed - self.duration).max(0.0);
+        self.completed_cycles += 1;
+        match self.loop_mode {
+            LoopMode::Once => true,
+            _ if self.duration <= 0.0 => true,
+            LoopMode::Loop => self.cycles.is_some_and(|c| self.completed_cycles >= c),
+            LoopMode::PingPong => {
+                self.reversed = !self.reversed;
+                self.cycles.is_some_and(|c| self.completed_cycles >= c * 2)
+            }
+        }
+    }
+}
+
+fn rhai_values(value: &Dynamic) -> Result<Vec<f32>, Box<EvalAltResult>> {
+    let number = |value: &Dynamic| {
+        value
+            .as_float()
+            .or_else(|_| value.as_int().map(|i| i as f64))
+            .map(|f| f as f32)
+            .map_err(|_| -> Box<EvalAltResult> { "tween values must be numbers".into() })
+    };
+    match value.clone().into_array() {
+        Ok(array) => array.iter().map(number).collect(),
+        Err(_) => Ok(vec![number(value)?]),
+    }
+}
+
+fn rhai_number(map: &Map, key: &str, default: f32) -> Result<f32, Box<EvalAltResult>> {
+    match map.get(key) {
+        Some(value) => Ok(rhai_values(value)?[0]),
+        None => Ok(default),
+    }
+}
+
+fn rhai_string(map: &Map, key: &str) -> Option<String> {
+    map.get(key).and_then(|value| value.clone().into_string().ok())
+}
+
+/// Builds a tween from a Rhai map such as
+/// `#{ target: "position", to: [100.0, 0.0], duration: 0.5, easing: "ease_out_quad" }`.
+/// Object targets default to `owner`, which also receives the `on_complete` event or callback.
+pub(crate) fn tween_from_rhai(owner: u128, map: &Map) -> Result<Tween, Box<EvalAltResult>> {
+    let object = match map.get("object") {
+        Some(id) => id.as_int().map_err(|_| -> Box<EvalAltResult> { "object must be an id".into() })? as u128,
+        None => owner,
+    };
+    let target_name = rhai_string(map, "target").ok_or("tween needs a target")?;
+    let target = match target_name.as_str() {
+        "position" => TweenTarget::Position(object),
+        "rotation" => TweenTarget::Rotation(object),
+        "scale" => TweenTarget::Scale(object),
+        "color" => TweenTarget::Color(object),
+        "opacity" => TweenTarget::Opacity(object),
+        "flash" => TweenTarget::Flash(object),
+        "data" => TweenTarget::DataMap(rhai_string(map, "key").ok_or("data tweens need a key")?),
+        "ui" => TweenTarget::UiStyle {
+            class: rhai_string(map, "class").ok_or("ui tweens need a class")?,
+            property: rhai_string(map, "property")
+                .and_then(|name| UiStyleProperty::from_name(&name))
+                .ok_or("ui tweens need a valid property")?,
+        },
+        other => return Err(format!("unknown tween target '{}'", other).into()),
+    };
+
+    let arity = target.arity();
+    let checked = |values: Vec<f32>| -> Result<Vec<f32>, Box<EvalAltResult>> {
+        match arity.contains(&values.len()) {
+            true => Ok(values),
+            false => Err(format!(
+                "{} tweens need {} to {} values, not {}",
+                target_name,
+                arity.start(),
+                arity.end(),
+                values.len()
+            )
+            .into()),
+        }
+    };
+    let to = checked(rhai_values(map.get("to").ok_or("tween needs a to value")?)?)?;
+    let mut builder = TweenBuilder::new(target)
+        .to(&to)
+        .duration(rhai_number(map, "duration", 1.0)?)
+        .delay(rhai_number(map, "delay", 0.0)?);
+    if let Some(from) = map.get("from") {
+        builder = builder.from(&checked(rhai_values(from)?)?);
+    }
+    if let Some(name) = rhai_string(map, "easing") {
+        let easing = Easing::from_name(&name).ok_or(format!("unknown easing '{}'", name))?;
+        builder = builder.easing(easing);
+    }
+    if let Some(name) = rhai_string(map, "loop") {
+        let loop_mode = LoopMode::from_name(&name).ok_or(format!("unknown loop mode '{}'", name))?;
+        let cycles = map
+            .get("repeat")
+            .and_then(|repeat| repeat.as_int().ok())
+            .map(|repeat| repeat.max(1) as u32);
+        builder = builder.looping(loop_mode, cycles);
+    }
+    if let Some(on_complete) = map.get("on_complete") {
+        let action = if let Some(callback) = on_complete.clone().try_cast::<FnPtr>() {
+            TimerAction::ScriptCallback(callback.fn_name().to_string())
+        } else {
+            let topic = on_complete.clone().into_string()?;
+            TimerAction::Event(UserEvent::from_value(&topic, Value::Null))
+        };
+        builder = builder.on_complete(owner, action);
+    }
+    if let Some(next) = map.get("then") {
+        let next = next
+            .clone()
+            .try_cast::<Map>()
+            .ok_or("then must be a tween map")?;
+        builder = builder.then(tween_from_rhai(owner, &next)?);
+    }
+    Ok(builder.build())
+}
+
+fn color_to_values(color: &RGBColor) -> Vec<f32> {
+    vec![color.red as f32, color.green as f32, color.blue as f32, color.alpha as f32]
+}
+
+fn write_color(color: &mut RGBColor, value: &[f32]) {
+    let channel = |i: usize| value.get(i).map_or(0, |v| v.round().clamp(0.0, 255.0) as u8);
+    color.red = channel(0);
+    color.green = channel(1);
+    color.blue = channel(2);
+    // Three value tweens leave alpha alone
+    if value.len() > 3 {
+        color.alpha = channel(3);
+    }
+}
+
+fn spacing_to_value(spacing: &SpacingUnit) -> f32 {
+    match spacing {
+        SpacingUnit::Pixels(px) => *px as f32,
+        SpacingUnit::PercentWidth(p) | SpacingUnit::PercentHeight(p) => *p as f32,
+    }
+}
+
+fn write_spacing(spacing: &mut SpacingUnit, value: f32) {
+    let value = value.round() as i32;
+    match spacing {
+        SpacingUnit::Pixels(px) => *px = value,
+        SpacingUnit::PercentWidth(p) | SpacingUnit::PercentHeight(p) => *p = value,
+    }
+}
+
+fn read_target(scene: &Scene, target: &TweenTarget) -> Option<Vec<f32>> {
+    let find = |id: &u128| scene.game_objects.iter().find(|o| o.id == *id);
+    match target {
+        TweenTarget::Position(id) => find(id).map(|o| vec![o.pos_x, o.pos_y]),
+        TweenTarget::Rotation(id) => find(id).map(|o| vec![o.rotation]),
+        TweenTarget::Scale(id) => find(id).map(|o| vec![o.scale_x, o.scale_y]),
+        TweenTarget::Color(id) => match find(id)?.graphics.as_ref()? {
+            GraphicsType::Circle(circle) => Some(color_to_values(&circle.color)),
+            GraphicsType::Rect(rect) => Some(color_to_values(&rect.color)),
+            GraphicsType::Triangle(triangle) => Some(color_to_values(&triangle.color)),
+            GraphicsType::Polygon(polygon) => Some(color_to_values(&polygon.color)),
+            GraphicsType::Line(line) => Some(color_to_values(&line.color)),
+            GraphicsType::Sprite(sprite) => Some(color_to_values(&sprite.tint)),
+            GraphicsType::AnimatedSprite(animated) => Some(color_to_values(&animated.tint)),
+            GraphicsType::Tilemap(tilemap) => Some(color_to_values(&tilemap.tint)),
+            // Particles take their colour from the emitter's colours over their life
+            GraphicsType::ParticleEmitter(_) => None,
+        },
+        TweenTarget::Opacity(id) => match find(id)?.graphics.as_ref()? {
+            GraphicsType::Sprite(sprite) => Some(vec![sprite.opacity]),
+            GraphicsType::AnimatedSprite(animated) => Some(vec![animated.opacity]),
+            GraphicsType::Tilemap(tilemap) => Some(vec![tilemap.opacity]),
+            _ => None,
+        },
+        TweenTarget::Flash(id) => match find(id)?.graphics.as_ref()? {
+            GraphicsType::Sprite(sprite) => Some(vec![sprite.flash]),
+            GraphicsType::AnimatedSprite(animated) => Some(vec![animated.flash]),
+            _ => None,
+        },
+        TweenTarget::DataMap(key) => Some(vec![scene.data_map.get(key)?.parse().ok()?]),
+        TweenTarget::UiStyle { class, property } => {
+            let element = scene.ui_ast.as_ref()?.elements.iter().find(|e| match e {
+                Element::Text(text) => text.classes.contains(class),
+                Element::Button(button) => button.classes.contains(class),
+            })?;
+            match (element, property) {
+                (Element::Text(t), UiStyleProperty::Color) => Some(color_to_values(&t.styles.color)),
+                (Element::Button(b), UiStyleProperty::Color) => Some(color_to_values(&b.styles.color)),
+                (Element::Button(b), UiStyleProperty::BackgroundColor) => {
+                    Some(color_to_values(&b.styles.background_color))
+                }
+                (Element::Text(_), UiStyleProperty::BackgroundColor) => None,
+                (Element::Text(t), UiStyleProperty::FontSize) => Some(vec![spacing_to_value(&t.styles.font_size)]),
+                (Element::Button(b), UiStyleProperty::FontSize) => Some(vec![spacing_to_value(&b.styles.font_size_px)]),
+                (Element::Text(t), margin) => Some(vec![spacing_to_value(match margin {
+                    UiStyleProperty::MarginTop => &t.styles.margin_top,
+                    UiStyleProperty::MarginBottom => &t.styles.margin_bottom,
+                    UiStyleProperty::MarginLeft => &t.styles.margin_left,
+                    _ => &t.styles.margin_right,
+                })]),
+                (Element::Button(b), margin) => Some(vec![spacing_to_value(match margin {
+                    UiStyleProperty::MarginTop => &b.styles.margin_top,
+                    UiStyleProperty::MarginBottom => &b.styles.margin_bottom,
+                    UiStyleProperty::MarginLeft => &b.styles.margin_left,
+                    _ => &b.styles.margin_right,
+                })]),
+            }
+        }
+    }
+}
+
+fn write_target(scene: &mut Scene, target: &TweenTarget, value: &[f32]) {
+    if !target.arity().contains(&value.len()) {
+        return;
+    }
+    let object = match target {
+        TweenTarget::Position(id)
+        | TweenTarget::Rotation(id)
+        | TweenTarget::Scale(id)
+        | TweenTarget::Color(id)
+        | TweenTarget::Opacity(id)
+        | TweenTarget::Flash(id) => scene.game_objects.iter_mut().find(|o| o.id == *id),
+        _ => None,
+    };
+
+    match target {
+        TweenTarget::Position(_) => {
+            let object = match object {
+                Some(object) => object,
+                None => return,
+            };
+            object.pos_x = value[0];
+            object.pos_y = value[1];
+            // Physics objects take their position from the rigid body every frame
+            if let Some(handle) = object.physics.rigid_body_handle {
+                if let Some(body) = scene.rigid_body_set.get_mut(handle) {
+                    body.set_translation(
+                        vector![
+                            screen_units_to_physics_units(value[0]),
+                            screen_units_to_physics_units(value[1])
+                        ],
+                        true,
+                    );
+                }
+            }
+        }
+        TweenTarget::Rotation(_) => {
+            let object = match object {
+                Some(object) => object,
+                None => return,
+            };
+            object.rotation = value[0];
+            if let Some(handle) = object.physics.rigid_body_handle {
+                if let Some(body) = scene.rigid_body_set.get_mut(handle) {
+                    body.set_rotation(Rotation::new(value[0]), true);
+                }
+            }
+        }
+        TweenTarget::Scale(_) => {
+            if let Some(object) = object {
+                object.scale_x = value[0];
+                object.scale_y = value[1];
+            }
+        }
+        TweenTarget::Color(_) => match object.and_then(|o| o.graphics.as_mut()) {
+            Some(GraphicsType::Circle(circle)) => write_color(&mut circle.color, value),
+            Some(GraphicsType::Rect(rect)) => write_color(&mut rect.color, value),
+            Some(GraphicsType::Triangle(triangle)) => write_color(&mut triangle.color, value),
+            Some(GraphicsType::Polygon(polygon)) => write_color(&mut polygon.color, value),
+            Some(GraphicsType::Line(line)) => write_color(&mut line.color, value),
+            Some(GraphicsType::Sprite(sprite)) => write_color(&mut sprite.tint, value),
+            Some(GraphicsType::AnimatedSprite(animated)) => write_color(&mut animated.tint, value),
+            Some(GraphicsType::Tilemap(tilemap)) => write_color(&mut tilemap.tint, value),
+            _ => {}
+        },
+        TweenTarget::Opacity(_) => match object.and_then(|o| o.graphics.as_mut()) {
+            Some(GraphicsType::Sprite(sprite)) => sprite.opacity = value[0],
+            Some(GraphicsType::AnimatedSprite(animated)) => animated.opacity = value[0],
+            Some(GraphicsType::Tilemap(tilemap)) => tilemap.opacity = value[0],
+            _ => {}
+        },
+        TweenTarget::Flash(_) => match object.and_then(|o| o.graphics.as_mut()) {
+            Some(GraphicsType::Sprite(sprite)) => sprite.flash = value[0],
+            Some(GraphicsType::AnimatedSprite(animated)) => animated.flash = value[0],
+            _ => {}
+        },
+        TweenTarget::DataMap(key) => {
+            let text = if value[0].fract() == 0.0 {
+                (value[0] as i64).to_string()
+            } else {
+                value[0].to_string()
+            };
+            scene.data_map.insert(key.clone(), text);
+        }
+        TweenTarget::UiStyle { class, property } => {
+            let ui_ast = match scene.ui_ast.as_mut() {
+                Some(ui_ast) => ui_ast,
+                None => return,
+            };
+            for element in &mut ui_ast.elements {
+                match element {
+                    Element::Text(text) if text.classes.contains(class) => {
+                        let styles = &mut text.styles;
+                        match property {
+                            UiStyleProperty::Color => write_color(&mut styles.color, value),
+                            UiStyleProperty::BackgroundColor => {}
+                            UiStyleProperty::FontSize => write_spacing(&mut styles.font_size, value[0]),
+                            UiStyleProperty::MarginTop => write_spacing(&mut styles.margin_top, value[0]),
+                            UiStyleProperty::MarginBottom => write_spacing(&mut styles.margin_bottom, value[0]),
+                            UiStyleProperty::MarginLeft => write_spacing(&mut styles.margin_left, value[0]),
+                            UiStyleProperty::MarginRight => write_spacing(&mut styles.margin_right, value[0]),
+                        }
+                    }
+                    Element::Button(button) if button.classes.contains(class) => {
+                        let styles = &mut button.styles;
+                        match property {
+                            UiStyleProperty::Color => write_color(&mut styles.color, value),
+                            UiStyleProperty::BackgroundColor => write_color(&mut styles.background_color, value),
+                            UiStyleProperty::FontSize => write_spacing(&mut styles.font_size_px, value[0]),
+                            UiStyleProperty::MarginTop => write_spacing(&mut styles.margin_top, value[0]),
+                            UiStyleProperty::MarginBottom => write_spacing(&mut styles.margin_bottom, value[0]),
+                            UiStyleProperty::MarginLeft => write_spacing(&mut styles.margin_left, value[0]),
+                            UiStyleProperty::MarginRight => write_spacing(&mut styles.margin_right, value[0]),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl Engine {
+    pub(crate) fn update_tweens(&mut self) {
+        let delta = self.clock.scaled_delta(&self.last_delta);
+        if delta <= 0.0 {
+            return;
+        }
+        let scene = match self.active_scene.as_mut() {
+            Some(scene) => scene,
+            None => return,
+        };
+
+        let mut finished = vec![];
+        let mut index = 0;
+        while index < self.tweens.len() {
+            if self.tweens[index].step(scene, delta) {
+                finished.push(self.tweens.remove(index));
+            } else {
+                index += 1;
+            }
+        }
+
+        for tween in finished {
+            if let Some(mut next) = tween.next {
+                next.handle = tween.handle;
+                self.tweens.push(*next);
+            }
+            if let Some((owner, action)) = tween.on_complete {
+                self.run_action(owner, action);
+            }
+        }
+    }
+    pub fn start_tween(&mut self, tween: Tween) -> TweenHandle {
+        let handle = tween.handle;
+        self.tweens.push(tween);
+        handle
+    }
+    pub fn stop_tween(&mut self, handle: TweenHandle) {
+        self.tweens.retain(|tween| tween.handle != handle);
+    }
+}
@@ -62,7 +62,7 @@ pub(crate) fn draw_button(
                 color.red as f32 / 255.0,
                 color.green as f32 / 255.0,
                 color.blue as f32 / 255.0,
-                1.0,
+                color.alpha as f32 / 255.0,
             ])
             .with_scale(font_size as f32 * 2.0)],
         ..Section::default()
@@ -46,7 +46,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -64,7 +64,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -82,7 +82,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -100,7 +100,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -120,7 +120,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -137,7 +137,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
                 ..Section::default()
@@ -155,7 +155,7 @@ pub fn draw_text(
                         t.styles.color.red as f32 / 255.0,
                         t.styles.color.green as f32 / 255.0,
                         t.styles.color.blue as f32 / 255.0,
-                        1.0,
+                        t.styles.color.alpha as f32 / 255.0,
                     ])
                     .with_scale(font_size as f32 * 5.0)],
 
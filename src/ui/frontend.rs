@@ -25,6 +25,13 @@ pub struct RGBColor {
     pub red: u8,
     pub green: u8,
     pub blue: u8,
+    /// 0 is fully transparent, 255 fully opaque
+    #[serde(default = "opaque")]
+    pub alpha: u8,
+}
+
+fn opaque() -> u8 {
+    255
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
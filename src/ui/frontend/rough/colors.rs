@@ -1,6 +1,6 @@
 use crate::ui::frontend::RGBColor;
 use nom::bytes::complete::{tag, take_while_m_n};
-use nom::combinator::map_res;
+use nom::combinator::{map_res, opt};
 use nom::sequence::tuple;
 use nom::IResult;
 
@@ -26,8 +26,17 @@ pub fn hex_color(input: &str) -> IResult<&str, RGBColor> {
     }
 
     let (modified_input, _) = tag("#")(modified_input)?;
-    let (_modified_input, (red, green, blue)) =
-        tuple((hex_primary, hex_primary, hex_primary))(modified_input)?;
+    // An optional fourth pair is alpha, e.g. #00000080
+    let (_modified_input, (red, green, blue, alpha)) =
+        tuple((hex_primary, hex_primary, hex_primary, opt(hex_primary)))(modified_input)?;
 
-    Ok((input, RGBColor { red, green, blue }))
+    Ok((
+        input,
+        RGBColor {
+            red,
+            green,
+            blue,
+            alpha: alpha.unwrap_or(255),
+        },
+    ))
 }
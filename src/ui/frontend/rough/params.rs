@@ -249,6 +249,7 @@ pub fn extract_params_from_line_for_text(line: &str) -> RoughParamsText {
                 red: 255,
                 green: 255,
                 blue: 255,
+                alpha: 255,
             },
             margin_top: SpacingUnit::Pixels(0),
             margin_bottom: SpacingUnit::Pixels(0),
@@ -368,11 +369,13 @@ pub fn extract_params_from_line_for_button(line: &str) -> RoughParamsButton {
                 red: 255,
                 green: 255,
                 blue: 255,
+                alpha: 255,
             },
             background_color: RGBColor {
                 red: 0,
                 green: 0,
                 blue: 0,
+                alpha: 255,
             },
             margin_top: SpacingUnit::Pixels(0),
             margin_bottom: SpacingUnit::Pixels(0),
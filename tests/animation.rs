@@ -0,0 +1,122 @@
+use alcubierre::game_object::animation::{AnimationClip, AnimationEnded, AnimationFrameChanged};
+use alcubierre::game_object::graphics::AnimatedSpriteData;
+use alcubierre::tween::LoopMode;
+use pretty_assertions::assert_eq;
+
+fn walk(loop_mode: LoopMode) -> AnimatedSpriteData {
+    AnimatedSpriteData::new(32.0, 32.0)
+        .clip(
+            "walk",
+            AnimationClip::new(loop_mode)
+                .frame("walk_0", 0.1)
+                .frame("walk_1", 0.2)
+                .frame("walk_2", 0.1),
+        )
+        .autoplay("walk")
+}
+
+#[test]
+fn frames_follow_their_durations() {
+    let mut sprite = walk(LoopMode::Loop);
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_0");
+
+    let events = sprite.advance(0.15);
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_1");
+    assert_eq!(
+        events[0].read::<AnimationFrameChanged>(),
+        Some(AnimationFrameChanged {
+            clip: "walk".to_string(),
+            frame: 1,
+        })
+    );
+
+    sprite.advance(0.1);
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_1");
+    sprite.advance(0.1);
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_2");
+}
+
+#[test]
+fn looping_clip_wraps_and_reports_end() {
+    let mut sprite = walk(LoopMode::Loop);
+    let events = sprite.advance(0.45);
+
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_0");
+    assert!(sprite.player().is_playing());
+    let ended: Vec<AnimationEnded> = events.iter().filter_map(|e| e.read()).collect();
+    assert_eq!(
+        ended,
+        vec![AnimationEnded {
+            clip: "walk".to_string(),
+            finished: false,
+        }]
+    );
+}
+
+#[test]
+fn long_updates_skip_whole_cycles_and_report_once() {
+    let mut sprite = walk(LoopMode::Loop);
+    // Twenty-five times round the clip, then halfway into the second frame
+    let events = sprite.advance(10.15);
+
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_1");
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[0].read::<AnimationFrameChanged>(),
+        Some(AnimationFrameChanged {
+            clip: "walk".to_string(),
+            frame: 1,
+        })
+    );
+    assert_eq!(
+        events[1].read::<AnimationEnded>(),
+        Some(AnimationEnded {
+            clip: "walk".to_string(),
+            finished: false,
+        })
+    );
+
+    let mut sprite = walk(LoopMode::PingPong);
+    let events = sprite.advance(6.45);
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_1");
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn once_clip_stops_on_last_frame() {
+    let mut sprite = walk(LoopMode::Once);
+    let events = sprite.advance(1.0);
+
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_2");
+    assert!(!sprite.player().is_playing());
+    assert_eq!(
+        events.last().unwrap().read::<AnimationEnded>(),
+        Some(AnimationEnded {
+            clip: "walk".to_string(),
+            finished: true,
+        })
+    );
+}
+
+#[test]
+fn ping_pong_clip_plays_backwards() {
+    let mut sprite = walk(LoopMode::PingPong);
+    sprite.advance(0.45);
+
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_1");
+    assert_eq!(sprite.player().frame(), 1);
+}
+
+#[test]
+fn switching_clip_keeps_frame() {
+    let mut sprite = walk(LoopMode::Loop).clip(
+        "run",
+        AnimationClip::from_frames(&["run_0", "run_1", "run_2"], 10.0, LoopMode::Loop),
+    );
+    sprite.advance(0.15);
+    sprite.switch_clip("run");
+    assert_eq!(sprite.current_sprite_id().unwrap(), "run_1");
+
+    sprite.play("walk");
+    assert_eq!(sprite.current_sprite_id().unwrap(), "walk_0");
+}
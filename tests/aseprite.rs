@@ -0,0 +1,70 @@
+use alcubierre::game_object::animation::AnimationClip;
+use alcubierre::tween::LoopMode;
+use alcubierre::SpriteAtlas;
+use image::{Rgba, RgbaImage};
+use pretty_assertions::assert_eq;
+
+/// Loads a sprite sheet fixture, with a blank image standing in for the exported one.
+fn load(descriptor: &str) -> SpriteAtlas {
+    let image = std::env::temp_dir().join("alcubierre_aseprite_sheet.png");
+    RgbaImage::from_pixel(64, 16, Rgba([0, 0, 0, 0])).save(&image).unwrap();
+    SpriteAtlas::new(image.to_str().unwrap(), descriptor)
+}
+
+fn frames(clip: &AnimationClip) -> Vec<(&str, f32)> {
+    clip.frames.iter().map(|frame| (frame.sprite_id.as_str(), frame.duration)).collect()
+}
+
+#[test]
+fn hash_frames_keep_their_order_for_tags() {
+    let atlas = load("tests/aseprite_hash.json");
+    assert_eq!((atlas.width, atlas.height), (64, 16));
+    let blink = atlas.sprite("blink").unwrap();
+    assert_eq!((blink.position.x, blink.position.y), (16, 0));
+    assert!(!blink.trimmed);
+
+    let idle = atlas.animation("idle").unwrap();
+    assert_eq!(frames(idle), vec![("stand", 0.1), ("blink", 0.2)]);
+    assert_eq!(idle.loop_mode, LoopMode::Loop);
+    let attack = atlas.animation("attack").unwrap();
+    assert_eq!(frames(attack), vec![("swing", 0.05), ("lunge", 0.05)]);
+    assert_eq!(attack.loop_mode, LoopMode::PingPong);
+
+    // Plays through as many times as the tag repeats, then stops
+    let double_blink = atlas.animation("double_blink").unwrap();
+    assert_eq!(
+        frames(double_blink),
+        vec![("stand", 0.1), ("blink", 0.2), ("stand", 0.1), ("blink", 0.2)]
+    );
+    assert_eq!(double_blink.loop_mode, LoopMode::Once);
+}
+
+#[test]
+fn array_frames_are_named_by_filename_and_keep_their_trim() {
+    let atlas = load("tests/aseprite_array.json");
+    let squashed = atlas.sprite("slime 2.aseprite").unwrap();
+    assert_eq!((squashed.position.x, squashed.position.y), (26, 0));
+    assert_eq!((squashed.sourceSize.width, squashed.sourceSize.height), (16, 16));
+    assert!(squashed.trimmed);
+    assert_eq!(
+        (squashed.trimRec.x, squashed.trimRec.y, squashed.trimRec.width, squashed.trimRec.height),
+        (0, 12, 16, 4)
+    );
+
+    // Reversed, and played once since the tag repeats once
+    let squash = atlas.animation("squash").unwrap();
+    assert_eq!(
+        frames(squash),
+        vec![("slime 2.aseprite", 0.25), ("slime 1.aseprite", 0.15), ("slime 0.aseprite", 0.1)]
+    );
+    assert_eq!(squash.loop_mode, LoopMode::Once);
+}
+
+#[test]
+#[should_panic(expected = "Invalid Aseprite sprite sheet")]
+fn broken_aseprite_sheets_report_the_aseprite_error() {
+    let broken = std::env::temp_dir().join("alcubierre_aseprite_broken.json");
+    let contents = std::fs::read_to_string("tests/aseprite_hash.json").unwrap();
+    std::fs::write(&broken, contents.replacen(r#""duration": 100"#, r#""duration": "long""#, 1)).unwrap();
+    load(broken.to_str().unwrap());
+}
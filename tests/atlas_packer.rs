@@ -0,0 +1,115 @@
+use alcubierre::{AtlasPackOptions, SpriteAtlas};
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::path::PathBuf;
+
+fn solid(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255])))
+}
+
+/// An empty directory only this test process uses.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("alcubierre_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn packed_sprites_do_not_overlap() {
+    let images = vec![
+        ("a".to_string(), solid(30, 10)),
+        ("b".to_string(), solid(16, 16)),
+        ("c".to_string(), solid(8, 40)),
+        ("d".to_string(), solid(20, 20)),
+    ];
+    let options = AtlasPackOptions::default();
+    let atlas = SpriteAtlas::pack_from_images(images, &options);
+
+    assert!(atlas.width.is_power_of_two() && atlas.height.is_power_of_two());
+    let rects: Vec<(u64, u64, u64, u64)> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|name| {
+            let sprite = atlas.sprite(name).unwrap();
+            (sprite.position.x, sprite.position.y, sprite.trimRec.width, sprite.trimRec.height)
+        })
+        .collect();
+    for (i, a) in rects.iter().enumerate() {
+        assert!(a.0 + a.2 <= atlas.width && a.1 + a.3 <= atlas.height);
+        for b in &rects[i + 1..] {
+            let padding = options.padding as u64;
+            let apart = a.0 + a.2 + padding <= b.0
+                || b.0 + b.2 + padding <= a.0
+                || a.1 + a.3 + padding <= b.1
+                || b.1 + b.3 + padding <= a.1;
+            assert!(apart, "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn trimming_keeps_source_size() {
+    let mut image = RgbaImage::new(32, 32);
+    for x in 4..10 {
+        for y in 8..20 {
+            image.put_pixel(x, y, Rgba([0, 255, 0, 255]));
+        }
+    }
+    let options = AtlasPackOptions {
+        trim: true,
+        ..AtlasPackOptions::default()
+    };
+    let atlas = SpriteAtlas::pack_from_images(vec![("leaf".to_string(), DynamicImage::ImageRgba8(image))], &options);
+    let sprite = atlas.sprite("leaf").unwrap();
+
+    assert!(sprite.trimmed);
+    assert_eq!((sprite.sourceSize.width, sprite.sourceSize.height), (32, 32));
+    assert_eq!(
+        (sprite.trimRec.x, sprite.trimRec.y, sprite.trimRec.width, sprite.trimRec.height),
+        (4, 8, 6, 12)
+    );
+}
+
+#[test]
+fn descriptor_round_trips() {
+    let dir = temp_dir("atlas_round_trip");
+    solid(12, 12).save(dir.join("one.png")).unwrap();
+    solid(6, 9).save(dir.join("two.png")).unwrap();
+
+    let atlas = SpriteAtlas::pack_from_dir(dir.to_str().unwrap(), &AtlasPackOptions::default());
+    let out = temp_dir("atlas_round_trip_out");
+    let (png, json) = (out.join("atlas.png"), out.join("atlas.json"));
+    atlas.write_to_files(png.to_str().unwrap(), json.to_str().unwrap());
+
+    let loaded = SpriteAtlas::new(png.to_str().unwrap(), json.to_str().unwrap());
+    assert_eq!((loaded.width, loaded.height), (atlas.width, atlas.height));
+    let two = loaded.sprite("two").unwrap();
+    assert_eq!((two.sourceSize.width, two.sourceSize.height), (6, 9));
+    assert_eq!(two.position.x, atlas.sprite("two").unwrap().position.x);
+
+    std::fs::remove_dir_all(dir).unwrap();
+    std::fs::remove_dir_all(out).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "would both be sprite hero")]
+fn sprites_from_files_sharing_a_stem_are_an_error() {
+    let dir = temp_dir("atlas_duplicate_stems");
+    solid(4, 4).save(dir.join("hero.png")).unwrap();
+    solid(4, 4).to_rgb8().save(dir.join("hero.jpg")).unwrap();
+
+    let result = std::panic::catch_unwind(|| SpriteAtlas::pack_from_dir(dir.to_str().unwrap(), &AtlasPackOptions::default()));
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::panic::resume_unwind(result.err().unwrap());
+}
+
+#[test]
+#[should_panic(expected = "do not fit in a 48x48 atlas")]
+fn rounding_up_to_a_power_of_two_stays_within_the_max_size() {
+    let options = AtlasPackOptions {
+        padding: 0,
+        max_size: 48,
+        ..AtlasPackOptions::default()
+    };
+    // Packs 40 high, which rounds up to 64
+    SpriteAtlas::pack_from_images(vec![("pole".to_string(), solid(16, 40))], &options);
+}
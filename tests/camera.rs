@@ -0,0 +1,129 @@
+use alcubierre::camera::{Camera2D, CameraBounds, CameraCommand, CameraFollow, Viewport};
+use alcubierre::game_object::graphics::RenderLayer;
+use alcubierre::ui::frontend::RGBColor;
+use alcubierre::{Engine, EngineConfig};
+
+const WINDOW: [f32; 2] = [800.0, 600.0];
+
+fn assert_close(a: [f32; 2], b: [f32; 2]) {
+    assert!(
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3,
+        "{:?} != {:?}",
+        a,
+        b
+    );
+}
+
+#[test]
+fn screen_and_world_round_trip() {
+    let mut camera = Camera2D::new();
+    camera.position = [3.0, -2.0];
+    camera.zoom = 2.0;
+    camera.rotation = 0.5;
+    camera.viewport = Viewport {
+        x: 0.5,
+        y: 0.0,
+        width: 0.5,
+        height: 1.0,
+    };
+
+    // The centre of the viewport is the camera's position
+    assert_close(camera.screen_to_world([600.0, 300.0], WINDOW), [3.0, -2.0]);
+    let world = camera.screen_to_world([450.0, 120.0], WINDOW);
+    assert_close(camera.world_to_screen(world, WINDOW), [450.0, 120.0]);
+}
+
+#[test]
+fn top_of_the_screen_is_up() {
+    let camera = Camera2D::new();
+    assert_close(camera.screen_to_world([400.0, 0.0], WINDOW), [0.0, 7.0]);
+}
+
+#[test]
+fn follow_waits_for_the_target_to_leave_the_dead_zone() {
+    let mut camera = Camera2D::new();
+    camera.apply(CameraCommand::Follow(CameraFollow::new(0).deadzone(2.0, 2.0)));
+
+    camera.update(0.016, Some([0.5, -0.5]), 1.0);
+    assert_close(camera.position, [0.0, 0.0]);
+
+    camera.update(0.016, Some([3.0, 0.0]), 1.0);
+    assert_close(camera.position, [2.0, 0.0]);
+}
+
+#[test]
+fn bounds_keep_the_view_inside_the_world() {
+    let mut camera = Camera2D::new();
+    camera.apply(CameraCommand::SetBounds(Some(CameraBounds {
+        min: [-10.0, -10.0],
+        max: [10.0, 30.0],
+    })));
+    camera.position = [100.0, -100.0];
+    camera.update(0.016, None, 1.0);
+    assert_close(camera.position, [3.0, -3.0]);
+
+    // Zoomed out past the bounds the view is centred on them
+    camera.zoom = 0.25;
+    camera.update(0.016, None, 1.0);
+    assert_close(camera.position, [0.0, 10.0]);
+}
+
+#[test]
+fn shake_ends_after_its_duration() {
+    let mut camera = Camera2D::new();
+    camera.apply(CameraCommand::Shake {
+        intensity: 1.0,
+        duration: 0.1,
+    });
+    camera.update(0.05, None, 1.0);
+    camera.update(0.1, None, 1.0);
+    assert_close(camera.screen_to_world([400.0, 300.0], WINDOW), [0.0, 0.0]);
+}
+
+#[test]
+fn split_screen_columns_cover_the_window() {
+    let left = Viewport::column(0, 2);
+    let right = Viewport::column(1, 2);
+    assert_eq!(left.to_pixels(WINDOW), [0.0, 0.0, 400.0, 600.0]);
+    assert_eq!(right.to_pixels(WINDOW), [400.0, 0.0, 400.0, 600.0]);
+    assert!(left.contains([399.0, 10.0], WINDOW));
+    assert!(!left.contains([400.0, 10.0], WINDOW));
+    assert!(right.contains([400.0, 10.0], WINDOW));
+    assert_eq!(Viewport::row(1, 2).to_pixels(WINDOW), [0.0, 300.0, 800.0, 300.0]);
+}
+
+#[test]
+fn layer_masks_pick_what_a_camera_sees() {
+    let mut minimap = Camera2D::new().with_layers(&[RenderLayer::World]);
+    assert!(minimap.sees(RenderLayer::World));
+    assert!(!minimap.sees(RenderLayer::Background));
+    assert!(!minimap.sees(RenderLayer::Overlay));
+
+    minimap.apply(CameraCommand::SetLayerVisible(RenderLayer::Overlay, true));
+    assert!(minimap.sees(RenderLayer::Overlay));
+}
+
+#[test]
+fn scenes_always_keep_a_camera() {
+    let mut engine = Engine::new(800, 600, EngineConfig {
+        gravity: 0.0,
+        clear_color: RGBColor { red: 0, green: 0, blue: 0, alpha: 255 },
+    });
+    let scene = engine.register_scene("main".to_string());
+    assert_eq!(scene.cameras().len(), 1);
+    assert_eq!(scene.add_camera(Camera2D::new().with_target("minimap")), 1);
+
+    let main = scene.remove_camera(0).unwrap();
+    assert_eq!(main.target, None);
+    // The minimap camera is the main one now, and can't be removed
+    assert_eq!(scene.camera().target.as_deref(), Some("minimap"));
+    assert!(scene.remove_camera(0).is_none());
+    assert!(scene.remove_camera(5).is_none());
+    assert_eq!(scene.cameras().len(), 1);
+    assert!(scene.camera_at(1).is_none());
+
+    // The engine only adds cameras once a scene is running
+    assert_eq!(engine.add_camera(Camera2D::new()), None);
+    engine.set_current_scene("main".to_string());
+    assert_eq!(engine.add_camera(Camera2D::new()), Some(1));
+}
@@ -0,0 +1,53 @@
+use alcubierre::camera::{DesignResolution, ScaleMode, VIEW_HEIGHT};
+
+const WINDOW: [f32; 2] = [1600.0, 720.0];
+
+#[test]
+fn fit_keeps_the_aspect_ratio_with_bars() {
+    let design = DesignResolution::new(640, 360, ScaleMode::Fit);
+
+    assert_eq!(design.scale(WINDOW), [2.0, 2.0]);
+    assert_eq!(design.visible(WINDOW), [640.0, 360.0]);
+    assert_eq!(design.letterbox(WINDOW), [160.0, 0.0, 1280.0, 720.0]);
+    assert_eq!(design.view_height(WINDOW), VIEW_HEIGHT);
+}
+
+#[test]
+fn fill_covers_the_window_and_crops() {
+    let design = DesignResolution::new(640, 360, ScaleMode::Fill);
+
+    assert_eq!(design.scale(WINDOW), [2.5, 2.5]);
+    assert_eq!(design.visible(WINDOW), [640.0, 288.0]);
+    assert_eq!(design.letterbox(WINDOW), [0.0, 0.0, 1600.0, 720.0]);
+    // Less of the world fits vertically
+    assert_eq!(design.view_height(WINDOW), VIEW_HEIGHT * 0.8);
+}
+
+#[test]
+fn stretch_scales_each_axis_on_its_own() {
+    let design = DesignResolution::new(640, 360, ScaleMode::Stretch);
+
+    assert_eq!(design.scale(WINDOW), [2.5, 2.0]);
+    assert_eq!(design.visible(WINDOW), [640.0, 360.0]);
+    assert_eq!(design.letterbox(WINDOW), [0.0, 0.0, 1600.0, 720.0]);
+}
+
+#[test]
+fn integer_only_scales_by_whole_multiples() {
+    let design = DesignResolution::new(640, 360, ScaleMode::Integer);
+
+    assert_eq!(design.scale([1900.0, 1000.0]), [2.0, 2.0]);
+    assert_eq!(design.letterbox([1900.0, 1000.0]), [310.0, 140.0, 1280.0, 720.0]);
+}
+
+#[test]
+fn window_and_design_positions_round_trip() {
+    for scale_mode in [ScaleMode::Fit, ScaleMode::Fill, ScaleMode::Stretch, ScaleMode::Integer] {
+        let design = DesignResolution::new(640, 360, scale_mode);
+        let visible = design.visible(WINDOW);
+
+        let centre = design.window_to_design([800.0, 360.0], WINDOW);
+        assert_eq!(centre, [visible[0] * 0.5, visible[1] * 0.5], "{:?}", scale_mode);
+        assert_eq!(design.design_to_window(centre, WINDOW), [800.0, 360.0], "{:?}", scale_mode);
+    }
+}
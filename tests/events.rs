@@ -0,0 +1,55 @@
+use alcubierre::game_object::events::{GameEvent, UserEvent};
+use pretty_assertions::assert_eq;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Scored {
+    points: i64,
+    player: String,
+}
+
+impl GameEvent for Scored {
+    const TOPIC: &'static str = "scored";
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Died;
+
+impl GameEvent for Died {
+    const TOPIC: &'static str = "died";
+}
+
+#[test]
+fn typed_event_round_trip() {
+    let event = UserEvent::new(Scored {
+        points: 3,
+        player: "left".to_string(),
+    });
+
+    assert_eq!(event.topic(), "scored");
+    assert_eq!(event.data(), &json!({ "points": 3, "player": "left" }));
+    assert_eq!(
+        event.read::<Scored>(),
+        Some(Scored {
+            points: 3,
+            player: "left".to_string(),
+        })
+    );
+    assert_eq!(event.read::<Died>(), None);
+}
+
+#[test]
+fn script_event_reads_as_typed() {
+    let event = UserEvent::from_value("scored", json!({ "points": 7, "player": "right" }));
+
+    assert!(event.is::<Scored>());
+    assert_eq!(event.sender(), None);
+    assert_eq!(
+        event.read::<Scored>(),
+        Some(Scored {
+            points: 7,
+            player: "right".to_string(),
+        })
+    );
+}
@@ -0,0 +1,64 @@
+use alcubierre::game_object::graphics::RenderLayer;
+use alcubierre::game_object::GameObjectBuilder;
+use alcubierre::ui::frontend::RGBColor;
+use alcubierre::{Engine, EngineConfig};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn layers_draw_back_to_front() {
+    let mut layers = vec![
+        (RenderLayer::Overlay, 0),
+        (RenderLayer::World, 5),
+        (RenderLayer::Background, 10),
+        (RenderLayer::World, -1),
+        (RenderLayer::Foreground, 0),
+    ];
+    layers.sort();
+    assert_eq!(
+        layers,
+        vec![
+            (RenderLayer::Background, 10),
+            (RenderLayer::World, -1),
+            (RenderLayer::World, 5),
+            (RenderLayer::Foreground, 0),
+            (RenderLayer::Overlay, 0),
+        ]
+    );
+}
+
+#[test]
+fn layer_names_resolve() {
+    assert_eq!(RenderLayer::from_name("background"), Some(RenderLayer::Background));
+    assert_eq!(RenderLayer::from_name("overlay"), Some(RenderLayer::Overlay));
+    assert_eq!(RenderLayer::from_name("sky"), None);
+    assert_eq!(RenderLayer::default(), RenderLayer::World);
+}
+
+#[test]
+fn engine_draws_by_layer_then_z_keeping_ties_in_order() {
+    let mut engine = Engine::new(64, 48, EngineConfig {
+        gravity: 0.0,
+        clear_color: RGBColor { red: 0, green: 0, blue: 0, alpha: 255 },
+    });
+    let scene = engine.register_scene("main".to_string());
+    let ids: Vec<u128> = [
+        (RenderLayer::Overlay, 0),
+        (RenderLayer::World, 5),
+        (RenderLayer::World, 0),
+        (RenderLayer::Background, 10),
+        (RenderLayer::World, 5),
+        (RenderLayer::Foreground, -3),
+        (RenderLayer::World, 0),
+    ]
+    .into_iter()
+    .map(|(layer, z_index)| scene.register_game_object(GameObjectBuilder::new().layer(layer).z_index(z_index)).id())
+    .collect();
+    engine.set_current_scene("main".to_string());
+
+    assert_eq!(engine.draw_order(), [3, 2, 6, 1, 4, 5, 0].map(|index| ids[index]));
+
+    engine.set_layer_visible(RenderLayer::World, false);
+    assert_eq!(engine.draw_order(), [3, 5, 0].map(|index| ids[index]));
+    engine.set_layer_visible(RenderLayer::World, true);
+    assert_eq!(engine.draw_order().len(), ids.len());
+}
@@ -0,0 +1,152 @@
+use alcubierre::game_object::graphics::GraphicsType;
+use alcubierre::game_object::tilemap::{Tile, TileCollision, TileRect};
+use alcubierre::game_object::GameObjectBuilder;
+use alcubierre::ldtk::{LdtkError, LdtkImport, LdtkLayerType, LdtkProject};
+
+const LDTK: &str = r#"{
+    "jsonVersion": "1.5.3",
+    "defs": {
+        "layers": [
+            { "uid": 10, "identifier": "Collision", "type": "IntGrid", "gridSize": 16,
+              "intGridValues": [{ "value": 1, "identifier": "Solid" }, { "value": 2, "identifier": "water" }] },
+            { "uid": 11, "identifier": "Things", "type": "Entities", "gridSize": 16 },
+            { "uid": 12, "identifier": "Ground", "type": "Tiles", "gridSize": 16 }
+        ],
+        "tilesets": [{
+            "uid": 1, "identifier": "Terrain", "relPath": "terrain.png", "pxWid": 64, "pxHei": 32,
+            "tileGridSize": 16, "__cWid": 4, "__cHei": 2, "padding": 0, "spacing": 0,
+            "enumTags": [{ "enumValueId": "One_Way", "tileIds": [2] }, { "enumValueId": "Grass", "tileIds": [0, 2] }]
+        }]
+    },
+    "levels": [{
+        "identifier": "Level_0", "iid": "level-0", "worldX": 256, "worldY": 0, "pxWid": 48, "pxHei": 32,
+        "fieldInstances": [{ "__identifier": "music", "__type": "String", "__value": "cave.ogg" }],
+        "layerInstances": [
+            { "__identifier": "Things", "__type": "Entities", "__cWid": 3, "__cHei": 2, "__gridSize": 16,
+              "__opacity": 1, "__pxTotalOffsetX": 0, "__pxTotalOffsetY": 0, "layerDefUid": 11, "visible": true,
+              "intGridCsv": [], "gridTiles": [], "autoLayerTiles": [],
+              "entityInstances": [
+                { "__identifier": "Player", "iid": "player-1", "px": [8, 16], "__pivot": [0.5, 1],
+                  "width": 16, "height": 32, "__tags": ["actor"], "__tile": null,
+                  "fieldInstances": [
+                    { "__identifier": "speed", "__type": "Int", "__value": 3 },
+                    { "__identifier": "spawn", "__type": "Point", "__value": { "cx": 2, "cy": 1 } }
+                  ] },
+                { "__identifier": "Crate", "iid": "crate-1", "px": [32, 0], "__pivot": [0, 0],
+                  "width": 16, "height": 16, "__tags": [],
+                  "__tile": { "tilesetUid": 1, "x": 48, "y": 0, "w": 16, "h": 16 },
+                  "fieldInstances": [] }
+              ] },
+            { "__identifier": "Ground", "__type": "Tiles", "__cWid": 3, "__cHei": 2, "__gridSize": 16,
+              "__opacity": 0.5, "__pxTotalOffsetX": 0, "__pxTotalOffsetY": 0, "__tilesetDefUid": 1,
+              "layerDefUid": 12, "visible": true, "intGridCsv": [], "autoLayerTiles": [], "entityInstances": [],
+              "gridTiles": [
+                { "px": [0, 0], "src": [32, 0], "f": 0, "t": 2 },
+                { "px": [16, 0], "src": [32, 0], "f": 0, "t": 2 },
+                { "px": [16, 0], "src": [0, 16], "f": 1, "t": 4 },
+                { "px": [32, 16], "src": [16, 0], "f": 3, "t": 1 }
+              ] },
+            { "__identifier": "Collision", "__type": "IntGrid", "__cWid": 3, "__cHei": 2, "__gridSize": 16,
+              "__opacity": 1, "__pxTotalOffsetX": 0, "__pxTotalOffsetY": 0, "layerDefUid": 10, "visible": true,
+              "intGridCsv": [0, 0, 2, 1, 1, 1], "gridTiles": [], "autoLayerTiles": [], "entityInstances": [] }
+        ]
+    }]
+}"#;
+
+#[test]
+fn levels_parse_with_layers_back_to_front() {
+    let project = LdtkProject::from_ldtk(LDTK).unwrap();
+
+    let tileset = project.tileset(1).unwrap();
+    assert_eq!(tileset.identifier, "Terrain");
+    assert_eq!(tileset.tile_id([48, 16, 16, 16]), Some(7));
+    assert_eq!(tileset.tile_id([8, 0, 16, 16]), None);
+    assert_eq!(tileset.tile_id([0, 0, 32, 16]), None);
+
+    let level = project.level("Level_0").unwrap();
+    assert_eq!(level.world_position, [256.0, 0.0]);
+    assert_eq!(level.fields["music"], "cave.ogg");
+    let types: Vec<LdtkLayerType> = level.layers.iter().map(|layer| layer.layer_type).collect();
+    assert_eq!(types, vec![LdtkLayerType::IntGrid, LdtkLayerType::Tiles, LdtkLayerType::Entities]);
+    assert_eq!(level.layers[0].int_grid, vec![0, 0, 2, 1, 1, 1]);
+    assert_eq!(level.layers[0].int_grid_names[&1], "Solid");
+    assert!(level.layers[1].tiles[3].flip_h && level.layers[1].tiles[3].flip_v);
+    assert_eq!(level.layers[2].entities[0].pivot, [0.5, 1.0]);
+}
+
+#[test]
+fn int_grid_values_become_hidden_colliders() {
+    let project = LdtkProject::from_ldtk(LDTK).unwrap();
+    let level = project.level("Level_0").unwrap();
+    let import = LdtkImport::new(16.0).collision("Water", TileCollision::Sensor);
+    let objects = project.game_objects(level, &import);
+
+    let Some(GraphicsType::Tilemap(collision)) = &objects[0].graphics else {
+        panic!("the IntGrid layer should be a tilemap");
+    };
+    assert!(!collision.visible);
+    assert_eq!(
+        collision.collision_rects(),
+        vec![
+            TileRect { collision: TileCollision::Sensor, x: 2, y: 0, width: 1, height: 1 },
+            TileRect { collision: TileCollision::Solid, x: 0, y: 1, width: 3, height: 1 },
+        ]
+    );
+}
+
+#[test]
+fn stacked_tiles_go_on_extra_tilemaps() {
+    let project = LdtkProject::from_ldtk(LDTK).unwrap();
+    let level = project.level("Level_0").unwrap();
+    let objects = project.game_objects(level, &LdtkImport::new(16.0).origin(-4.0, 2.0));
+
+    let tilemaps: Vec<_> = objects[1..3]
+        .iter()
+        .map(|object| match &object.graphics {
+            Some(GraphicsType::Tilemap(tilemap)) => tilemap,
+            _ => panic!("the tile layer should be tilemaps"),
+        })
+        .collect();
+    assert_eq!([objects[1].pos_x, objects[1].pos_y], [-4.0, 2.0]);
+    assert_eq!(tilemaps[0].atlas.as_deref(), Some("Terrain"));
+    assert_eq!(tilemaps[0].opacity, 0.5);
+    assert_eq!(tilemaps[0].get_tile(1, 0), Some(&Tile::new("2")));
+    assert_eq!(tilemaps[0].get_tile(2, 1), Some(&Tile::new("1").flip_h().flip_v()));
+    assert_eq!(tilemaps[1].get_tile(1, 0), Some(&Tile::new("4").flip_h()));
+    assert_eq!(tilemaps[1].get_tile(0, 0), None);
+    // Tiles tagged with a collision enum collide
+    assert_eq!(tilemaps[0].collision_at(0, 0), Some(TileCollision::OneWay));
+    assert_eq!(tilemaps[0].collision_at(2, 1), None);
+}
+
+#[test]
+fn entities_are_placed_on_their_centre_with_their_fields() {
+    let project = LdtkProject::from_ldtk(LDTK).unwrap();
+    let level = project.level("Level_0").unwrap();
+    let import = LdtkImport::new(16.0).prefab("Player", |_| GameObjectBuilder::new().scale(2.0, 2.0));
+    let objects = project.game_objects(level, &import);
+
+    let player = &objects[3];
+    assert_eq!([player.pos_x, player.pos_y], [0.5, 0.0]);
+    assert_eq!(player.scale_x, 2.0);
+    assert_eq!(player.properties["speed"].as_int(), Ok(3));
+    assert_eq!(player.properties["iid"].clone().into_string(), Ok("player-1".to_string()));
+    let spawn = player.properties["spawn"].clone().cast::<rhai::Map>();
+    assert_eq!(spawn["cx"].as_int(), Ok(2));
+
+    let crate_object = &objects[4];
+    assert_eq!([crate_object.pos_x, crate_object.pos_y], [2.5, -0.5]);
+    let Some(GraphicsType::Sprite(sprite)) = &crate_object.graphics else {
+        panic!("the crate should be a sprite");
+    };
+    assert_eq!((sprite.sprite_id.as_str(), sprite.atlas.as_deref()), ("3", Some("Terrain")));
+}
+
+#[test]
+fn malformed_projects_are_errors() {
+    assert!(matches!(LdtkProject::from_ldtk("{ \"levels\": "), Err(LdtkError::Parse(_))));
+    assert!(matches!(LdtkProject::load("missing.ldtk"), Err(LdtkError::Io(..))));
+
+    let external = LDTK.replace(r#""layerInstances": ["#, r#""externalRelPath": "Level_0.ldtkl", "unused": ["#);
+    assert!(matches!(LdtkProject::from_ldtk(&external), Err(LdtkError::ExternalLevel(level)) if level == "Level_0"));
+}
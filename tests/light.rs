@@ -0,0 +1,85 @@
+use alcubierre::light::{occluders, visibility_polygon, Light2D, Occluder};
+use alcubierre::physics::{AlcubierreCollider, AlcubierreColliderType};
+use alcubierre::ui::frontend::RGBColor;
+use rapier2d::geometry::ColliderSet;
+
+const WHITE: RGBColor = RGBColor { red: 255, green: 255, blue: 255, alpha: 255 };
+
+fn length([x, y]: [f32; 2]) -> f32 {
+    (x * x + y * y).sqrt()
+}
+
+fn collider(collider_type: AlcubierreColliderType, sensor: bool) -> AlcubierreCollider {
+    AlcubierreCollider { collider_type, sensor, restitution: 0.0, friction: 0.0 }
+}
+
+#[test]
+fn unblocked_light_is_round() {
+    let area = visibility_polygon([0.0, 0.0], 2.0, &[], None);
+    assert_eq!(area.len(), 32);
+    assert!(area.iter().all(|&point| (length(point) - 2.0).abs() < 1e-4));
+    // Counter-clockwise from the right
+    assert!(area[0][0] > 1.99 && area[1][1] > 0.0);
+}
+
+#[test]
+fn walls_stop_the_light() {
+    let wall = [[1.0, -1.0], [1.0, 1.0]];
+    let area = visibility_polygon([0.0, 0.0], 5.0, &[wall], None);
+
+    assert!(area.iter().filter(|&&[x, y]| y.abs() < x - 1e-2).all(|&[x, _]| x <= 1.0 + 1e-4));
+    // Light goes round the wall's ends
+    assert!(area.iter().any(|&point| point[1] > 1.0 && (length(point) - 5.0).abs() < 1e-3));
+    assert!(area.iter().any(|&point| point[1] < -1.0 && (length(point) - 5.0).abs() < 1e-3));
+    // Walls out of reach are ignored
+    let far = visibility_polygon([0.0, 0.0], 0.5, &[wall], None);
+    assert_eq!(far.len(), 32);
+}
+
+#[test]
+fn spot_lights_start_at_their_centre_and_turn_with_the_object() {
+    let spot = Light2D::spot(3.0, WHITE, 0.0, 90.0).offset(1.0, 0.0);
+    let area = spot.lit_area([2.0, 2.0], std::f32::consts::FRAC_PI_2, 0, &[]);
+
+    let center = spot.center([2.0, 2.0], std::f32::consts::FRAC_PI_2);
+    assert!((center[0] - 2.0).abs() < 1e-5 && (center[1] - 3.0).abs() < 1e-5);
+    assert_eq!(area[0], center);
+    // Facing up, 45 degrees either side
+    for &[x, y] in &area[1..] {
+        let [x, y] = [x - center[0], y - center[1]];
+        assert!(y >= x.abs() - 1e-4);
+    }
+}
+
+#[test]
+fn shadows_come_from_other_objects_colliders() {
+    let mut colliders = ColliderSet::new();
+    let mut wall = collider(AlcubierreColliderType::Rectangle((50.0, 200.0)), false).to_rapier(1);
+    wall.set_translation(rapier2d::math::Vector::new(2.0, 0.0));
+    colliders.insert(wall);
+    colliders.insert(collider(AlcubierreColliderType::Circle(25.0), false).to_rapier(0));
+    colliders.insert(collider(AlcubierreColliderType::Rectangle((400.0, 400.0)), true).to_rapier(2));
+
+    let edges = occluders(&colliders);
+    // The wall's four sides and the ball's sixteen, but no sensor
+    assert_eq!(edges.iter().filter(|edge| edge.owner == 1).count(), 4);
+    assert_eq!(edges.iter().filter(|edge| edge.owner == 0).count(), 16);
+    assert!(edges.iter().all(|edge| edge.owner != 2));
+    let near_side = Occluder { owner: 1, segment: [[75.0, 100.0], [75.0, -100.0]] };
+    assert!(edges.contains(&near_side));
+
+    let light = Light2D::point(200.0, WHITE);
+    let unlit = light.lit_area([0.0, 0.0], 0.0, 0, &edges);
+    let shadowed = light.clone().cast_shadows().lit_area([0.0, 0.0], 0.0, 0, &edges);
+    assert!(unlit.iter().any(|&[x, y]| x > 75.0 && y.abs() < 10.0));
+    // Straight past the wall is dark, and the owner's own ball doesn't block anything
+    assert!(shadowed.iter().all(|&[x, y]| x <= 75.0 + 1e-3 || y.abs() > 10.0));
+    assert!(shadowed.iter().all(|&point| length(point) > 25.0));
+}
+
+#[test]
+fn falloff_stays_above_zero() {
+    assert_eq!(Light2D::point(2.0, WHITE).falloff(0.0).falloff, 0.01);
+    assert_eq!(Light2D::point(2.0, WHITE).falloff(-3.0).falloff, 0.01);
+    assert_eq!(Light2D::point(2.0, WHITE).falloff(2.5).falloff, 2.5);
+}
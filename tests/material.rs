@@ -0,0 +1,29 @@
+use alcubierre::{Material, MaterialInstance};
+use pretty_assertions::assert_eq;
+
+const DISSOLVE: &str = "@fragment fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> { return in.color; }";
+
+#[test]
+fn instance_params_override_defaults_by_name() {
+    let material = Material::new("dissolve", DISSOLVE)
+        .param("amount", 0.0)
+        .param("edge", 0.1)
+        .texture("noise");
+    assert_eq!(material.texture.as_deref(), Some("noise"));
+
+    let instance = MaterialInstance::new("dissolve")
+        .param("edge", 0.3)
+        .param("unknown", 9.0);
+    let values = material.param_values(&instance.params);
+    assert_eq!(&values[..3], &[0.0, 0.3, 0.0]);
+}
+
+#[test]
+fn setting_a_param_keeps_its_slot() {
+    let mut instance = MaterialInstance::new("water").param("speed", 1.0).param("depth", 2.0);
+    instance.set_param("speed", 4.0);
+    assert_eq!(
+        instance.params,
+        vec![("speed".to_string(), 4.0), ("depth".to_string(), 2.0)]
+    );
+}
@@ -25,6 +25,7 @@ fn basic_rough_parse() {
                         red: 250,
                         green: 17,
                         blue: 234,
+                        alpha: 255,
                     },
                     margin_top: Pixels(2),
                     margin_bottom: Pixels(0),
@@ -43,11 +44,13 @@ fn basic_rough_parse() {
                         red: 255,
                         green: 255,
                         blue: 255,
+                        alpha: 255,
                     },
                     background_color: RGBColor {
                         red: 0,
                         green: 0,
                         blue: 0,
+                        alpha: 255,
                     },
                     margin_top: Pixels(0),
                     margin_bottom: Pixels(0),
@@ -69,6 +72,7 @@ fn basic_rough_parse() {
                         red: 255,
                         green: 255,
                         blue: 255,
+                        alpha: 255,
                     },
                     margin_top: Pixels(0),
                     margin_bottom: Pixels(0),
@@ -112,3 +116,21 @@ fn more_complex_rough_parse() {
 
     // fs::write("c2.json", st).expect("Unable to write file");
 }
+
+#[test]
+fn hex_colors_with_alpha() {
+    let ast = parse_ui_blob(r##"<text color="#ff000080">Faded</text>"##);
+
+    match &ast.elements[0] {
+        Text(text) => assert_eq!(
+            text.styles.color,
+            RGBColor {
+                red: 255,
+                green: 0,
+                blue: 0,
+                alpha: 128,
+            }
+        ),
+        _ => panic!("Expected a text element"),
+    }
+}
@@ -0,0 +1,90 @@
+use alcubierre::game_object::particles::ParticleEmitterData;
+use alcubierre::ui::frontend::RGBColor;
+use std::f32::consts::FRAC_PI_2;
+
+fn color(red: u8, green: u8, blue: u8, alpha: u8) -> RGBColor {
+    RGBColor { red, green, blue, alpha }
+}
+
+fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+    (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4
+}
+
+#[test]
+fn rate_emits_into_a_fixed_pool() {
+    let mut emitter = ParticleEmitterData::new(5).rate(10.0).lifetime(10.0, 10.0).seed(1);
+
+    emitter.update(0.25, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 2);
+    // The half particle left over is owed to the next frame
+    emitter.update(0.25, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 5);
+    emitter.update(1.0, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 5);
+    assert_eq!(emitter.max_particles(), 5);
+}
+
+#[test]
+fn bursts_emit_whether_or_not_the_emitter_is_running() {
+    let mut emitter = ParticleEmitterData::new(100).emitting(false).initial_burst(3).lifetime(10.0, 10.0).seed(2);
+    assert!(!emitter.is_emitting());
+
+    emitter.update(1.0, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 3);
+    emitter.burst(2);
+    emitter.update(0.0, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 5);
+
+    emitter.start();
+    emitter.update(0.5, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 10);
+    emitter.stop();
+    emitter.update(0.5, [0.0, 0.0], 0.0);
+    assert_eq!(emitter.particles().len(), 10);
+}
+
+#[test]
+fn particles_fall_and_expire() {
+    let mut emitter = ParticleEmitterData::new(10)
+        .emitting(false)
+        .initial_burst(1)
+        .angle(90.0, 90.0)
+        .speed(2.0, 2.0)
+        .gravity(0.0, -10.0)
+        .lifetime(1.0, 1.0);
+
+    emitter.update(0.0, [1.0, 1.0], 0.0);
+    let particle = emitter.particles()[0];
+    assert!(close(particle.position, [1.0, 1.0]));
+    assert!(close(particle.velocity, [0.0, 2.0]));
+
+    emitter.update(0.5, [5.0, 5.0], 0.0);
+    let particle = emitter.particles()[0];
+    assert!(close(particle.velocity, [0.0, -3.0]));
+    assert!(close(particle.position, [1.0, -0.5]));
+    assert_eq!(particle.progress(), 0.5);
+
+    emitter.update(0.6, [5.0, 5.0], 0.0);
+    assert!(emitter.particles().is_empty());
+}
+
+#[test]
+fn emission_turns_with_the_object() {
+    let mut emitter = ParticleEmitterData::new(10).emitting(false).initial_burst(1).angle(0.0, 0.0).speed(3.0, 3.0);
+    emitter.update(0.0, [0.0, 0.0], FRAC_PI_2);
+    assert!(close(emitter.particles()[0].velocity, [0.0, 3.0]));
+}
+
+#[test]
+fn colour_and_size_blend_over_lifetime() {
+    let emitter = ParticleEmitterData::new(1)
+        .colors(&[color(255, 0, 0, 255), color(0, 0, 255, 255), color(0, 0, 255, 0)])
+        .sizes(&[1.0, 0.0]);
+
+    assert_eq!(emitter.color_at(0.0), color(255, 0, 0, 255));
+    assert_eq!(emitter.color_at(0.25), color(128, 0, 128, 255));
+    assert_eq!(emitter.color_at(0.75), color(0, 0, 255, 128));
+    assert_eq!(emitter.color_at(1.0), color(0, 0, 255, 0));
+    assert_eq!(emitter.size_at(0.5), 0.5);
+    assert_eq!(emitter.size_at(2.0), 0.0);
+}
@@ -0,0 +1,33 @@
+use alcubierre::camera::PixelPerfect;
+
+#[test]
+fn scales_by_whole_pixels_with_centred_bars() {
+    let pixel = PixelPerfect::new(320, 180, 12.0);
+
+    // 1080x940 fits 3x horizontally and 5x vertically, so 3x wins
+    assert_eq!(pixel.scale([1080.0, 940.0]), 3.0);
+    assert_eq!(pixel.letterbox([1080.0, 940.0]), [60.0, 200.0, 960.0, 540.0]);
+    assert_eq!(pixel.letterbox([1280.0, 720.0]), [0.0, 0.0, 1280.0, 720.0]);
+
+    // Smaller than the virtual resolution shrinks to fit instead
+    assert_eq!(pixel.scale([160.0, 180.0]), 0.5);
+}
+
+#[test]
+fn window_and_virtual_positions_round_trip() {
+    let pixel = PixelPerfect::new(320, 180, 12.0);
+    let window = [1080.0, 940.0];
+
+    assert_eq!(pixel.window_to_virtual([60.0, 200.0], window), [0.0, 0.0]);
+    assert_eq!(pixel.window_to_virtual([540.0, 470.0], window), [160.0, 90.0]);
+    assert_eq!(pixel.virtual_to_window([160.0, 90.0], window), [540.0, 470.0]);
+}
+
+#[test]
+fn view_height_and_texels_follow_pixels_per_unit() {
+    let pixel = PixelPerfect::new(320, 180, 12.0);
+
+    assert_eq!(pixel.view_height(), 15.0);
+    assert_eq!(pixel.texel(1.0), 1.0 / 12.0);
+    assert_eq!(pixel.texel(2.0), 1.0 / 24.0);
+}
@@ -0,0 +1,45 @@
+use alcubierre::{PostEffect, PostPass, MAX_POST_PARAMS};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn built_in_passes_have_named_params() {
+    let vignette = PostPass::vignette();
+    assert_eq!(vignette.name, "vignette");
+    assert_eq!(vignette.effect, PostEffect::Vignette);
+    assert_eq!(vignette.get_param("intensity"), Some(0.5));
+    assert_eq!(vignette.get_param("strength"), None);
+    assert!(vignette.enabled);
+}
+
+#[test]
+fn params_keep_their_slot_when_changed() {
+    let mut pass = PostPass::custom("wobble", "// fs_main")
+        .param("speed", 1.0)
+        .param("amount", 0.1);
+    pass.set_param("speed", 3.0);
+    assert_eq!(
+        pass.params,
+        vec![("speed".to_string(), 3.0), ("amount".to_string(), 0.1)]
+    );
+}
+
+#[test]
+#[should_panic]
+fn too_many_params_panics() {
+    let mut pass = PostPass::custom("busy", "// fs_main");
+    for index in 0..=MAX_POST_PARAMS {
+        pass.set_param(&format!("p{}", index), 0.0);
+    }
+}
+
+#[test]
+fn try_set_param_refuses_new_params_when_full() {
+    let mut pass = PostPass::custom("busy", "// fs_main");
+    for index in 0..MAX_POST_PARAMS {
+        assert!(pass.try_set_param(&format!("p{}", index), 0.0));
+    }
+    assert!(!pass.try_set_param("extra", 1.0));
+    assert!(pass.try_set_param("p0", 2.0));
+    assert_eq!(pass.params.len(), MAX_POST_PARAMS);
+    assert_eq!(pass.get_param("p0"), Some(2.0));
+}
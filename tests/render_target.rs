@@ -0,0 +1,50 @@
+use alcubierre::camera::Camera2D;
+use alcubierre::ui::frontend::RGBColor;
+use alcubierre::{Engine, EngineConfig, RenderTarget};
+use pretty_assertions::assert_eq;
+use std::time::Duration;
+
+const RED: RGBColor = RGBColor {
+    red: 255,
+    green: 0,
+    blue: 0,
+    alpha: 255,
+};
+
+#[test]
+fn render_targets_start_transparent() {
+    let target = RenderTarget::new("minimap", 0, 128);
+    assert_eq!((target.width, target.height), (1, 128));
+    assert_eq!(target.clear_color.alpha, 0);
+    assert!(target.ui.is_none());
+
+    let target = target.clear_color(RED);
+    assert_eq!(target.clear_color, RED);
+}
+
+#[test]
+fn cameras_draw_into_named_targets() {
+    let camera = Camera2D::new().with_target("minimap");
+    assert_eq!(camera.target.as_deref(), Some("minimap"));
+    assert_eq!(Camera2D::new().target, None);
+}
+
+#[test]
+fn headless_frame_is_cleared_to_the_clear_color() {
+    let mut engine = Engine::new(64, 48, EngineConfig {
+        gravity: 0.0,
+        clear_color: RED,
+    });
+    // Machines without a GPU or software adapter can't draw anything
+    if !engine.init_headless(64, 48) {
+        return;
+    }
+    engine.register_scene("main".to_string());
+    engine.set_current_scene("main".to_string());
+
+    let frame = engine.render_headless_frame(Duration::from_millis(16));
+    assert_eq!(frame.dimensions(), (64, 48));
+    for pixel in frame.pixels() {
+        assert_eq!(pixel.0, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,38 @@
+use alcubierre::triangulate;
+use pretty_assertions::assert_eq;
+
+fn area(points: &[[f32; 2]], indices: &[u32]) -> f32 {
+    indices
+        .chunks(3)
+        .map(|t| {
+            let (a, b, c) = (points[t[0] as usize], points[t[1] as usize], points[t[2] as usize]);
+            ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) * 0.5
+        })
+        .sum()
+}
+
+#[test]
+fn triangulates_convex_polygons() {
+    let square = [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]];
+    let indices = triangulate(&square);
+    assert_eq!(indices.len(), 6);
+    assert_eq!(area(&square, &indices), 4.0);
+}
+
+#[test]
+fn triangulates_concave_polygons_in_either_winding() {
+    // An L shape, 3 squares in area
+    let mut shape = vec![[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [1.0, 1.0], [1.0, 2.0], [0.0, 2.0]];
+    for _ in 0..2 {
+        let indices = triangulate(&shape);
+        assert_eq!(indices.len(), (shape.len() - 2) * 3);
+        // Every triangle comes out counter-clockwise, so none would be culled
+        assert_eq!(area(&shape, &indices), 3.0);
+        shape.reverse();
+    }
+}
+
+#[test]
+fn too_few_points_give_no_triangles() {
+    assert_eq!(triangulate(&[[0.0, 0.0], [1.0, 0.0]]), Vec::<u32>::new());
+}
@@ -0,0 +1,77 @@
+use alcubierre::game_object::graphics::SpriteData;
+use alcubierre::ui::frontend::RGBColor;
+use alcubierre::{AtlasPackOptions, SpriteAtlas, SpriteVertex};
+use image::{DynamicImage, Rgba, RgbaImage};
+use pretty_assertions::assert_eq;
+
+/// A 32 by 32 frame whose pixels are 6 wide and 12 tall, 4 from the left and 8 from the top.
+fn trimmed_atlas() -> SpriteAtlas {
+    let mut image = RgbaImage::new(32, 32);
+    for x in 4..10 {
+        for y in 8..20 {
+            image.put_pixel(x, y, Rgba([0, 255, 0, 255]));
+        }
+    }
+    let options = AtlasPackOptions {
+        trim: true,
+        ..AtlasPackOptions::default()
+    };
+    SpriteAtlas::pack_from_images(vec![("leaf".to_string(), DynamicImage::ImageRgba8(image))], &options)
+}
+
+fn positions(vertices: &[SpriteVertex; 4]) -> Vec<[f32; 2]> {
+    vertices.iter().map(|vertex| [vertex.position[0], vertex.position[1]]).collect()
+}
+
+#[test]
+fn trimmed_sprites_sit_where_they_were_in_the_frame_around_the_pivot() {
+    let atlas = trimmed_atlas();
+    let sprite = atlas.sprite("leaf").unwrap();
+
+    // Two world units for the 32 pixel frame, pivoted a quarter across and three quarters up
+    let (vertices, indices) = atlas.get_sprite_from_atlas(sprite, [10.0, 20.0], [2.0, 2.0], Some([0.25, 0.75]), false, false);
+    assert_eq!(
+        positions(&vertices),
+        vec![[9.75, 19.25], [10.125, 19.25], [10.125, 20.0], [9.75, 20.0]]
+    );
+    assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+    // Only the trimmed pixels are sampled
+    let [u1, v1] = vertices[0].tex_coords;
+    let [u2, v2] = vertices[2].tex_coords;
+    assert_eq!(u1, sprite.position.x as f32 / atlas.width as f32);
+    assert_eq!(u2 - u1, 6.0 / atlas.width as f32);
+    assert_eq!(v1 - v2, 12.0 / atlas.height as f32);
+
+    // Flipping mirrors the pixels and the pivot across the frame
+    let (flipped, _) = atlas.get_sprite_from_atlas(sprite, [10.0, 20.0], [2.0, 2.0], Some([0.25, 0.75]), true, false);
+    assert_eq!(
+        positions(&flipped),
+        vec![[9.875, 19.25], [10.25, 19.25], [10.25, 20.0], [9.875, 20.0]]
+    );
+    assert_eq!(flipped[0].tex_coords[0], u2);
+}
+
+#[test]
+fn tint_opacity_and_flash_colour_every_vertex() {
+    let atlas = trimmed_atlas();
+    let (mut vertices, _) = atlas.get_sprite_from_atlas(atlas.sprite("leaf").unwrap(), [0.0, 0.0], [1.0, 1.0], None, false, false);
+    let mut sprite = SpriteData::new("leaf", 1.0, 1.0)
+        .tint(RGBColor { red: 255, green: 188, blue: 0, alpha: 128 })
+        .opacity(0.5);
+    sprite.flash = 0.25;
+
+    sprite.color_vertices(&mut vertices);
+    for vertex in &vertices {
+        // The tint is made linear, about half for sRGB 188, and its alpha faded by the opacity
+        let expected = [1.0, 0.5029, 0.0, 0.5 * 128.0 / 255.0];
+        assert!(vertex.color.iter().zip(expected).all(|(channel, expected)| (channel - expected).abs() < 1e-3));
+        assert_eq!(vertex.flash, 0.25);
+    }
+
+    // Opacity and flash past 1 are clamped
+    sprite.tint.alpha = 255;
+    sprite.opacity = 3.0;
+    sprite.flash = 2.0;
+    sprite.color_vertices(&mut vertices);
+    assert!(vertices.iter().all(|vertex| vertex.color[3] == 1.0 && vertex.flash == 1.0));
+}
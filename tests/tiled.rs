@@ -0,0 +1,190 @@
+use alcubierre::game_object::graphics::GraphicsType;
+use alcubierre::game_object::tilemap::{Tile, TileCollision, TileRect};
+use alcubierre::game_object::GameObjectBuilder;
+use alcubierre::physics::AlcubierreColliderType;
+use alcubierre::tiled::{TiledError, TiledImport, TiledLayer, TiledMap, TiledProperty, TiledShape};
+
+const TMJ: &str = r#"{
+    "width": 3, "height": 2, "tilewidth": 16, "tileheight": 16,
+    "orientation": "orthogonal", "infinite": false,
+    "properties": [{ "name": "music", "type": "string", "value": "cave.ogg" }],
+    "tilesets": [{
+        "firstgid": 1, "name": "terrain", "image": "terrain.png", "imagewidth": 64, "imageheight": 32,
+        "tilewidth": 16, "tileheight": 16, "columns": 4, "tilecount": 8, "margin": 0, "spacing": 0,
+        "tiles": [
+            { "id": 0, "properties": [{ "name": "collision", "type": "string", "value": "solid" }] },
+            { "id": 1, "objectgroup": { "objects": [{ "id": 1, "x": 0, "y": 0, "width": 16, "height": 16 }] } },
+            { "id": 2, "properties": [{ "name": "collision", "type": "string", "value": "one_way" }] }
+        ]
+    }],
+    "layers": [
+        { "type": "tilelayer", "name": "ground", "width": 3, "height": 2, "data": [0, 3, 0, 1, 2, 2147483650] },
+        { "type": "objectgroup", "name": "things", "objects": [
+            { "id": 1, "name": "spawn", "type": "player", "x": 8, "y": 8, "point": true,
+              "properties": [{ "name": "speed", "type": "int", "value": 3 }] },
+            { "id": 2, "name": "wall", "x": 16, "y": 0, "width": 32, "height": 16,
+              "properties": [{ "name": "sensor", "type": "bool", "value": true }] },
+            { "id": 3, "name": "ramp", "x": 0, "y": 32, "polygon": [{ "x": 0, "y": 0 }, { "x": 16, "y": 0 }, { "x": 8, "y": -8 }] },
+            { "id": 4, "name": "crate", "gid": 4, "x": 0, "y": 32, "width": 16, "height": 16 }
+        ] }
+    ]
+}"#;
+
+const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="music" value="cave.ogg"/>
+ </properties>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="8" columns="4">
+  <image source="terrain.png" width="64" height="32"/>
+  <tile id="0">
+   <properties>
+    <property name="collision" value="solid"/>
+   </properties>
+  </tile>
+  <tile id="1">
+   <objectgroup draworder="index">
+    <object id="1" x="0" y="0" width="16" height="16"/>
+   </objectgroup>
+  </tile>
+  <tile id="2">
+   <properties>
+    <property name="collision" value="one_way"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" width="3" height="2">
+  <data encoding="csv">
+0,3,0,
+1,2,2147483650
+</data>
+ </layer>
+ <!-- Things to spawn -->
+ <objectgroup id="2" name="things">
+  <object id="1" name="spawn" type="player" x="8" y="8">
+   <properties>
+    <property name="speed" type="int" value="3"/>
+   </properties>
+   <point/>
+  </object>
+  <object id="2" name="wall" x="16" y="0" width="32" height="16">
+   <properties>
+    <property name="sensor" type="bool" value="true"/>
+   </properties>
+  </object>
+  <object id="3" name="ramp" x="0" y="32">
+   <polygon points="0,0 16,0 8,-8"/>
+  </object>
+  <object id="4" name="crate" gid="4" x="0" y="32" width="16" height="16"/>
+ </objectgroup>
+</map>
+"#;
+
+#[test]
+fn tmj_and_tmx_describe_the_same_map() {
+    let map = TiledMap::from_tmj(TMJ).unwrap();
+
+    assert_eq!(map, TiledMap::from_tmx(TMX).unwrap());
+    assert_eq!((map.width, map.height, map.tile_width, map.tile_height), (3, 2, 16, 16));
+    assert_eq!(map.properties["music"], TiledProperty::String("cave.ogg".to_string()));
+
+    let TiledLayer::Tiles(ground) = &map.layers[0] else {
+        panic!("ground should be a tile layer");
+    };
+    assert_eq!(ground.data, vec![0, 3, 0, 1, 2, 2147483650]);
+    let TiledLayer::Objects(things) = &map.layers[1] else {
+        panic!("things should be an object layer");
+    };
+    assert_eq!(things.objects[0].class, "player");
+    assert_eq!(things.objects[0].shape, TiledShape::Point);
+    assert_eq!(things.objects[0].properties["speed"], TiledProperty::Int(3));
+    assert_eq!(things.objects[2].shape, TiledShape::Polygon(vec![[0.0, 0.0], [16.0, 0.0], [8.0, -8.0]]));
+    assert_eq!(things.objects[3].gid, Some(4));
+
+    let (tileset, index) = map.tileset_for(2147483650).unwrap();
+    assert_eq!((tileset.name.as_str(), index), ("terrain", 1));
+    assert_eq!(tileset.collision(0), Some(TileCollision::Solid));
+    assert_eq!(tileset.collision(1), Some(TileCollision::Solid));
+    assert_eq!(tileset.collision(2), Some(TileCollision::OneWay));
+    assert_eq!(tileset.collision(3), None);
+}
+
+#[test]
+fn base64_layers_decode_to_gids() {
+    let tmj = TMJ.replace(
+        r#""data": [0, 3, 0, 1, 2, 2147483650]"#,
+        r#""encoding": "base64", "data": "AAAAAAMAAAAAAAAAAQAAAAIAAAACAACA""#,
+    );
+    assert_eq!(TiledMap::from_tmj(&tmj).unwrap(), TiledMap::from_tmj(TMJ).unwrap());
+}
+
+#[test]
+fn malformed_and_unsupported_maps_are_errors() {
+    assert!(matches!(TiledMap::from_tmj("{ \"width\": "), Err(TiledError::Parse(_))));
+    assert!(matches!(TiledMap::from_tmx("<map><layer>"), Err(TiledError::Parse(_))));
+    assert!(matches!(TiledMap::load("missing.tmj"), Err(TiledError::Io(..))));
+
+    let csv = TMJ.replace(r#""data": [0, 3, 0, 1, 2, 2147483650]"#, r#""encoding": "csv", "data": "0,3,x,1,2,0""#);
+    assert!(matches!(TiledMap::from_tmj(&csv), Err(TiledError::Parse(_))));
+    let compressed = TMJ.replace(r#""data": [0"#, r#""compression": "zlib", "data": [0"#);
+    assert!(matches!(TiledMap::from_tmj(&compressed), Err(TiledError::Unsupported(_))));
+    let infinite = TMJ.replace(r#""infinite": false"#, r#""infinite": true"#);
+    assert!(matches!(TiledMap::from_tmj(&infinite), Err(TiledError::Unsupported(_))));
+}
+
+#[test]
+fn tile_layers_become_tilemaps_with_colliders() {
+    let map = TiledMap::from_tmj(TMJ).unwrap();
+    let objects = map.game_objects(&TiledImport::new(16.0));
+
+    let Some(GraphicsType::Tilemap(ground)) = &objects[0].graphics else {
+        panic!("ground should be a tilemap");
+    };
+    assert_eq!([objects[0].pos_x, objects[0].pos_y], [0.0, 0.0]);
+    assert_eq!(ground.atlas.as_deref(), Some("terrain"));
+    assert_eq!((ground.tile_width, ground.tile_height), (1.0, 1.0));
+    assert_eq!(ground.get_tile(1, 0), Some(&Tile::new("2")));
+    assert_eq!(ground.get_tile(2, 1), Some(&Tile::new("1").flip_h()));
+    assert_eq!(
+        ground.collision_rects(),
+        vec![
+            TileRect { collision: TileCollision::OneWay, x: 1, y: 0, width: 1, height: 1 },
+            TileRect { collision: TileCollision::Solid, x: 0, y: 1, width: 3, height: 1 },
+        ]
+    );
+}
+
+#[test]
+fn objects_are_placed_in_world_units_with_their_properties() {
+    let map = TiledMap::from_tmj(TMJ).unwrap();
+    let import = TiledImport::new(16.0)
+        .origin(10.0, 0.0)
+        .prefab("player", |_| GameObjectBuilder::new().scale(2.0, 2.0));
+    let objects = map.game_objects(&import);
+
+    let spawn = &objects[1];
+    assert_eq!([spawn.pos_x, spawn.pos_y], [10.5, -0.5]);
+    assert_eq!(spawn.scale_x, 2.0);
+    assert_eq!(spawn.properties["speed"].as_int(), Ok(3));
+
+    let wall = &objects[2];
+    assert_eq!([wall.pos_x, wall.pos_y], [12.0, -0.5]);
+    let collider = wall.pre_rapier_collider.as_ref().unwrap();
+    assert!(matches!(collider.collider_type, AlcubierreColliderType::Rectangle((width, height)) if width == 2.0 && height == 1.0));
+    assert!(collider.sensor);
+    let body = wall.rigid_body.as_ref().unwrap();
+    assert_eq!([body.translation().x, body.translation().y], [12.0 / 50.0, -0.5 / 50.0]);
+
+    let ramp = &objects[3];
+    let collider = ramp.pre_rapier_collider.as_ref().unwrap();
+    assert!(matches!(&collider.collider_type, AlcubierreColliderType::Polygon(points) if points[2] == [0.5, 0.5]));
+
+    // Tile objects sit on their bottom-left corner
+    let crate_object = &objects[4];
+    assert_eq!([crate_object.pos_x, crate_object.pos_y], [10.5, -1.5]);
+    let Some(GraphicsType::Sprite(sprite)) = &crate_object.graphics else {
+        panic!("crate should be a sprite");
+    };
+    assert_eq!((sprite.sprite_id.as_str(), sprite.atlas.as_deref()), ("3", Some("terrain")));
+    assert!(crate_object.pre_rapier_collider.is_none());
+}
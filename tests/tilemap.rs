@@ -0,0 +1,87 @@
+use alcubierre::game_object::tilemap::{Tile, TileCollision, TileRect, TilemapData};
+
+fn rect(collision: TileCollision, x: u32, y: u32, width: u32, height: u32) -> TileRect {
+    TileRect {
+        collision,
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn solid_tiles_merge_into_rows_then_blocks() {
+    // ....
+    // ##..
+    // ####
+    // ####
+    let tilemap = TilemapData::new(4, 4, 1.0, 1.0)
+        .tiles(&[
+            None, None, None, None,
+            Some("wall"), Some("wall"), None, None,
+            Some("wall"), Some("wall"), Some("wall"), Some("wall"),
+            Some("wall"), Some("wall"), Some("wall"), Some("wall"),
+        ])
+        .collision("wall", TileCollision::Solid);
+
+    assert_eq!(
+        tilemap.collision_rects(),
+        vec![
+            rect(TileCollision::Solid, 0, 1, 2, 1),
+            rect(TileCollision::Solid, 0, 2, 4, 2),
+        ]
+    );
+}
+
+#[test]
+fn one_way_tiles_only_merge_along_rows() {
+    let tilemap = TilemapData::new(3, 2, 1.0, 1.0)
+        .tiles(&[
+            Some("ledge"), Some("ledge"), Some("water"),
+            Some("ledge"), Some("ledge"), Some("water"),
+        ])
+        .collision("ledge", TileCollision::OneWay)
+        .collision("water", TileCollision::Sensor);
+
+    assert_eq!(
+        tilemap.collision_rects(),
+        vec![
+            rect(TileCollision::OneWay, 0, 0, 2, 1),
+            rect(TileCollision::Sensor, 2, 0, 1, 2),
+            rect(TileCollision::OneWay, 0, 1, 2, 1),
+        ]
+    );
+}
+
+#[test]
+fn setting_tiles_updates_collision() {
+    let mut tilemap = TilemapData::new(3, 1, 1.0, 1.0).collision("wall", TileCollision::Solid);
+    assert!(tilemap.collision_rects().is_empty());
+
+    tilemap.set_tile(0, 0, Tile::new("wall"));
+    tilemap.set_tile(2, 0, Tile::new("wall"));
+    assert_eq!(tilemap.get_tile(2, 0), Some(&Tile::new("wall")));
+    assert_eq!(tilemap.collision_rects().len(), 2);
+
+    tilemap.set_tile(1, 0, Tile::new("wall"));
+    assert_eq!(tilemap.collision_rects(), vec![rect(TileCollision::Solid, 0, 0, 3, 1)]);
+
+    tilemap.clear_tile(0, 0);
+    assert_eq!(tilemap.get_tile(0, 0), None);
+    assert_eq!(tilemap.collision_rects(), vec![rect(TileCollision::Solid, 1, 0, 2, 1)]);
+
+    // Outside the map
+    tilemap.set_tile(3, 0, Tile::new("wall"));
+    assert_eq!(tilemap.get_tile(3, 0), None);
+}
+
+#[test]
+fn positions_map_to_tiles_from_the_top_left() {
+    let tilemap = TilemapData::new(4, 3, 2.0, 1.0);
+
+    assert_eq!(tilemap.tile_at([0.5, -0.5]), Some((0, 0)));
+    assert_eq!(tilemap.tile_at([7.9, -2.9]), Some((3, 2)));
+    assert_eq!(tilemap.tile_at([1.0, 0.5]), None);
+    assert_eq!(tilemap.tile_center(1, 2), [3.0, -2.5]);
+}
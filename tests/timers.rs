@@ -0,0 +1,193 @@
+use alcubierre::game_object::behaviours::{EngineView, UserBehaviour};
+use alcubierre::game_object::{GameObjectBuilder, GameObjectIPC, GameObjectView};
+use alcubierre::physics::{AlcubierreCollider, AlcubierreColliderType};
+use alcubierre::timers::{TimerAction, TimerHandle};
+use alcubierre::ui::frontend::RGBColor;
+use alcubierre::{Engine, EngineConfig};
+use pretty_assertions::assert_eq;
+use rapier2d::prelude::RigidBodyBuilder;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Starts a timer when loaded that counts how often it fires, and cancels it once asked to.
+#[derive(Clone, Default)]
+struct Ticker {
+    seconds: f32,
+    repeating: bool,
+    fired: Rc<Cell<u32>>,
+    cancel: Rc<Cell<bool>>,
+    handle: Option<TimerHandle>,
+}
+
+impl UserBehaviour for Ticker {
+    fn game_loop(&mut self, _game_object_view: GameObjectView, engine_view: EngineView) {
+        if self.cancel.get() {
+            if let Some(handle) = self.handle.take() {
+                engine_view.cancel_timer(handle);
+            }
+        }
+    }
+    fn loaded(&mut self, engine_view: EngineView, game_object_view: GameObjectView) {
+        let fired = self.fired.clone();
+        let action = TimerAction::callback(move |_| fired.set(fired.get() + 1));
+        self.handle = Some(match self.repeating {
+            true => engine_view.every(game_object_view.id, self.seconds, action),
+            false => engine_view.after(game_object_view.id, self.seconds, action),
+        });
+    }
+}
+
+/// Keeps the numbers published on the `step` topic.
+#[derive(Clone, Default)]
+struct Recorder {
+    steps: Rc<RefCell<Vec<i64>>>,
+}
+
+impl UserBehaviour for Recorder {
+    fn game_loop(&mut self, _game_object_view: GameObjectView, _engine_view: EngineView) {}
+    fn loaded(&mut self, engine_view: EngineView, game_object_view: GameObjectView) {
+        engine_view.subscribe(game_object_view.id, "step");
+    }
+    fn received_event(&mut self, event: &GameObjectIPC, _engine_view: EngineView, _game_object_view: GameObjectView) {
+        if let GameObjectIPC::UserEvent(event) = event {
+            self.steps.borrow_mut().push(event.data().as_i64().unwrap());
+        }
+    }
+}
+
+fn engine_with(objects: Vec<GameObjectBuilder>) -> Engine {
+    let mut engine = Engine::new(64, 48, EngineConfig {
+        gravity: 0.0,
+        clear_color: RGBColor { red: 0, green: 0, blue: 0, alpha: 255 },
+    });
+    let scene = engine.register_scene("main".to_string());
+    for object in objects {
+        scene.register_game_object(object);
+    }
+    engine.set_current_scene("main".to_string());
+    // Timers and coroutines asked for while loading start at the end of the first frame
+    engine.step(Duration::ZERO);
+    engine
+}
+
+fn ticker(seconds: f32, repeating: bool) -> (Engine, Ticker) {
+    let ticker = Ticker { seconds, repeating, ..Ticker::default() };
+    let engine = engine_with(vec![GameObjectBuilder::new().user_behaviour(ticker.clone())]);
+    (engine, ticker)
+}
+
+fn seconds(seconds: f32) -> Duration {
+    Duration::from_secs_f32(seconds)
+}
+
+#[test]
+fn one_shot_timers_fire_once() {
+    let (mut engine, ticker) = ticker(0.5, false);
+    engine.step(seconds(0.25));
+    assert_eq!(ticker.fired.get(), 0);
+    engine.step(seconds(0.25));
+    assert_eq!(ticker.fired.get(), 1);
+    engine.step(seconds(1.0));
+    assert_eq!(ticker.fired.get(), 1);
+}
+
+#[test]
+fn repeating_timers_fire_once_per_frame_and_keep_their_phase() {
+    let (mut engine, ticker) = ticker(0.25, true);
+    // Three and a half intervals in one frame
+    engine.step(seconds(0.875));
+    assert_eq!(ticker.fired.get(), 1);
+    // The overshoot carries over, leaving an eighth of a second to the next one
+    engine.step(seconds(0.0625));
+    assert_eq!(ticker.fired.get(), 1);
+    engine.step(seconds(0.0625));
+    assert_eq!(ticker.fired.get(), 2);
+    engine.step(seconds(0.25));
+    assert_eq!(ticker.fired.get(), 3);
+}
+
+#[test]
+fn cancelled_timers_stop_firing() {
+    let (mut engine, ticker) = ticker(0.5, true);
+    engine.step(seconds(0.5));
+    assert_eq!(ticker.fired.get(), 1);
+    ticker.cancel.set(true);
+    engine.step(seconds(0.25));
+    engine.step(seconds(2.0));
+    assert_eq!(ticker.fired.get(), 1);
+}
+
+#[test]
+fn timers_wait_while_paused_and_follow_the_time_scale() {
+    let (mut engine, ticker) = ticker(1.0, true);
+    engine.set_paused(true);
+    engine.step(seconds(5.0));
+    assert_eq!(ticker.fired.get(), 0);
+
+    engine.set_paused(false);
+    engine.set_time_scale(0.5);
+    engine.step(seconds(1.0));
+    assert_eq!(ticker.fired.get(), 0);
+    engine.step(seconds(1.0));
+    assert_eq!(ticker.fired.get(), 1);
+
+    engine.set_time_scale(2.0);
+    engine.step(seconds(0.5));
+    assert_eq!(ticker.fired.get(), 2);
+}
+
+/// Scripts need a body to run their behaviour
+fn script(path: &str) -> GameObjectBuilder {
+    GameObjectBuilder::new()
+        .behaviour(path)
+        .collider(AlcubierreCollider {
+            collider_type: AlcubierreColliderType::Circle(1.0),
+            sensor: true,
+            restitution: 0.0,
+            friction: 0.0,
+        })
+        .rigid_body(RigidBodyBuilder::fixed().build())
+}
+
+#[test]
+fn coroutines_resume_after_their_last_wait() {
+    let recorder = Recorder::default();
+    let script = script("tests/coroutine.rhai");
+    let mut engine = engine_with(vec![script, GameObjectBuilder::new().user_behaviour(recorder.clone())]);
+
+    // Events arrive the frame after they're published
+    let mut steps = vec![];
+    for _ in 0..6 {
+        engine.step(seconds(0.25));
+        steps.push(recorder.steps.borrow().clone());
+    }
+    assert_eq!(
+        steps,
+        vec![
+            vec![],
+            // Waiting half a second
+            vec![1],
+            vec![1],
+            // Yielded for a frame
+            vec![1, 2],
+            vec![1, 2, 3],
+            vec![1, 2, 3],
+        ]
+    );
+}
+
+#[test]
+fn coroutines_cancel_timers_they_started_before_a_wait() {
+    let recorder = Recorder::default();
+    let script = script("tests/timer_coroutine.rhai");
+    let mut engine = engine_with(vec![script, GameObjectBuilder::new().user_behaviour(recorder.clone())]);
+
+    let mut rings = vec![];
+    for _ in 0..8 {
+        engine.step(seconds(0.25));
+        rings.push(recorder.steps.borrow().len());
+    }
+    // Rings every quarter second until the coroutine wakes from its wait and cancels the bell
+    assert_eq!(rings, vec![0, 0, 1, 2, 3, 3, 3, 3]);
+}
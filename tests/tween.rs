@@ -0,0 +1,82 @@
+use alcubierre::tween::{Easing, LoopMode, TweenBuilder, TweenTarget, UiStyleProperty};
+use pretty_assertions::assert_eq;
+
+const EASINGS: [&str; 31] = [
+    "linear",
+    "ease_in_sine",
+    "ease_out_sine",
+    "ease_in_out_sine",
+    "ease_in_quad",
+    "ease_out_quad",
+    "ease_in_out_quad",
+    "ease_in_cubic",
+    "ease_out_cubic",
+    "ease_in_out_cubic",
+    "ease_in_quart",
+    "ease_out_quart",
+    "ease_in_out_quart",
+    "ease_in_quint",
+    "ease_out_quint",
+    "ease_in_out_quint",
+    "ease_in_expo",
+    "ease_out_expo",
+    "ease_in_out_expo",
+    "ease_in_circ",
+    "ease_out_circ",
+    "ease_in_out_circ",
+    "ease_in_back",
+    "ease_out_back",
+    "ease_in_out_back",
+    "ease_in_elastic",
+    "ease_out_elastic",
+    "ease_in_out_elastic",
+    "ease_in_bounce",
+    "ease_out_bounce",
+    "ease_in_out_bounce",
+];
+
+#[test]
+fn easings_start_and_end_on_endpoints() {
+    for name in EASINGS {
+        let easing = Easing::from_name(name).unwrap();
+        assert!(easing.apply(0.0).abs() < 1e-4, "{} at 0", name);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-4, "{} at 1", name);
+    }
+}
+
+#[test]
+fn in_out_easings_are_symmetric() {
+    for name in EASINGS.iter().filter(|name| name.starts_with("ease_in_out")) {
+        let easing = Easing::from_name(name).unwrap();
+        assert!((easing.apply(0.5) - 0.5).abs() < 1e-4, "{} at 0.5", name);
+    }
+}
+
+#[test]
+fn names_resolve() {
+    assert_eq!(Easing::from_name("ease_out_quad"), Some(Easing::QuadOut));
+    assert_eq!(Easing::from_name("wobble"), None);
+    assert_eq!(LoopMode::from_name("ping_pong"), Some(LoopMode::PingPong));
+}
+
+#[test]
+fn targets_know_how_many_values_they_take() {
+    assert_eq!(TweenTarget::Position(0).arity(), 2..=2);
+    assert_eq!(TweenTarget::Color(0).arity(), 3..=4);
+    assert_eq!(TweenTarget::DataMap("score".to_string()).arity(), 1..=1);
+    let background = TweenTarget::UiStyle { class: "hud".to_string(), property: UiStyleProperty::BackgroundColor };
+    assert_eq!(background.arity(), 3..=4);
+    TweenBuilder::new(TweenTarget::Color(0)).to(&[255.0, 0.0, 0.0]).build();
+}
+
+#[test]
+#[should_panic]
+fn tweens_without_enough_values_panic_when_built() {
+    TweenBuilder::new(TweenTarget::Position(0)).to(&[5.0]).build();
+}
+
+#[test]
+#[should_panic]
+fn tweens_without_a_to_value_panic_when_built() {
+    TweenBuilder::new(TweenTarget::Rotation(0)).build();
+}